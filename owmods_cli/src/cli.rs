@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::{command, Parser, Subcommand, ValueHint};
+use clap::{command, Args, Parser, Subcommand, ValueEnum, ValueHint};
 use clap_complete::Shell;
 
 #[derive(Parser)]
@@ -78,6 +78,18 @@ pub enum Commands {
     Enable {
         #[arg(help = "The unique name of the mod to enable", value_hint = ValueHint::Other)]
         unique_name: String,
+        #[arg(
+            short = 'c',
+            long = "disable-conflicting",
+            help = "Disable mods that conflict with this one without asking"
+        )]
+        disable_conflicting: bool,
+        #[arg(
+            short = 'f',
+            long = "force",
+            help = "Enable the mod even if it conflicts with other enabled mods"
+        )]
+        force: bool,
     },
     #[command(
         about = "Disable a mod (use -r to disable dependencies too)",
@@ -128,13 +140,80 @@ pub enum Commands {
         #[arg(help = "The unique name of the mod to uninstall", value_hint = ValueHint::Other)]
         unique_name: String,
     },
-    #[command(about = "Export enabled mods to stdout as JSON")]
-    Export,
     #[command(
-        about = "Import mods from a .json file (installs if not there, enables if already installed)"
+        about = "Uninstall mods that were installed as dependencies but aren't needed anymore"
+    )]
+    Autoremove {
+        #[arg(
+            help = "Don't uninstall anything, just list mods that would be removed",
+            short = 'd',
+            long = "dry-run"
+        )]
+        dry: bool,
+    },
+    #[command(
+        about = "Find leftovers in the Mods folder (orphaned folders, duplicate mods, stray archives) and move them to the trash"
+    )]
+    Clean {
+        #[arg(
+            help = "Don't remove anything, just list what was found",
+            short = 'd',
+            long = "dry-run"
+        )]
+        dry: bool,
+    },
+    #[command(
+        about = "List, restore, or empty uninstalled mods and cleaned up leftovers kept in the trash"
+    )]
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+    #[command(about = "Show the dependency tree of installed mods")]
+    Tree {
+        #[arg(help = "The unique name of the mod to show the tree of (shows all mods if not passed)", value_hint = ValueHint::Other)]
+        unique_name: Option<String>,
+        #[arg(
+            long = "reverse",
+            help = "Show mods that depend on the mod instead of its dependencies (needs a mod for the dot and json formats)"
+        )]
+        reverse: bool,
+        #[arg(
+            short = 'f',
+            long = "format",
+            help = "The format to output the tree in",
+            value_enum,
+            default_value = "tree"
+        )]
+        format: GraphFormat,
+    },
+    #[command(about = "View or change the settings of a mod")]
+    Config {
+        #[arg(help = "The unique name of the mod to change the settings of", value_hint = ValueHint::Other)]
+        unique_name: String,
+        #[command(subcommand)]
+        action: ModConfigAction,
+    },
+    #[command(about = "Export enabled mods to stdout as JSON or a share code")]
+    Export {
+        #[arg(
+            short = 's',
+            long = "share-code",
+            help = "Export a short share code instead of JSON, this can be imported or opened as an owmods://import/ link"
+        )]
+        share_code: bool,
+        #[arg(
+            short = 'v',
+            long = "versions",
+            help = "Include the installed version of each mod"
+        )]
+        include_versions: bool,
+    },
+    #[command(
+        about = "Import mods from a .json file or share code (installs if not there, enables if already installed)"
     )]
     Import {
-        #[arg(help = "The path to the JSON file to import mods from", value_hint = ValueHint::FilePath)]
+        #[arg(help = "The path to the JSON file or the share code to import mods from", value_hint = ValueHint::FilePath)]
         file_path: PathBuf,
         #[arg(
             short = 'd',
@@ -142,6 +221,39 @@ pub enum Commands {
             help = "Disable mods that aren't present in the file"
         )]
         disable_missing: bool,
+        #[arg(
+            long = "dry-run",
+            help = "Don't change anything, just show what importing would do"
+        )]
+        dry: bool,
+    },
+    #[command(
+        about = "Adopt mods and their settings from another OWML install or Mods folder (keeps enabled states)"
+    )]
+    Migrate {
+        #[arg(help = "The path to the OWML or Mods folder to migrate from", value_hint = ValueHint::DirPath)]
+        path: PathBuf,
+        #[arg(
+            short = 'm',
+            long = "move",
+            help = "Move the mods instead of copying them"
+        )]
+        move_mods: bool,
+        #[arg(long = "replace", help = "Replace mods that are already installed")]
+        replace: bool,
+        #[arg(
+            short = 'd',
+            long = "dry-run",
+            help = "Don't adopt anything, just list the mods that were found"
+        )]
+        dry: bool,
+    },
+    #[command(
+        about = "Export or import a modpack (an archive of mods and their settings that can be imported offline)"
+    )]
+    Pack {
+        #[command(subcommand)]
+        action: PackAction,
     },
     #[command(about = "Run the game")]
     Run {
@@ -171,6 +283,10 @@ pub enum Commands {
             value_hint = ValueHint::Other
         )]
         port: u16,
+        #[command(flatten)]
+        filter: LogFilterArgs,
+        #[command(flatten)]
+        output: LogOutputArgs,
     },
     #[command(about = "Run a server to listen for log messages on")]
     LogServer {
@@ -182,6 +298,24 @@ pub enum Commands {
             value_hint = ValueHint::Other
         )]
         port: u16,
+        #[command(flatten)]
+        filter: LogFilterArgs,
+        #[command(flatten)]
+        output: LogOutputArgs,
+    },
+    #[command(about = "Send a command to the game through a running log server")]
+    Send {
+        #[arg(help = "The port of the log server the game is connected to", value_hint = ValueHint::Other)]
+        port: u16,
+        #[arg(help = "The command to send: ping, reload-configs, or console", value_hint = ValueHint::Other)]
+        command: String,
+        #[arg(help = "Arguments for the command (for console, the command to run)", value_hint = ValueHint::Other)]
+        args: Vec<String>,
+    },
+    #[command(about = "Work with saved game logs")]
+    Logs {
+        #[command(subcommand)]
+        action: LogsAction,
     },
     #[command(about = "Quickly open something")]
     Open {
@@ -206,6 +340,16 @@ pub enum Commands {
         #[arg(short = 'f', long = "fix-deps", help = "Try to fix dependency issues")]
         fix: bool,
     },
+    #[command(about = "Check installed mods for missing, modified, or extra files")]
+    Verify {
+        #[arg(help = "The unique name of the mod to verify (verifies all mods if not passed)", value_hint = ValueHint::Other)]
+        unique_name: Option<String>,
+        #[arg(
+            long = "repair",
+            help = "Reinstall mods whose files don't match what was installed (installs the latest version from the database)"
+        )]
+        repair: bool,
+    },
     #[command(about = "Clear which mod warnings were already shown")]
     ClearWarnings,
     #[command(about = "Generate auto completions for the given shell")]
@@ -230,3 +374,178 @@ pub enum ModListTypes {
     #[command(about = "Show all mods in the database (may want to use grep/find with this!)")]
     Remote,
 }
+
+#[derive(Subcommand)]
+pub enum ModConfigAction {
+    #[command(about = "Show the current value of a setting (shows all settings if not passed)")]
+    Get {
+        #[arg(help = "The setting to show", value_hint = ValueHint::Other)]
+        key: Option<String>,
+    },
+    #[command(about = "Change the value of a setting")]
+    Set {
+        #[arg(help = "The setting to change", value_hint = ValueHint::Other)]
+        key: String,
+        #[arg(help = "The new value of the setting", value_hint = ValueHint::Other)]
+        value: String,
+    },
+    #[command(
+        about = "Reset settings to their default values (resets the whole config if none are passed)"
+    )]
+    Reset {
+        #[arg(help = "The settings to reset", value_hint = ValueHint::Other)]
+        keys: Vec<String>,
+    },
+    #[command(about = "Show settings that aren't set to their default value")]
+    Diff {
+        #[arg(help = "The ID of a snapshot to check instead of the current config", value_hint = ValueHint::Other)]
+        snapshot: Option<String>,
+    },
+    #[command(about = "Take a snapshot of the current settings")]
+    Snapshot,
+    #[command(about = "List snapshots of the settings")]
+    Snapshots,
+    #[command(about = "Restore the settings from a snapshot")]
+    Restore {
+        #[arg(help = "The ID of the snapshot to restore", value_hint = ValueHint::Other)]
+        snapshot: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TrashAction {
+    #[command(about = "List mods in the trash, newest first", alias = "ls")]
+    List,
+    #[command(about = "Restore a mod or cleaned up leftover from the trash")]
+    Restore {
+        #[arg(
+            help = "The ID of the trash entry, or a unique name (or path in the Mods folder for leftovers) to restore the most recently removed version of that mod",
+            value_hint = ValueHint::Other
+        )]
+        target: String,
+    },
+    #[command(about = "Permanently delete mods in the trash")]
+    Empty {
+        #[arg(
+            short = 'o',
+            long = "old",
+            help = "Only delete mods that are older than the configured max age"
+        )]
+        old_only: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LogsAction {
+    #[command(about = "Print a saved game log session as if the game was running")]
+    Replay {
+        #[arg(help = "The session file to replay (a .jsonl or .jsonl.gz file in the game_logs folder)", value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+    },
+    #[command(
+        about = "Compress and delete old manager and game logs according to the log retention settings"
+    )]
+    Prune,
+}
+
+#[derive(Subcommand)]
+pub enum PackAction {
+    #[command(about = "Export installed mods to a modpack")]
+    Export {
+        #[arg(help = "The path to save the modpack to", value_hint = ValueHint::FilePath)]
+        file_path: PathBuf,
+        #[arg(
+            short = 'd',
+            long = "include-disabled",
+            help = "Include mods that are disabled (they'll stay disabled when imported)"
+        )]
+        include_disabled: bool,
+        #[arg(short = 'o', long = "owml", help = "Include OWML in the modpack")]
+        include_owml: bool,
+    },
+    #[command(about = "Import a modpack, this doesn't need an internet connection")]
+    Import {
+        #[arg(help = "The path to the modpack to import", value_hint = ValueHint::FilePath)]
+        file_path: PathBuf,
+        #[arg(
+            short = 'o',
+            long = "owml",
+            help = "Also install OWML from the modpack if it has it"
+        )]
+        install_owml: bool,
+    },
+}
+
+#[derive(Args, Clone)]
+pub struct LogFilterArgs {
+    #[arg(
+        short = 'l',
+        long = "level",
+        help = "Only show game logs at least this severe",
+        value_enum
+    )]
+    pub level: Option<LogLevel>,
+    #[arg(
+        short = 's',
+        long = "sender",
+        help = "Only show game logs from senders matching this name, supports * and ? (can be passed multiple times)",
+        value_hint = ValueHint::Other
+    )]
+    pub sender: Vec<String>,
+    #[arg(
+        long = "sender-type",
+        help = "Only show game logs with this sender type (can be passed multiple times)",
+        value_hint = ValueHint::Other
+    )]
+    pub sender_type: Vec<String>,
+    #[arg(
+        long = "grep",
+        help = "Only show game logs whose message matches this regex",
+        value_hint = ValueHint::Other
+    )]
+    pub grep: Option<String>,
+    #[arg(
+        long = "exclude-sender",
+        help = "Hide game logs from senders matching this name, supports * and ? (can be passed multiple times)",
+        value_hint = ValueHint::Other
+    )]
+    pub exclude_sender: Vec<String>,
+    #[arg(
+        long = "exclude-grep",
+        help = "Hide game logs whose message matches this regex (can be passed multiple times)",
+        value_hint = ValueHint::Other
+    )]
+    pub exclude_grep: Vec<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct LogOutputArgs {
+    #[arg(
+        long = "log-file",
+        help = "Also write game logs to this file as text, old logs are moved to <file>.1, <file>.2, etc. once it gets too big",
+        value_hint = ValueHint::FilePath
+    )]
+    pub log_file: Option<PathBuf>,
+    #[arg(
+        long = "broadcast",
+        help = "Also send game logs to anything that connects to this port over TCP (0 picks a free port)",
+        value_hint = ValueHint::Other
+    )]
+    pub broadcast: Option<u16>,
+}
+
+#[derive(ValueEnum, Clone)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+#[derive(ValueEnum, Clone)]
+pub enum GraphFormat {
+    Tree,
+    Dot,
+    Json,
+}
@@ -1,71 +1,245 @@
-use std::io::{stdin, Read};
+use std::{
+    io::{stdin, Read},
+    mem::take,
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
 use anyhow::Result;
+use colored::Colorize;
 use log::{debug, error, info, warn};
 use owmods_core::{
     alerts::get_warnings,
     config::Config,
     db::LocalDatabase,
     game::launch_game,
-    socket::{LogServer, SocketMessage, SocketMessageType},
+    game_logs::{replay_game_log, GameLogWriter},
+    log_analysis::{LogAnalysis, LogAnalyzer},
+    log_queue::LogQueueMetrics,
+    log_retention::{prune_logs, LogPruneReport, LogRetentionPolicy},
+    log_sinks::{format_message, LogDispatcher, LogSink, RotatingFileSink, TcpBroadcastSink},
+    socket::{LogFilter, LogFilterRule, LogServer, SocketMessage, SocketMessageType},
 };
 use tokio::{sync::mpsc, try_join};
 
-fn handle_game_log(message: &SocketMessage) {
-    let unknown = &"Unknown".to_string();
-    let log_header = format!(
-        "[{}::{}][{:?}] ",
-        message.sender_name.as_ref().unwrap_or(unknown),
-        message.sender_type.as_ref().unwrap_or(unknown),
-        message.message_type
+use crate::cli::{LogFilterArgs, LogLevel, LogOutputArgs};
+
+/// Prints game logs through our logger so they're coloured by type
+struct TerminalSink;
+
+impl LogSink for TerminalSink {
+    fn write(&mut self, message: &SocketMessage) -> Result<()> {
+        let out_message = format_message(message);
+        match message.message_type {
+            SocketMessageType::Message
+            | SocketMessageType::Info
+            | SocketMessageType::Success
+            | SocketMessageType::Quit => {
+                info!("{}", out_message)
+            }
+            SocketMessageType::Error | SocketMessageType::Fatal => {
+                error!("{}", out_message)
+            }
+            SocketMessageType::Warning => warn!("{}", out_message),
+            SocketMessageType::Debug => debug!("{}", out_message),
+        }
+        Ok(())
+    }
+}
+
+const LOG_FILE_MAX_SIZE: u64 = 10 * 1024 * 1024;
+const LOG_FILE_MAX_FILES: u32 = 5;
+
+fn make_log_sinks(port: u16, output: &LogOutputArgs) -> Result<LogDispatcher> {
+    let mut sinks = LogDispatcher::default();
+    sinks.add(TerminalSink);
+    match GameLogWriter::for_session(port) {
+        Ok(writer) => {
+            debug!("Saving game logs to {}", writer.path().display());
+            sinks.add(writer);
+        }
+        Err(why) => {
+            warn!(
+                "Couldn't create game log file, logs won't be saved: {:?}",
+                why
+            );
+        }
+    }
+    if let Some(path) = &output.log_file {
+        sinks.add(RotatingFileSink::new(
+            path,
+            LOG_FILE_MAX_SIZE,
+            LOG_FILE_MAX_FILES,
+        )?);
+        info!("Writing game logs to {}", path.display());
+    }
+    if let Some(broadcast_port) = output.broadcast {
+        let sink = TcpBroadcastSink::bind(broadcast_port)?;
+        info!("Broadcasting game logs on port {}", sink.port);
+        sinks.add(sink);
+    }
+    Ok(sinks)
+}
+
+async fn handle_game_logs(
+    mut sinks: LogDispatcher,
+    mut rx: mpsc::Receiver<SocketMessage>,
+) -> Result<()> {
+    sinks.consume(&mut rx).await;
+    Ok(())
+}
+
+fn print_log_analysis(analysis: &LogAnalysis) {
+    if analysis.is_clean() {
+        info!("No errors were logged this session");
+        return;
+    }
+    info!(
+        "{}",
+        format!(
+            "Session summary: {} message(s), {} warning(s), {} error(s)",
+            analysis.total_messages, analysis.warnings, analysis.errors
+        )
+        .bold()
     );
-    let spacing = " ".repeat(log_header.len());
-    let out_message = if message.message.trim().is_empty() {
-        log_header
-    } else {
-        message
-            .message
-            .lines()
-            .enumerate()
-            .map(|(i, l)| {
-                format!(
-                    "{}{l}",
-                    if i == 0 {
-                        log_header.clone()
-                    } else {
-                        spacing.clone()
-                    }
-                )
-            })
-            .collect::<Vec<String>>()
-            .join("\n")
-    };
-    match message.message_type {
-        SocketMessageType::Message
-        | SocketMessageType::Info
-        | SocketMessageType::Success
-        | SocketMessageType::Quit => {
-            info!("{}", out_message)
+    if let Some(fatal) = &analysis.first_fatal {
+        let first_line = fatal.message.lines().next().unwrap_or_default();
+        error!(
+            "First fatal error from {}: {}",
+            fatal.sender_name.as_deref().unwrap_or("Unknown"),
+            first_line
+        );
+    }
+    if !analysis.exceptions.is_empty() {
+        info!("Exceptions:");
+        for exception in analysis.exceptions.iter() {
+            info!(
+                "- {} x{} from {}{}",
+                exception.exception_type.bold(),
+                exception.count,
+                exception.source,
+                if exception.message.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", exception.message)
+                }
+            );
+            if let Some(frame) = exception.frames.first() {
+                info!("    at {}", frame);
+            }
         }
-        SocketMessageType::Error | SocketMessageType::Fatal => {
-            error!("{}", out_message)
+    }
+    if !analysis.top_sources.is_empty() {
+        info!("Top error sources:");
+        for source in analysis.top_sources.iter() {
+            info!(
+                "- {} ({} error(s), {} exception(s))",
+                source.name.bold(),
+                source.errors,
+                source.exceptions
+            );
         }
-        SocketMessageType::Warning => warn!("{}", out_message),
-        SocketMessageType::Debug => debug!("{}", out_message),
+    }
+    if !analysis.suspects.is_empty() {
+        warn!(
+            "Suspect mods (try disabling these first): {}",
+            analysis.suspects.join(", ")
+        );
+    }
+}
+
+fn print_log_queue_metrics(metrics: &LogQueueMetrics) {
+    if metrics.spilled > 0 {
+        info!(
+            "Logs came in faster than they could be handled, {} message(s) were buffered to disk (at most {} waiting at once)",
+            metrics.spilled, metrics.max_lag
+        );
+    }
+    if metrics.dropped > 0 {
+        error!("{} message(s) from the game were lost", metrics.dropped);
+    }
+}
+
+pub fn print_prune_report(report: &LogPruneReport) {
+    if report.is_empty() {
+        info!("No logs needed to be compressed or deleted");
+    } else {
+        info!(
+            "Compressed {} and deleted {} log file(s), freeing {} KiB",
+            report.compressed,
+            report.deleted,
+            report.freed_bytes / 1024
+        );
+    }
+}
+
+// Runs before starting a new session so old logs don't pile up, problems here shouldn't stop the game from launching
+pub fn cleanup_old_logs(policy: &LogRetentionPolicy) {
+    match prune_logs(policy) {
+        Ok(report) if !report.is_empty() => debug!(
+            "Compressed {} and deleted {} old log file(s)",
+            report.compressed, report.deleted
+        ),
+        Ok(_) => {}
+        Err(why) => warn!("Couldn't clean up old logs: {:?}", why),
     }
 }
 
-pub async fn start_just_logs(port: &u16) -> Result<()> {
-    let server = LogServer::new(*port).await?;
+pub fn make_log_filter(args: &LogFilterArgs) -> Result<LogFilter> {
+    let mut include = LogFilterRule::default();
+    if let Some(level) = &args.level {
+        include = include.with_min_level(match level {
+            LogLevel::Debug => SocketMessageType::Debug,
+            LogLevel::Info => SocketMessageType::Info,
+            LogLevel::Warning => SocketMessageType::Warning,
+            LogLevel::Error => SocketMessageType::Error,
+            LogLevel::Fatal => SocketMessageType::Fatal,
+        });
+    }
+    for sender in args.sender.iter() {
+        include = include.with_sender_name(sender)?;
+    }
+    for sender_type in args.sender_type.iter() {
+        include = include.with_sender_type(sender_type);
+    }
+    if let Some(grep) = &args.grep {
+        include = include.with_message_regex(grep)?;
+    }
+    let mut filter = LogFilter::default().include(include);
+    for sender in args.exclude_sender.iter() {
+        filter = filter.exclude(LogFilterRule::default().with_sender_name(sender)?);
+    }
+    for grep in args.exclude_grep.iter() {
+        filter = filter.exclude(LogFilterRule::default().with_message_regex(grep)?);
+    }
+    Ok(filter)
+}
+
+pub async fn start_just_logs(port: &u16, filter: LogFilter, output: &LogOutputArgs) -> Result<()> {
+    let server = LogServer::new(*port).await?.with_filter(filter);
+    let sinks = make_log_sinks(server.port, output)?;
+    let (tx, rx) = mpsc::channel(32);
+
+    try_join!(server.listen(tx, false), handle_game_logs(sinks, rx))?;
+
+    Ok(())
+}
+
+pub async fn replay_logs(path: &Path, local_db: Option<&LocalDatabase>) -> Result<()> {
     let (tx, mut rx) = mpsc::channel(32);
+    let mut analyzer = LogAnalyzer::new(local_db);
 
-    try_join!(server.listen(tx, false), async {
+    let (count, _) = try_join!(replay_game_log(path, tx), async {
         while let Some(msg) = rx.recv().await {
-            handle_game_log(&msg);
+            TerminalSink.write(&msg)?;
+            analyzer.add(&msg);
         }
         Ok(())
     })?;
 
+    info!("Replayed {} message(s)", count);
+    print_log_analysis(&analyzer.finish());
+
     Ok(())
 }
 
@@ -74,6 +248,8 @@ pub async fn start_game(
     config: &Config,
     port: Option<&u16>,
     new_window: bool,
+    filter: LogFilter,
+    output: &LogOutputArgs,
 ) -> Result<()> {
     let names = config.viewed_alerts.iter().map(|n| n.as_str()).collect();
     let warnings = get_warnings(local_db.active().collect(), names);
@@ -94,21 +270,23 @@ pub async fn start_game(
     config.save()?;
 
     if let Some(port) = port {
-        let server = LogServer::new(*port).await?;
+        let server = LogServer::new(*port).await?.with_filter(filter);
         let port = server.port;
+        let mut sinks = make_log_sinks(port, output)?;
+        let analyzer = Arc::new(Mutex::new(LogAnalyzer::new(Some(local_db))));
+        sinks.add(analyzer.clone());
 
-        let (tx, mut rx) = mpsc::channel(32);
+        let (tx, rx) = mpsc::channel(32);
 
         try_join!(
             server.listen(tx, true),
             launch_game(&config, false, Some(&port)),
-            async {
-                while let Some(msg) = rx.recv().await {
-                    handle_game_log(&msg);
-                }
-                Ok(())
-            }
+            handle_game_logs(sinks, rx)
         )?;
+
+        print_log_queue_metrics(&server.metrics());
+        let analysis = take(&mut *analyzer.lock().unwrap()).finish();
+        print_log_analysis(&analysis);
     } else if new_window && cfg!(windows) {
         launch_game(&config, true, None).await?;
     } else {
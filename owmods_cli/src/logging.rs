@@ -14,7 +14,7 @@ use owmods_core::{
         ProgressAction, ProgressFinishPayload, ProgressIncrementPayload, ProgressMessagePayload,
         ProgressPayload, ProgressStartPayload, ProgressType,
     },
-    validate::ModValidationError,
+    validate::{ManifestError, ModValidationError},
 };
 
 const PROGRESS_TEMPLATE: &str = "{spinner} {wide_msg} [{bar:100.green/cyan}]";
@@ -160,7 +160,7 @@ pub fn log_mod_validation_errors(local_mod: &UnsafeLocalMod, local_db: &LocalDat
                 warn!("{} conflicts with {}!", name, conflict_name);
             }
             ModValidationError::InvalidManifest(why) => {
-                error!("Could not load manifest for {}: {}", name, why);
+                log_manifest_error(name, why);
             }
             ModValidationError::DuplicateMod(other_path) => {
                 error!(
@@ -174,10 +174,38 @@ pub fn log_mod_validation_errors(local_mod: &UnsafeLocalMod, local_db: &LocalDat
                     name, new_version
                 )
             }
+            ModValidationError::MissingPatcher(path) => {
+                error!(
+                    "The prepatcher specified in {}'s manifest.json ({}) appears to be missing",
+                    name, path
+                );
+            }
+            ModValidationError::InvalidPreservePath(path) => {
+                warn!(
+                    "{} wants to preserve {}, but paths to preserve must be relative and stay inside the mod's folder",
+                    name, path
+                );
+            }
         }
     }
 }
 
+fn log_manifest_error(name: &str, err: &ManifestError) {
+    let location = match (err.line, err.column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", err.file, line, column),
+        _ => err.file.clone(),
+    };
+    error!("Could not load manifest for {}: {}", name, err.message);
+    error!("  --> {}", location);
+    if let (Some(snippet), Some(column)) = (err.snippet.as_ref(), err.column) {
+        error!("   | {}", snippet);
+        error!("   | {}^", " ".repeat(column.saturating_sub(1) as usize));
+    }
+    if let Some(hint) = err.hint.as_ref() {
+        warn!("Hint: {}", hint);
+    }
+}
+
 pub fn show_pre_patcher_warning(mod_name: &str) {
     warn!("========\n{mod_name} possibly modified game files.\nIn order to disable it completely, use the \"verify game files\" option in Steam / Epic.\nCheck {mod_name}'s readme for more information.\n========");
 }
@@ -6,6 +6,8 @@ use colored::Colorize;
 use log::{error, info, warn, LevelFilter};
 use owmods_core::{
     alerts::fetch_alert,
+    autoremove::{get_orphaned_mods, remove_orphaned_mods},
+    clean::{clean_mods_folder, scan_mods_folder, CleanIssue, CleanIssueKind},
     config::Config,
     constants::OWML_UNIQUE_NAME,
     db::{LocalDatabase, RemoteDatabase},
@@ -13,15 +15,33 @@ use owmods_core::{
         download_and_install_owml, install_mod_from_db, install_mod_from_url, install_mod_from_zip,
     },
     file::get_default_owml_path,
-    io::{export_mods, import_mods},
+    graph::{dependents_tree, ModGraph},
+    integrity::{repair_mods, verify_mod, verify_mods},
+    io::{
+        decode_share_code, export_mods_as, import_mod_list, preview_import, read_import_list,
+        ExportFormat, ImportAction, ImportPreview,
+    },
+    log_retention::prune_logs,
+    migrate::{adopt_mods, scan_migration_source, MigrationMode},
+    mod_settings::{
+        get_changed_settings, get_mod_setting, get_mod_settings, reset_mod_config,
+        reset_selected_mod_settings, set_mod_setting, ModSettingType,
+    },
+    modpack::{export_modpack, import_modpack},
     mods::{
         local::{LocalMod, UnsafeLocalMod},
         remote::RemoteMod,
     },
     open::{open_github, open_readme, open_shortcut},
     protocol::{ProtocolInstallType, ProtocolPayload},
-    remove::{remove_failed_mod, remove_mod},
-    toggle::toggle_mod,
+    remove::{remove_failed_mod, remove_mod_to_trash},
+    snapshots::{get_snapshot, get_snapshots, restore_snapshot, take_snapshot, SnapshotReason},
+    socket::{send_command, SocketCommand},
+    toggle::{enable_mod_with_snapshot, get_enable_conflicts, toggle_mod_with_snapshot},
+    trash::{
+        empty_trash, get_trashed_mods, purge_old_trash, restore_trashed_leftover,
+        restore_trashed_mod,
+    },
     updates::update_all,
     validate::fix_deps,
 };
@@ -30,8 +50,13 @@ mod cli;
 mod game;
 mod logging;
 
-use cli::{BaseCli, Commands, ModListTypes};
-use game::{start_game, start_just_logs};
+use cli::{
+    BaseCli, Commands, GraphFormat, LogsAction, ModConfigAction, ModListTypes, PackAction,
+    TrashAction,
+};
+use game::{
+    cleanup_old_logs, make_log_filter, print_prune_report, replay_logs, start_game, start_just_logs,
+};
 use logging::{log_mod_validation_errors, show_pre_patcher_warning, Logger};
 
 async fn run_from_cli(cli: BaseCli) -> Result<()> {
@@ -315,7 +340,7 @@ async fn run_from_cli(cli: BaseCli) -> Result<()> {
                                 remove_failed_mod(local_mod)?;
                             }
                             UnsafeLocalMod::Valid(local_mod) => {
-                                remove_mod(local_mod, &db, false)?;
+                                remove_mod_to_trash(local_mod, &config, &db, false)?;
                             }
                         }
                     }
@@ -331,8 +356,21 @@ async fn run_from_cli(cli: BaseCli) -> Result<()> {
                         unique_name,
                         if r { " and dependencies" } else { "" }
                     );
-                    let show_warnings_for = remove_mod(local_mod, &db, r)?;
-                    for mod_name in show_warnings_for {
+                    let result = remove_mod_to_trash(local_mod, &config, &db, r)?;
+                    if r {
+                        for unique_name in result.removed.iter().skip(1) {
+                            info!("Uninstalled dependency {}", unique_name);
+                        }
+                        for kept in result.kept.iter() {
+                            info!(
+                                "Kept {} ({}), it's still needed by {}",
+                                kept.name,
+                                kept.unique_name,
+                                kept.needed_by.join(", ")
+                            );
+                        }
+                    }
+                    for mod_name in result.show_warnings_for {
                         show_pre_patcher_warning(&mod_name);
                     }
                     info!("Done");
@@ -341,18 +379,389 @@ async fn run_from_cli(cli: BaseCli) -> Result<()> {
                 }
             }
         }
-        Commands::Export => {
+        Commands::Autoremove { dry } => {
+            let db = LocalDatabase::fetch(&config.owml_path)?;
+            let orphans = get_orphaned_mods(&config, &db)?;
+            if orphans.is_empty() {
+                info!("No mods to remove");
+            } else if *dry {
+                info!("The following mods are no longer needed by any installed mod:");
+                for orphan in orphans {
+                    info!(
+                        "- {} ({})",
+                        orphan.manifest.name, orphan.manifest.unique_name
+                    );
+                }
+            } else {
+                let (removed, show_warnings_for) = remove_orphaned_mods(&config, &db)?;
+                for mod_name in show_warnings_for {
+                    show_pre_patcher_warning(&mod_name);
+                }
+                info!("Removed {} mod(s):", removed.len());
+                for unique_name in removed {
+                    info!("- {}", unique_name);
+                }
+            }
+        }
+        Commands::Clean { dry } => {
+            let db = LocalDatabase::fetch(&config.owml_path)?;
+            if *dry {
+                let issues = scan_mods_folder(&config, &db)?;
+                if issues.is_empty() {
+                    info!("Nothing to clean up");
+                }
+                for issue in issues.iter() {
+                    print_clean_issue(issue);
+                }
+            } else {
+                let (cleaned, remaining) = clean_mods_folder(&config, &db)?;
+                if cleaned.is_empty() && remaining.is_empty() {
+                    info!("Nothing to clean up");
+                } else {
+                    info!(
+                        "Moved {} item(s) to the trash, use `owmods trash restore` to undo",
+                        cleaned.len()
+                    );
+                }
+                if !remaining.is_empty() {
+                    warn!("The following have to be removed manually:");
+                    for issue in remaining.iter() {
+                        print_clean_issue(issue);
+                    }
+                }
+            }
+        }
+        Commands::Trash { action } => match action {
+            TrashAction::List => {
+                let trashed = get_trashed_mods(&config)?;
+                if trashed.is_empty() {
+                    info!("The trash is empty");
+                }
+                for entry in trashed.iter() {
+                    let days = entry.age().as_secs() / (24 * 60 * 60);
+                    let removed = match days {
+                        0 => "today".to_string(),
+                        1 => "yesterday".to_string(),
+                        _ => format!("{days} days ago"),
+                    };
+                    if entry.leftover {
+                        info!(
+                            "({}) {} (cleaned up) removed {}",
+                            entry.id.dimmed(),
+                            entry.name.bold(),
+                            removed
+                        );
+                    } else {
+                        info!(
+                            "({}) {} v{} ({}) removed {}",
+                            entry.id.dimmed(),
+                            entry.name.bold(),
+                            entry.version,
+                            entry.unique_name,
+                            removed
+                        );
+                    }
+                }
+            }
+            TrashAction::Restore { target } => {
+                let db = LocalDatabase::fetch(&config.owml_path)?;
+                let entry = get_trashed_mods(&config)?
+                    .into_iter()
+                    .find(|e| &e.id == target || &e.unique_name == target)
+                    .ok_or_else(|| anyhow!("{} isn't in the trash", target))?;
+                if entry.leftover {
+                    let restored = restore_trashed_leftover(&config, &entry.id)?;
+                    info!("Restored {} to {}", entry.name, restored.display());
+                } else {
+                    let restored = restore_trashed_mod(&config, &db, &entry.id)?;
+                    info!(
+                        "Restored {} v{} to {}",
+                        restored.manifest.name, restored.manifest.version, restored.mod_path
+                    );
+                }
+            }
+            TrashAction::Empty { old_only } => {
+                let count = if *old_only {
+                    purge_old_trash(&config)?.len()
+                } else {
+                    empty_trash(&config)?
+                };
+                info!("Permanently deleted {} mod(s)", count);
+            }
+        },
+        Commands::Tree {
+            unique_name,
+            reverse,
+            format,
+        } => {
+            if *reverse && unique_name.is_none() && !matches!(format, GraphFormat::Tree) {
+                return Err(anyhow!(
+                    "--reverse needs the unique name of a mod with the dot and json formats"
+                ));
+            }
+            let local_db = LocalDatabase::fetch(&config.owml_path)?;
+            if let Some(unique_name) = unique_name {
+                if local_db.get_mod(unique_name).is_none() {
+                    return Err(anyhow!("Mod {} Is Not Installed", unique_name));
+                }
+            }
+            let mut graph = ModGraph::build(&local_db, None);
+            // Dependents are always installed, so the reverse graph doesn't need the database
+            if graph.has_missing() && !*reverse {
+                // Fill in names and versions of mods that aren't installed
+                match RemoteDatabase::fetch(&config.database_url).await {
+                    Ok(remote_db) => graph = ModGraph::build(&local_db, Some(&remote_db)),
+                    Err(why) => warn!("Couldn't fetch the database for missing mods: {:?}", why),
+                }
+            }
+            if let Some(unique_name) = unique_name {
+                graph = graph.subgraph(unique_name, *reverse);
+            }
+            match format {
+                GraphFormat::Tree if *reverse => {
+                    print!("{}", dependents_tree(&local_db, unique_name.as_deref())?);
+                }
+                GraphFormat::Tree => print!("{}", graph.to_tree(unique_name.as_deref())),
+                GraphFormat::Dot => print!("{}", graph.to_dot()),
+                GraphFormat::Json => println!("{}", graph.to_json()?),
+            }
+        }
+        Commands::Config {
+            unique_name,
+            action,
+        } => {
+            let local_db = LocalDatabase::fetch(&config.owml_path)?;
+            let local_mod = local_db
+                .get_mod(unique_name)
+                .ok_or_else(|| anyhow!("Mod {} Is Not Installed", unique_name))?;
+            match action {
+                ModConfigAction::Get { key: Some(key) } => {
+                    println!("{}", get_mod_setting(local_mod, key)?.value);
+                }
+                ModConfigAction::Get { key: None } => {
+                    let settings = get_mod_settings(local_mod)?;
+                    if settings.is_empty() {
+                        info!("{} has no settings", local_mod.manifest.name);
+                    }
+                    for setting in settings {
+                        let extra = match setting.setting_type {
+                            ModSettingType::Slider => format!(
+                                " ({} - {})",
+                                setting.min.map_or("?".to_string(), |m| m.to_string()),
+                                setting.max.map_or("?".to_string(), |m| m.to_string())
+                            ),
+                            ModSettingType::Selector => {
+                                format!(" ({})", setting.options.unwrap_or_default().join(", "))
+                            }
+                            _ => String::new(),
+                        };
+                        println!(
+                            "{}: {} {}",
+                            setting.key.bold(),
+                            setting.value,
+                            format!(
+                                "[{:?}{}, default: {}]",
+                                setting.setting_type, extra, setting.default_value
+                            )
+                            .dimmed()
+                        );
+                    }
+                }
+                ModConfigAction::Set { key, value } => {
+                    let setting = get_mod_setting(local_mod, key)?;
+                    let value = setting.parse_value(value)?;
+                    set_mod_setting(local_mod, key, value.clone())?;
+                    info!("Set {} to {}", key, value);
+                }
+                ModConfigAction::Reset { keys } => {
+                    take_snapshot(&config, local_mod, SnapshotReason::Reset)?;
+                    if keys.is_empty() {
+                        reset_mod_config(local_mod)?;
+                        info!("Reset the config of {}", local_mod.manifest.name);
+                    } else {
+                        reset_selected_mod_settings(local_mod, keys)?;
+                        info!("Reset {}", keys.join(", "));
+                    }
+                }
+                ModConfigAction::Diff { snapshot } => {
+                    let changed = if let Some(id) = snapshot {
+                        get_snapshot(&config, unique_name, id)?.changed_settings(local_mod)?
+                    } else {
+                        get_changed_settings(local_mod)?
+                    };
+                    if changed.is_empty() {
+                        info!("All settings are set to their defaults");
+                    }
+                    for setting in changed {
+                        println!(
+                            "{}: {} {}",
+                            setting.key.bold(),
+                            setting.value,
+                            format!("(default: {})", setting.default_value).dimmed()
+                        );
+                    }
+                }
+                ModConfigAction::Snapshot => {
+                    match take_snapshot(&config, local_mod, SnapshotReason::Manual)? {
+                        Some(snapshot) => info!("Took snapshot {}", snapshot.id),
+                        None => warn!("{} has no config to snapshot", local_mod.manifest.name),
+                    }
+                }
+                ModConfigAction::Snapshots => {
+                    let snapshots = get_snapshots(&config, unique_name)?;
+                    if snapshots.is_empty() {
+                        info!("No snapshots found");
+                    }
+                    for snapshot in snapshots {
+                        let changed = snapshot.changed_settings(local_mod)?.len();
+                        let state = if snapshot.enabled {
+                            "enabled"
+                        } else {
+                            "disabled"
+                        };
+                        println!(
+                            "{} {} {}, {} changed setting(s)",
+                            snapshot.id.bold(),
+                            format!("({:?})", snapshot.reason).dimmed(),
+                            state,
+                            changed
+                        );
+                    }
+                }
+                ModConfigAction::Restore { snapshot } => {
+                    restore_snapshot(&config, local_mod, snapshot)?;
+                    info!("Restored snapshot {}", snapshot);
+                }
+            }
+        }
+        Commands::Export {
+            share_code,
+            include_versions,
+        } => {
             let local_db = LocalDatabase::fetch(&config.owml_path)?;
-            println!("{}", export_mods(&local_db)?);
+            let format = if *share_code {
+                ExportFormat::ShareCode
+            } else {
+                ExportFormat::Json
+            };
+            println!("{}", export_mods_as(&local_db, format, *include_versions)?);
         }
         Commands::Import {
             file_path,
             disable_missing,
+            dry,
         } => {
             let remote_db = RemoteDatabase::fetch(&config.database_url).await?;
             let local_db = LocalDatabase::fetch(&config.owml_path)?;
-            import_mods(&config, &local_db, &remote_db, file_path, *disable_missing).await?;
+            let list = if file_path.is_file() {
+                read_import_list(file_path)?
+            } else {
+                decode_share_code(file_path.to_str().unwrap_or_default()).map_err(|e| {
+                    anyhow!(
+                        "{} isn't a file or a valid share code: {e}",
+                        file_path.display()
+                    )
+                })?
+            };
+            if *dry {
+                let preview = preview_import(&list, &local_db, &remote_db, *disable_missing);
+                print_import_preview(&preview);
+            } else {
+                import_mod_list(&config, &local_db, &remote_db, &list, *disable_missing).await?;
+            }
         }
+        Commands::Migrate {
+            path,
+            move_mods,
+            replace,
+            dry,
+        } => {
+            let remote_db = RemoteDatabase::fetch(&config.database_url).await?;
+            let local_db = LocalDatabase::fetch(&config.owml_path)?;
+            let scan = scan_migration_source(path, &local_db, &remote_db)?;
+            if scan.mods.is_empty() {
+                info!("No mods found in {}", scan.mods_path);
+                return Ok(());
+            }
+            info!("Found {} mod(s) in {}:", scan.mods.len(), scan.mods_path);
+            for candidate in scan.mods.iter() {
+                let mut line = format!(
+                    "- {} v{} ({})",
+                    candidate.name,
+                    candidate.version,
+                    candidate.unique_name.dimmed()
+                );
+                if !candidate.enabled {
+                    line = format!("{line} {}", "[disabled]".dimmed());
+                }
+                if candidate.is_local_only() {
+                    line = format!("{line} {}", "[not in the database]".yellow());
+                }
+                if let Some(installed) = &candidate.installed_version {
+                    line = format!("{line} {}", format!("[v{installed} installed]").cyan());
+                }
+                info!("{}", line);
+            }
+            for invalid in scan.invalid.iter() {
+                warn!("Couldn't read the mod at {}, it won't be adopted", invalid);
+            }
+            if !*dry {
+                let mode = if *move_mods {
+                    MigrationMode::Move
+                } else {
+                    MigrationMode::Copy
+                };
+                let result = adopt_mods(&config, &local_db, &scan, mode, *replace)?;
+                if !result.skipped.is_empty() {
+                    info!(
+                        "Skipped {} mod(s) that are already installed, use --replace to replace them",
+                        result.skipped.len()
+                    );
+                }
+                info!("Adopted {} mod(s)", result.adopted.len());
+            }
+        }
+        Commands::Pack { action } => match action {
+            PackAction::Export {
+                file_path,
+                include_disabled,
+                include_owml,
+            } => {
+                let local_db = LocalDatabase::fetch(&config.owml_path)?;
+                let manifest = export_modpack(
+                    &config,
+                    &local_db,
+                    file_path,
+                    *include_disabled,
+                    *include_owml,
+                )?;
+                info!(
+                    "Exported {} mod(s) to {}",
+                    manifest.mods.len(),
+                    file_path.to_str().unwrap()
+                );
+            }
+            PackAction::Import {
+                file_path,
+                install_owml,
+            } => {
+                let local_db = LocalDatabase::fetch(&config.owml_path)?;
+                let manifest = import_modpack(&config, &local_db, file_path, *install_owml)?;
+                for modpack_mod in manifest.mods.iter() {
+                    info!(
+                        "{} v{} ({})",
+                        modpack_mod.name,
+                        modpack_mod.version,
+                        if modpack_mod.enabled {
+                            "enabled"
+                        } else {
+                            "disabled"
+                        }
+                    );
+                }
+                info!("Imported {} mod(s)", manifest.mods.len());
+            }
+        },
         Commands::Update { dry } => {
             let remote_db = RemoteDatabase::fetch(&config.database_url).await?;
             let local_db = LocalDatabase::fetch(&config.owml_path)?;
@@ -365,34 +774,97 @@ async fn run_from_cli(cli: BaseCli) -> Result<()> {
                 info!("No Updates Available!");
             }
         }
-        Commands::Enable { unique_name } | Commands::Disable { unique_name } => {
+        Commands::Enable {
+            unique_name,
+            disable_conflicting,
+            force,
+        } if !*force && unique_name != "*" && unique_name != "all" => {
             let db = LocalDatabase::fetch(&config.owml_path)?;
-            let enable = matches!(cli.command, Commands::Enable { unique_name: _ });
+            let conflicts = get_enable_conflicts(unique_name, &db, r)?;
+            let mut disable_conflicting = *disable_conflicting;
+            if !conflicts.is_empty() && !disable_conflicting {
+                for conflict in conflicts.iter() {
+                    warn!(
+                        "{} conflicts with {}, which is enabled",
+                        conflict.unique_name, conflict.conflicting_mod
+                    );
+                }
+                warn!("Disable the conflicting mods and continue? (yes/no)");
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                disable_conflicting = answer.trim() == "yes";
+            }
+            let result =
+                enable_mod_with_snapshot(unique_name, &config, &db, r, disable_conflicting)?;
+            if result.enabled {
+                for disabled in result.disabled.iter() {
+                    info!("Disabled {}", disabled);
+                }
+                for mod_name in result.show_warnings_for {
+                    show_pre_patcher_warning(&mod_name);
+                }
+            } else {
+                warn!("Aborting, use --force to enable anyway");
+            }
+        }
+        Commands::Enable { unique_name, .. } | Commands::Disable { unique_name } => {
+            let db = LocalDatabase::fetch(&config.owml_path)?;
+            let enable = matches!(cli.command, Commands::Enable { .. });
             let mut show_warnings_for: Vec<String> = vec![];
             if unique_name == "*" || unique_name == "all" {
                 for local_mod in db.valid() {
-                    show_warnings_for.extend(toggle_mod(
+                    show_warnings_for.extend(toggle_mod_with_snapshot(
                         &local_mod.manifest.unique_name,
+                        &config,
                         &db,
                         enable,
                         false,
                     )?);
                 }
             } else {
-                show_warnings_for = toggle_mod(unique_name, &db, enable, r)?;
+                show_warnings_for = toggle_mod_with_snapshot(unique_name, &config, &db, enable, r)?;
             }
             for mod_name in show_warnings_for {
                 show_pre_patcher_warning(&mod_name);
             }
         }
-        Commands::LogServer { port } => {
-            start_just_logs(port).await?;
+        Commands::LogServer {
+            port,
+            filter,
+            output,
+        } => {
+            cleanup_old_logs(&config.log_retention);
+            start_just_logs(port, make_log_filter(filter)?, output).await?;
         }
+        Commands::Send {
+            port,
+            command,
+            args,
+        } => {
+            let command = SocketCommand::parse(command, args)?;
+            let sent_to = send_command(*port, &command).await?;
+            if sent_to == 0 {
+                warn!("No game is connected to the log server on port {}", port);
+            } else {
+                info!("Sent {:?} to {} game(s)", command, sent_to);
+            }
+        }
+        Commands::Logs { action } => match action {
+            LogsAction::Replay { file } => {
+                let local_db = LocalDatabase::fetch(&config.owml_path).ok();
+                replay_logs(file, local_db.as_ref()).await?;
+            }
+            LogsAction::Prune => {
+                print_prune_report(&prune_logs(&config.log_retention)?);
+            }
+        },
         Commands::Run {
             force,
             port,
             no_server,
             new_window,
+            filter,
+            output,
         } => {
             info!("Attempting to launch game...");
             let mut local_db = LocalDatabase::fetch(&config.owml_path)?;
@@ -415,8 +887,19 @@ async fn run_from_cli(cli: BaseCli) -> Result<()> {
             if *new_window && cfg!(unix) {
                 warn!("Skipping option --new-window as this is a Windows only flag");
             }
+            if !no_server {
+                cleanup_old_logs(&config.log_retention);
+            }
             let port = if no_server { None } else { Some(port) };
-            start_game(&local_db, &config, port, *new_window).await?;
+            start_game(
+                &local_db,
+                &config,
+                port,
+                *new_window,
+                make_log_filter(filter)?,
+                output,
+            )
+            .await?;
         }
         Commands::Open { identifier } => {
             info!("Opening {}", identifier);
@@ -458,6 +941,58 @@ async fn run_from_cli(cli: BaseCli) -> Result<()> {
                 info!("No issues found!");
             }
         }
+        Commands::Verify {
+            unique_name,
+            repair,
+        } => {
+            let local_db = LocalDatabase::fetch(&config.owml_path)?;
+            let (reports, untracked) = if let Some(unique_name) = unique_name {
+                let local_mod = local_db
+                    .get_mod(unique_name)
+                    .ok_or_else(|| anyhow!("Mod {} Is Not Installed", unique_name))?;
+                match verify_mod(local_mod)? {
+                    Some(report) if report.is_intact() => (vec![], vec![]),
+                    Some(report) => (vec![report], vec![]),
+                    None => (vec![], vec![unique_name.clone()]),
+                }
+            } else {
+                info!("Verifying all mods...");
+                verify_mods(&local_db)?
+            };
+            for unique_name in untracked.iter() {
+                warn!(
+                    "{} has no record of its installed files (reinstall it to start tracking)",
+                    unique_name
+                );
+            }
+            for report in reports.iter() {
+                error!("{} doesn't match what was installed:", report.unique_name);
+                for path in report.missing.iter() {
+                    error!("  missing: {}", path);
+                }
+                for path in report.modified.iter() {
+                    error!("  modified: {}", path);
+                }
+                for path in report.extra.iter() {
+                    warn!("  extra: {}", path);
+                }
+            }
+            if reports.is_empty() {
+                info!("No issues found!");
+            } else if *repair {
+                let remote_db = RemoteDatabase::fetch(&config.database_url).await?;
+                let not_found = repair_mods(&reports, &config, &remote_db, &local_db).await?;
+                for unique_name in not_found {
+                    error!(
+                        "Can't repair {}, it isn't in the database (reinstall it manually)",
+                        unique_name
+                    );
+                }
+                info!("Done");
+            } else {
+                error!("Issues found, run with --repair to reinstall affected mods");
+            }
+        }
         Commands::ClearWarnings => {
             let mut new_config = config.clone();
             new_config.viewed_alerts = vec![];
@@ -510,6 +1045,21 @@ async fn run_from_cli(cli: BaseCli) -> Result<()> {
                         warn!("Aborting");
                     }
                 }
+                ProtocolInstallType::ImportList => {
+                    let list = decode_share_code(&payload.payload)?;
+                    let preview = preview_import(&list, &local_db, &remote_db, false);
+                    print_import_preview(&preview);
+                    if preview.has_changes() {
+                        info!("Import these mods? (yes/no)");
+                        let mut answer = String::new();
+                        std::io::stdin().read_line(&mut answer)?;
+                        if answer.trim() == "yes" {
+                            import_mod_list(&config, &local_db, &remote_db, &list, false).await?;
+                        } else {
+                            warn!("Aborting");
+                        }
+                    }
+                }
                 ProtocolInstallType::Unknown => {
                     error!("Unknown install type, ignoring");
                 }
@@ -519,6 +1069,86 @@ async fn run_from_cli(cli: BaseCli) -> Result<()> {
     Ok(())
 }
 
+fn print_import_preview(preview: &ImportPreview) {
+    let name_width = preview
+        .entries
+        .iter()
+        .map(|e| e.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    info!(
+        "{}",
+        format!(
+            "{:<10} {:<name_width$} {:<12} {:<12}",
+            "Action", "Name", "Installed", "Target"
+        )
+        .bold()
+    );
+    for entry in preview.entries.iter() {
+        let action = format!("{:<10}", format!("{:?}", entry.action));
+        let action = match entry.action {
+            ImportAction::Install => action.green(),
+            ImportAction::Enable => action.cyan(),
+            ImportAction::Disable => action.yellow(),
+            ImportAction::Untouched => action.dimmed(),
+            ImportAction::Unknown => action.red(),
+        };
+        let target = match entry.action {
+            ImportAction::Install => entry.remote_version.as_deref(),
+            ImportAction::Unknown => entry.list_version.as_deref(),
+            _ => entry.installed_version.as_deref(),
+        };
+        let mut line = format!(
+            "{} {:<name_width$} {:<12} {:<12}",
+            action,
+            entry.name,
+            entry.installed_version.as_deref().unwrap_or("-"),
+            target.unwrap_or("-"),
+        );
+        if entry.version_mismatch() {
+            line = format!(
+                "{line} {}",
+                format!("(list has v{})", entry.list_version.as_ref().unwrap()).yellow()
+            );
+        }
+        info!("{}", line.trim_end());
+    }
+    if !preview.unknown.is_empty() {
+        warn!(
+            "{} mod(s) aren't installed or in the database and will be skipped: {}",
+            preview.unknown.len(),
+            preview.unknown.join(", ")
+        );
+    }
+    if !preview.has_changes() {
+        info!("Importing wouldn't change anything");
+    }
+}
+
+fn print_clean_issue(issue: &CleanIssue) {
+    let description = match issue.kind {
+        CleanIssueKind::OrphanFolder => "Folder without a manifest".to_string(),
+        CleanIssueKind::EmptyFolder => "Empty folder".to_string(),
+        CleanIssueKind::Duplicate => format!(
+            "Duplicate of {}",
+            issue
+                .other_path
+                .as_deref()
+                .or(issue.unique_name.as_deref())
+                .unwrap_or("another mod")
+        ),
+        CleanIssueKind::StrayArchive => "Stray archive".to_string(),
+        CleanIssueKind::LargeFile => "Large untracked file".to_string(),
+    };
+    info!(
+        "- {} ({}, {} KiB)",
+        issue.display_path.bold(),
+        description,
+        issue.size_kb
+    );
+}
+
 fn yes_no(v: bool) -> String {
     if v {
         "Yes".to_string()
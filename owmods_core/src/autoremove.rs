@@ -0,0 +1,298 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Config,
+    constants::DEPENDENCY_MARKS_FILE_NAME,
+    db::LocalDatabase,
+    file::{deserialize_from_json, serialize_to_json},
+    mods::local::LocalMod,
+    remove::remove_mod_to_trash,
+};
+
+/// Keeps track of which mods were installed only because another mod depended on them.
+/// Any installed mod not in this list is considered to be explicitly installed by the user.
+///
+/// This is stored next to the manager's settings file, see [DependencyMarks::path].
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyMarks {
+    /// The unique names of mods that were installed as a dependency
+    pub installed_as_dependency: Vec<String>,
+}
+
+impl DependencyMarks {
+    /// Get the path the marks are stored at, this is a sibling of the config's `settings.json`
+    pub fn path(config: &Config) -> PathBuf {
+        config.path.with_file_name(DEPENDENCY_MARKS_FILE_NAME)
+    }
+
+    /// Get the current dependency marks, if the file doesn't exist yet we get an empty list.
+    ///
+    /// ## Errors
+    ///
+    /// If the file exists but we can't read or deserialize it.
+    ///
+    pub fn get(config: &Config) -> Result<Self> {
+        let path = Self::path(config);
+        if path.is_file() {
+            deserialize_from_json(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Save the dependency marks
+    ///
+    /// ## Errors
+    ///
+    /// If we can't save to the file
+    ///
+    pub fn save(&self, config: &Config) -> Result<()> {
+        serialize_to_json(self, &Self::path(config), true)
+    }
+
+    /// Check if a mod is marked as installed as a dependency
+    pub fn is_dependency(&self, unique_name: &str) -> bool {
+        self.installed_as_dependency
+            .iter()
+            .any(|m| m == unique_name)
+    }
+
+    /// Mark the given mods as installed because another mod needed them and save
+    ///
+    /// ## Errors
+    ///
+    /// If we can't read or save the marks
+    ///
+    pub fn mark_dependencies(config: &Config, unique_names: &[String]) -> Result<()> {
+        if unique_names.is_empty() {
+            return Ok(());
+        }
+        let mut marks = Self::get(config)?;
+        for unique_name in unique_names {
+            if !marks.is_dependency(unique_name) {
+                debug!("Marking {} as installed as a dependency", unique_name);
+                marks.installed_as_dependency.push(unique_name.clone());
+            }
+        }
+        marks.save(config)
+    }
+
+    /// Remove the given mods from the list of dependencies, meaning they're explicitly installed.
+    /// This only touches the file if any of the mods were actually marked.
+    ///
+    /// ## Errors
+    ///
+    /// If we can't read or save the marks
+    ///
+    pub fn unmark(config: &Config, unique_names: &[String]) -> Result<()> {
+        let mut marks = Self::get(config)?;
+        let old_len = marks.installed_as_dependency.len();
+        marks
+            .installed_as_dependency
+            .retain(|m| !unique_names.contains(m));
+        if marks.installed_as_dependency.len() != old_len {
+            marks.save(config)?;
+        }
+        Ok(())
+    }
+}
+
+/// Get all mods that were installed as a dependency and aren't needed by any installed mod anymore.
+///
+/// A mod is still needed if a mod that was explicitly installed depends on it (directly or through other dependencies).
+/// Disabled mods still count as needing their dependencies.
+///
+/// ## Returns
+///
+/// The orphaned mods, these are safe to uninstall.
+///
+/// ## Errors
+///
+/// If we can't read the dependency marks.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::autoremove::get_orphaned_mods;
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// for orphan in get_orphaned_mods(&config, &local_db).unwrap() {
+///     println!("{} is no longer needed", orphan.manifest.name);
+/// }
+/// ```
+///
+pub fn get_orphaned_mods<'a>(
+    config: &Config,
+    local_db: &'a LocalDatabase,
+) -> Result<Vec<&'a LocalMod>> {
+    let marks = DependencyMarks::get(config)?;
+    let candidates: Vec<&LocalMod> = local_db
+        .valid()
+        .filter(|m| marks.is_dependency(&m.manifest.unique_name))
+        .collect();
+
+    // Start with every explicitly installed mod being needed,
+    // then keep adding dependencies that are needed by something we know is needed until nothing changes
+    let mut needed: Vec<&str> = local_db
+        .valid()
+        .filter(|m| !marks.is_dependency(&m.manifest.unique_name))
+        .map(|m| m.manifest.unique_name.as_str())
+        .collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for candidate in candidates.iter() {
+            let unique_name = candidate.manifest.unique_name.as_str();
+            if needed.contains(&unique_name) {
+                continue;
+            }
+            if local_db
+                .dependent(candidate)
+                .any(|m| needed.contains(&m.manifest.unique_name.as_str()))
+            {
+                needed.push(unique_name);
+                changed = true;
+            }
+        }
+    }
+
+    Ok(candidates
+        .into_iter()
+        .filter(|m| !needed.contains(&m.manifest.unique_name.as_str()))
+        .collect())
+}
+
+/// Uninstall all mods found by [get_orphaned_mods] and clear their dependency marks.
+///
+/// ## Returns
+///
+/// A tuple containing:
+/// - The unique names of the mods that were removed
+/// - The unique names of removed mods that use pre-patchers
+///   and thus **should have a warning shown to the user telling them to check the mod's README for instructions**
+///
+/// ## Errors
+///
+/// If we can't read the dependency marks or can't delete the folder of any orphaned mod.
+///
+pub fn remove_orphaned_mods(
+    config: &Config,
+    local_db: &LocalDatabase,
+) -> Result<(Vec<String>, Vec<String>)> {
+    let orphans = get_orphaned_mods(config, local_db)?;
+    let mut removed: Vec<String> = vec![];
+    let mut show_warnings_for: Vec<String> = vec![];
+    for orphan in orphans {
+        info!("Removing {}", orphan.manifest.name);
+        show_warnings_for
+            .extend(remove_mod_to_trash(orphan, config, local_db, false)?.show_warnings_for);
+        removed.push(orphan.manifest.unique_name.clone());
+    }
+    DependencyMarks::unmark(config, &removed)?;
+    Ok((removed, show_warnings_for))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::test_utils::TestContext;
+
+    use super::*;
+
+    fn setup(ctx: &mut TestContext) {
+        let mut time_saver = ctx.install_test_zip("Bwc9876.TimeSaver.zip", false);
+        ctx.install_test_zip("Bwc9876.SaveEditor.zip", true);
+        time_saver.manifest.dependencies = Some(vec!["Bwc9876.SaveEditor".to_string()]);
+        ctx.insert_test_mod(&time_saver);
+        DependencyMarks::mark_dependencies(&ctx.config, &["Bwc9876.SaveEditor".to_string()])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_marks_round_trip() {
+        let ctx = TestContext::new();
+        let names = vec![
+            "Example.TestMod0".to_string(),
+            "Example.TestMod1".to_string(),
+        ];
+        DependencyMarks::mark_dependencies(&ctx.config, &names).unwrap();
+        DependencyMarks::mark_dependencies(&ctx.config, &names).unwrap();
+        let marks = DependencyMarks::get(&ctx.config).unwrap();
+        assert_eq!(marks.installed_as_dependency.len(), 2);
+        DependencyMarks::unmark(&ctx.config, &names[0..1]).unwrap();
+        let marks = DependencyMarks::get(&ctx.config).unwrap();
+        assert!(!marks.is_dependency("Example.TestMod0"));
+        assert!(marks.is_dependency("Example.TestMod1"));
+    }
+
+    #[test]
+    fn test_orphans_still_needed() {
+        let mut ctx = TestContext::new();
+        setup(&mut ctx);
+        let orphans = get_orphaned_mods(&ctx.config, &ctx.local_db).unwrap();
+        assert!(orphans.is_empty());
+    }
+
+    #[test]
+    fn test_orphans_dependent_removed() {
+        let mut ctx = TestContext::new();
+        setup(&mut ctx);
+        ctx.local_db.mods.remove("Bwc9876.TimeSaver");
+        let orphans = get_orphaned_mods(&ctx.config, &ctx.local_db).unwrap();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].manifest.unique_name, "Bwc9876.SaveEditor");
+    }
+
+    #[test]
+    fn test_orphans_cyclical_deps() {
+        let mut ctx = TestContext::new();
+        setup(&mut ctx);
+        let mut time_saver = ctx.local_db.get_mod("Bwc9876.TimeSaver").unwrap().clone();
+        let mut save_editor = ctx.local_db.get_mod("Bwc9876.SaveEditor").unwrap().clone();
+        save_editor.manifest.dependencies = Some(vec!["Bwc9876.TimeSaver".to_string()]);
+        ctx.insert_test_mod(&save_editor);
+        DependencyMarks::mark_dependencies(&ctx.config, &["Bwc9876.TimeSaver".to_string()])
+            .unwrap();
+        let orphans = get_orphaned_mods(&ctx.config, &ctx.local_db).unwrap();
+        assert_eq!(orphans.len(), 2);
+        // Now something explicit needs one of them, so both are needed
+        let mut test_mod = LocalMod::get_test(0);
+        test_mod.manifest.dependencies = Some(vec!["Bwc9876.TimeSaver".to_string()]);
+        ctx.insert_test_mod(&test_mod);
+        time_saver.enabled = false;
+        ctx.insert_test_mod(&time_saver);
+        let orphans = get_orphaned_mods(&ctx.config, &ctx.local_db).unwrap();
+        assert!(orphans.is_empty());
+    }
+
+    #[test]
+    fn test_remove_orphaned_mods() {
+        let mut ctx = TestContext::new();
+        setup(&mut ctx);
+        ctx.local_db.mods.remove("Bwc9876.TimeSaver");
+        let (removed, _) = remove_orphaned_mods(&ctx.config, &ctx.local_db).unwrap();
+        assert_eq!(removed, vec!["Bwc9876.SaveEditor".to_string()]);
+        assert!(!ctx.get_test_path("Bwc9876.SaveEditor").is_dir());
+        let marks = DependencyMarks::get(&ctx.config).unwrap();
+        assert!(marks.installed_as_dependency.is_empty());
+    }
+
+    #[test]
+    fn test_zip_install_is_explicit() {
+        let mut ctx = TestContext::new();
+        setup(&mut ctx);
+        ctx.local_db.mods.remove("Bwc9876.SaveEditor");
+        ctx.install_test_zip("Bwc9876.SaveEditor.zip", false);
+        let marks = DependencyMarks::get(&ctx.config).unwrap();
+        assert!(!marks.is_dependency("Bwc9876.SaveEditor"));
+    }
+}
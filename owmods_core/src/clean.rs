@@ -0,0 +1,425 @@
+use std::{
+    fs::read_dir,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::Serialize;
+use typeshare::typeshare;
+
+use crate::{
+    config::Config, db::LocalDatabase, integrity::verify_mod, mods::local::UnsafeLocalMod,
+    trash::trash_leftover, validate::ModValidationError,
+};
+
+/// Files bigger than this that weren't installed with any mod are reported (50 MiB)
+const LARGE_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Extensions of files we consider archives
+const ARCHIVE_EXTENSIONS: [&str; 5] = ["zip", "7z", "rar", "tar", "gz"];
+
+/// What kind of leftover a [CleanIssue] is
+#[typeshare]
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CleanIssueKind {
+    /// A folder that doesn't have a manifest in it, OWML won't load anything from it
+    OrphanFolder,
+    /// A folder that doesn't have any files in it
+    EmptyFolder,
+    /// A mod with the same unique name as another mod that's already loaded
+    Duplicate,
+    /// An archive that wasn't installed with any mod, usually left over from installing a mod manually
+    StrayArchive,
+    /// A large file that wasn't installed with any mod
+    LargeFile,
+}
+
+/// Something in the `Mods` folder that probably shouldn't be there
+#[typeshare]
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanIssue {
+    /// What kind of leftover this is
+    pub kind: CleanIssueKind,
+    /// The absolute path to the file or folder
+    pub path: String,
+    /// The path relative to the `Mods` folder, good for display
+    pub display_path: String,
+    /// The size of the file or folder in KiB
+    pub size_kb: u32,
+    /// For duplicates, the unique name of the mod that's duplicated
+    pub unique_name: Option<String>,
+    /// For duplicates, the path of the mod that's loaded instead
+    pub other_path: Option<String>,
+}
+
+impl CleanIssue {
+    fn new(kind: CleanIssueKind, path: &Path, mods_path: &Path, size: u64) -> Self {
+        Self {
+            kind,
+            path: path.to_str().unwrap().to_string(),
+            display_path: path
+                .strip_prefix(mods_path)
+                .unwrap_or(path)
+                .to_str()
+                .unwrap()
+                .to_string(),
+            size_kb: (size / 1024).try_into().unwrap_or(u32::MAX),
+            unique_name: None,
+            other_path: None,
+        }
+    }
+
+    /// Whether [clean_issue] can safely remove this.
+    /// Large files are never removed automatically as they may be something the user wants to keep.
+    pub fn can_clean(&self) -> bool {
+        self.kind != CleanIssueKind::LargeFile
+    }
+}
+
+/// Get the total size of a file or folder and whether it has any files in it at all
+fn get_size(path: &Path) -> Result<(u64, bool)> {
+    if path.is_file() {
+        return Ok((path.metadata()?.len(), true));
+    }
+    let mut total = 0;
+    let mut has_files = false;
+    for entry in read_dir(path)? {
+        let (size, files) = get_size(&entry?.path())?;
+        total += size;
+        has_files = has_files || files;
+    }
+    Ok((total, has_files))
+}
+
+fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| ARCHIVE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Check a loose file, archives are always reported, other files only if they're large
+fn check_file(path: &Path, mods_path: &Path) -> Result<Option<CleanIssue>> {
+    let size = path.metadata()?.len();
+    Ok(if is_archive(path) {
+        Some(CleanIssue::new(
+            CleanIssueKind::StrayArchive,
+            path,
+            mods_path,
+            size,
+        ))
+    } else if size > LARGE_FILE_SIZE {
+        Some(CleanIssue::new(
+            CleanIssueKind::LargeFile,
+            path,
+            mods_path,
+            size,
+        ))
+    } else {
+        None
+    })
+}
+
+fn scan_dir(
+    dir: &Path,
+    mods_path: &Path,
+    mod_paths: &[PathBuf],
+    issues: &mut Vec<CleanIssue>,
+) -> Result<()> {
+    let mut entries = read_dir(dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            if mod_paths.contains(&path) {
+                // Files inside mods are checked against their record in `scan_mods_folder`
+                continue;
+            } else if mod_paths.iter().any(|p| p.starts_with(&path)) {
+                scan_dir(&path, mods_path, mod_paths, issues)?;
+            } else {
+                let (size, has_files) = get_size(&path)?;
+                let kind = if has_files {
+                    CleanIssueKind::OrphanFolder
+                } else {
+                    CleanIssueKind::EmptyFolder
+                };
+                issues.push(CleanIssue::new(kind, &path, mods_path, size));
+            }
+        } else if let Some(issue) = check_file(&path, mods_path)? {
+            issues.push(issue);
+        }
+    }
+    Ok(())
+}
+
+/// Scan the `Mods` folder for things that probably shouldn't be there:
+/// - Folders that don't have a manifest in them, or don't have any files at all
+/// - Mods that have the same unique name as another mod
+/// - Archives that weren't installed with a mod
+/// - Large files that weren't installed with a mod
+///
+/// Files inside a mod's folder are only checked if the mod has a record of the files it was installed with
+/// (see [crate::integrity]), otherwise we can't tell what belongs to the mod.
+///
+/// ## Returns
+///
+/// Everything that was found, see [CleanIssue::can_clean] for what can be removed with [clean_issue].
+///
+/// ## Errors
+///
+/// If we can't read the `Mods` folder or any of the folders in it.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::clean::scan_mods_folder;
+/// use owmods_core::config::Config;
+/// use owmods_core::db::LocalDatabase;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// for issue in scan_mods_folder(&config, &local_db).unwrap() {
+///     println!("{:?}: {}", issue.kind, issue.display_path);
+/// }
+/// ```
+///
+pub fn scan_mods_folder(config: &Config, local_db: &LocalDatabase) -> Result<Vec<CleanIssue>> {
+    let mods_path = PathBuf::from(&config.owml_path).join("Mods");
+    let mut issues: Vec<CleanIssue> = vec![];
+    if !mods_path.is_dir() {
+        return Ok(issues);
+    }
+
+    for local_mod in local_db.invalid() {
+        if let UnsafeLocalMod::Invalid(failed_mod) = local_mod {
+            if let ModValidationError::DuplicateMod(other_path) = &failed_mod.error {
+                let path = PathBuf::from(&failed_mod.mod_path);
+                let (size, _) = get_size(&path)?;
+                let mut issue = CleanIssue::new(CleanIssueKind::Duplicate, &path, &mods_path, size);
+                issue.unique_name = local_db
+                    .valid()
+                    .find(|m| &m.mod_path == other_path)
+                    .map(|m| m.manifest.unique_name.clone());
+                issue.other_path = Some(other_path.clone());
+                issues.push(issue);
+            }
+        }
+    }
+
+    let mod_paths: Vec<PathBuf> = local_db
+        .all()
+        .map(|m| match m {
+            UnsafeLocalMod::Valid(m) => PathBuf::from(&m.mod_path),
+            UnsafeLocalMod::Invalid(m) => PathBuf::from(&m.mod_path),
+        })
+        .collect();
+    scan_dir(&mods_path, &mods_path, &mod_paths, &mut issues)?;
+
+    for local_mod in local_db.valid() {
+        let mod_path = PathBuf::from(&local_mod.mod_path);
+        if let Some(report) = verify_mod(local_mod)? {
+            for extra in report.extra {
+                let path = mod_path.join(extra);
+                if let Some(issue) = check_file(&path, &mods_path)? {
+                    issues.push(issue);
+                }
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Remove something found by [scan_mods_folder] by moving it to the trash with [trash_leftover],
+/// so it can be restored with [crate::trash::restore_trashed_leftover].
+///
+/// ## Errors
+///
+/// - If the issue can't be cleaned automatically (see [CleanIssue::can_clean])
+/// - If the path isn't in the `Mods` folder
+/// - If a duplicate isn't a duplicate mod anymore
+/// - If we can't move the file or folder to the trash
+///
+pub fn clean_issue(config: &Config, local_db: &LocalDatabase, issue: &CleanIssue) -> Result<()> {
+    if !issue.can_clean() {
+        return Err(anyhow!("{} has to be removed manually", issue.display_path));
+    }
+    let mods_path = PathBuf::from(&config.owml_path).join("Mods");
+    let path = PathBuf::from(&issue.path);
+    if !path.starts_with(&mods_path) || path == mods_path {
+        return Err(anyhow!("{} isn't in the Mods folder", issue.path));
+    }
+    if issue.kind == CleanIssueKind::Duplicate
+        && !local_db
+            .invalid()
+            .any(|m| matches!(m, UnsafeLocalMod::Invalid(m) if m.mod_path == issue.path))
+    {
+        return Err(anyhow!("{} isn't a duplicate mod", issue.display_path));
+    }
+    trash_leftover(config, &path, &issue.display_path)?;
+    Ok(())
+}
+
+/// Scan the `Mods` folder with [scan_mods_folder] and move everything that can be removed safely to the trash.
+///
+/// ## Returns
+///
+/// A tuple containing:
+/// - Everything that was removed
+/// - Everything that has to be dealt with manually
+///
+/// ## Errors
+///
+/// If we can't scan the `Mods` folder. Failing to remove a single item just logs a warning and leaves it in the second list.
+///
+pub fn clean_mods_folder(
+    config: &Config,
+    local_db: &LocalDatabase,
+) -> Result<(Vec<CleanIssue>, Vec<CleanIssue>)> {
+    let mut cleaned: Vec<CleanIssue> = vec![];
+    let mut remaining: Vec<CleanIssue> = vec![];
+    for issue in scan_mods_folder(config, local_db)? {
+        if !issue.can_clean() {
+            remaining.push(issue);
+            continue;
+        }
+        match clean_issue(config, local_db, &issue) {
+            Ok(_) => {
+                info!("Moved {} to the trash", issue.display_path);
+                cleaned.push(issue);
+            }
+            Err(why) => {
+                warn!("Couldn't remove {}: {:?}", issue.display_path, why);
+                remaining.push(issue);
+            }
+        }
+    }
+    Ok((cleaned, remaining))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs::{self, File};
+
+    use crate::{
+        file::copy_dir_all,
+        test_utils::TestContext,
+        trash::{get_trashed_mods, restore_trashed_leftover},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_scan_mods_folder() {
+        let mut ctx = TestContext::new();
+        ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        fs::create_dir_all(ctx.join_mods_folder("Empty").join("Nested")).unwrap();
+        fs::create_dir_all(ctx.join_mods_folder("Orphan")).unwrap();
+        fs::write(ctx.join_mods_folder("Orphan").join("Orphan.dll"), "").unwrap();
+        fs::write(ctx.join_mods_folder("Leftover.zip"), "").unwrap();
+        fs::write(ctx.join_mods_folder("notes.txt"), "").unwrap();
+        let issues = scan_mods_folder(&ctx.config, &ctx.local_db).unwrap();
+        let kinds = issues
+            .iter()
+            .map(|i| (i.kind, i.display_path.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (CleanIssueKind::EmptyFolder, "Empty"),
+                (CleanIssueKind::StrayArchive, "Leftover.zip"),
+                (CleanIssueKind::OrphanFolder, "Orphan"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_mods_folder_inside_mod() {
+        let mut ctx = TestContext::new();
+        let new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let archive_path = PathBuf::from(&new_mod.mod_path).join("Old.zip");
+        fs::write(&archive_path, "").unwrap();
+        let large_path = PathBuf::from(&new_mod.mod_path).join("huge.bin");
+        File::create(&large_path)
+            .unwrap()
+            .set_len(LARGE_FILE_SIZE + 1)
+            .unwrap();
+        let issues = scan_mods_folder(&ctx.config, &ctx.local_db).unwrap();
+        assert_eq!(issues.len(), 2);
+        let archive = issues
+            .iter()
+            .find(|i| i.kind == CleanIssueKind::StrayArchive)
+            .unwrap();
+        assert_eq!(archive.path, archive_path.to_str().unwrap());
+        let large = issues
+            .iter()
+            .find(|i| i.kind == CleanIssueKind::LargeFile)
+            .unwrap();
+        assert!(!large.can_clean());
+        let (cleaned, remaining) = clean_mods_folder(&ctx.config, &ctx.local_db).unwrap();
+        assert_eq!(cleaned.len(), 1);
+        assert_eq!(remaining.len(), 1);
+        assert!(!archive_path.is_file());
+        assert!(large_path.is_file());
+    }
+
+    #[test]
+    fn test_clean_mods_folder_duplicate() {
+        let mut ctx = TestContext::new();
+        let new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", false);
+        let dupe_path = ctx.join_mods_folder("TimeSaverCopy");
+        copy_dir_all(Path::new(&new_mod.mod_path), &dupe_path).unwrap();
+        fs::create_dir_all(ctx.join_mods_folder("Empty")).unwrap();
+        ctx.fetch_local_db();
+        let issues = scan_mods_folder(&ctx.config, &ctx.local_db).unwrap();
+        let dupe = issues
+            .iter()
+            .find(|i| i.kind == CleanIssueKind::Duplicate)
+            .unwrap();
+        assert_eq!(dupe.unique_name.as_deref(), Some("Bwc9876.TimeSaver"));
+        let (cleaned, remaining) = clean_mods_folder(&ctx.config, &ctx.local_db).unwrap();
+        assert_eq!(cleaned.len(), 2);
+        assert!(remaining.is_empty());
+        assert!(!PathBuf::from(&dupe.path).is_dir());
+        assert!(!ctx.join_mods_folder("Empty").is_dir());
+        ctx.fetch_local_db();
+        assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").is_some());
+        assert_eq!(ctx.local_db.invalid().count(), 0);
+    }
+
+    #[test]
+    fn test_clean_issue_to_trash() {
+        let ctx = TestContext::new();
+        let orphan_path = ctx.join_mods_folder("Orphan");
+        fs::create_dir_all(&orphan_path).unwrap();
+        fs::write(orphan_path.join("Orphan.dll"), "").unwrap();
+        let issues = scan_mods_folder(&ctx.config, &ctx.local_db).unwrap();
+        assert_eq!(issues.len(), 1);
+        clean_issue(&ctx.config, &ctx.local_db, &issues[0]).unwrap();
+        assert!(!orphan_path.exists());
+        let trashed = get_trashed_mods(&ctx.config).unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].unique_name, "Orphan");
+        restore_trashed_leftover(&ctx.config, &trashed[0].id).unwrap();
+        assert!(orphan_path.join("Orphan.dll").is_file());
+    }
+
+    #[test]
+    fn test_clean_issue_outside_mods_folder() {
+        let ctx = TestContext::new();
+        let path = ctx.temp_dir.path().join("settings.json");
+        fs::write(&path, "{}").unwrap();
+        let issue = CleanIssue::new(
+            CleanIssueKind::OrphanFolder,
+            &path,
+            &ctx.join_mods_folder(""),
+            0,
+        );
+        assert!(clean_issue(&ctx.config, &ctx.local_db, &issue).is_err());
+        assert!(path.is_file());
+    }
+}
@@ -7,10 +7,11 @@ use typeshare::typeshare;
 
 use crate::{
     constants::{
-        CONFIG_FILE_NAME, DEFAULT_ALERT_URL, DEFAULT_DB_URL, OLD_ALERT_URL,
-        OWML_DEFAULT_CONFIG_NAME, OWML_EXE_NAME, OWML_MANIFEST_NAME,
+        CONFIG_FILE_NAME, DEFAULT_ALERT_URL, DEFAULT_DB_URL, DEFAULT_TRASH_MAX_AGE_DAYS,
+        OLD_ALERT_URL, OWML_DEFAULT_CONFIG_NAME, OWML_EXE_NAME, OWML_MANIFEST_NAME,
     },
     file::{deserialize_from_json, get_app_path, get_default_owml_path, serialize_to_json},
+    log_retention::LogRetentionPolicy,
 };
 
 /// Represents the core config, contains critical info needed by the core API
@@ -26,11 +27,21 @@ pub struct Config {
     pub alert_url: String,
     /// The mod warnings that have been shown to the user
     pub viewed_alerts: Vec<String>,
+    /// How many days uninstalled mods are kept in the trash before they're deleted for good, `0` keeps them forever
+    #[serde(default = "default_trash_max_age_days")]
+    pub trash_max_age_days: u32,
+    /// How long to keep manager and game logs, see [crate::log_retention]
+    #[serde(default)]
+    pub log_retention: LogRetentionPolicy,
     /// Where the config is saved, this is not serialized
     #[serde(skip)]
     pub path: PathBuf,
 }
 
+fn default_trash_max_age_days() -> u32 {
+    DEFAULT_TRASH_MAX_AGE_DAYS
+}
+
 impl Config {
     /// Create a new config object with defaults set and optionally set to save at a specified: `path`.
     ///
@@ -64,6 +75,8 @@ impl Config {
             database_url: String::from(DEFAULT_DB_URL),
             alert_url: String::from(DEFAULT_ALERT_URL),
             viewed_alerts: vec![],
+            trash_max_age_days: DEFAULT_TRASH_MAX_AGE_DAYS,
+            log_retention: LogRetentionPolicy::default(),
             path,
         })
     }
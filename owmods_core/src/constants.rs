@@ -35,3 +35,39 @@ pub const WEBSITE_URL: &str = "https://outerwildsmods.com";
 
 /// The name of the old manager folder, the new manager uses the OWML installation here to make migration easier
 pub const OLD_MANAGER_FOLDER_NAME: &str = "OuterWildsModManager";
+
+/// The name of the file that tracks which mods were installed as dependencies, stored next to the settings file
+pub const DEPENDENCY_MARKS_FILE_NAME: &str = "installed_dependencies.json";
+
+/// The name of the file in each mod's folder that records the files installed with it
+pub const INSTALLED_FILES_NAME: &str = ".owmods_files.json";
+
+/// The name of the folder settings snapshots are stored in, stored next to the settings file
+pub const SETTINGS_SNAPSHOTS_DIR_NAME: &str = "settings_snapshots";
+
+/// The name of the folder uninstalled mods are moved to, stored next to the settings file
+pub const TRASH_DIR_NAME: &str = "trash";
+
+/// The name of the file in each trash entry that describes the mod that was removed
+pub const TRASH_INFO_FILE_NAME: &str = "trash.json";
+
+/// How many days uninstalled mods are kept in the trash by default
+pub const DEFAULT_TRASH_MAX_AGE_DAYS: u32 = 30;
+
+/// The name of the folder game log sessions are stored in, in the app data directory
+pub const GAME_LOGS_DIR_NAME: &str = "game_logs";
+
+/// The name of the folder the GUI's own logs are stored in, in the app data directory
+pub const LOGS_DIR_NAME: &str = "logs";
+
+/// How many days logs are kept by default
+pub const DEFAULT_LOG_MAX_AGE_DAYS: u32 = 30;
+
+/// How many megabytes each log folder can take up by default
+pub const DEFAULT_LOG_MAX_TOTAL_SIZE_MB: u32 = 500;
+
+/// How many log files are kept in each log folder by default
+pub const DEFAULT_LOG_MAX_FILES: u32 = 200;
+
+/// How many days until logs are compressed by default
+pub const DEFAULT_LOG_COMPRESS_AFTER_DAYS: u32 = 1;
@@ -12,7 +12,7 @@ use crate::{
     search::search_list,
     toggle::get_mod_enabled,
     updates::check_mod_needs_update,
-    validate::{check_mod, ModValidationError},
+    validate::{check_mod, ManifestError, ModValidationError},
 };
 
 use super::{fix_version, RemoteDatabase};
@@ -397,8 +397,8 @@ impl LocalDatabase {
                     );
                 }
             } else {
-                let err = format!("{:?}", local_mod.err().unwrap());
-                warn!("Failed to load mod at {}: {:?}", path, err);
+                let err = ManifestError::from_error(&entry, &local_mod.err().unwrap());
+                warn!("Failed to load mod at {}: {}", path, err);
                 let failed_mod = FailedMod {
                     mod_path: path.to_string(),
                     display_path,
@@ -446,7 +446,10 @@ mod tests {
         if let UnsafeLocalMod::Invalid(bad_mod) = bad_mod {
             assert_eq!(bad_mod.mod_path, bad_mod_path.to_str().unwrap());
             if let ModValidationError::InvalidManifest(e) = &bad_mod.error {
-                assert!(e.to_ascii_lowercase().contains("string"));
+                assert!(e.message.to_ascii_lowercase().contains("string"));
+                assert_eq!(e.line, Some(1));
+                assert_eq!(e.snippet.as_deref(), Some("\"awagga\""));
+                assert!(e.hint.is_some());
             } else {
                 panic!("Wrong Error on bad_mod!");
             }
@@ -7,7 +7,7 @@ pub use local::LocalDatabase;
 /// Query the remote database of mods
 pub use remote::RemoteDatabase;
 
-fn fix_version(version: &str) -> &str {
+pub(crate) fn fix_version(version: &str) -> &str {
     version.trim().trim_start_matches('v')
 }
 
@@ -15,14 +15,17 @@ use zip::ZipArchive;
 
 use crate::{
     analytics::{send_analytics_event, AnalyticsEventName},
+    autoremove::DependencyMarks,
     config::Config,
     constants::OWML_UNIQUE_NAME,
     db::{LocalDatabase, RemoteDatabase},
     file::{check_file_matches_paths, create_all_parents, fix_json},
+    integrity::record_installed_files,
     mods::local::{get_paths_to_preserve, LocalMod, ModManifest},
     mods::remote::RemoteMod,
     progress::{ProgressAction, ProgressBar, ProgressType},
     remove::remove_old_mod_files,
+    snapshots::{try_take_snapshot, SnapshotReason},
     toggle::generate_config,
 };
 
@@ -260,11 +263,27 @@ pub async fn download_and_install_owml(
     } else {
         Ok(&owml.download_url)
     }?;
-    let target_path = PathBuf::from(&config.owml_path);
     let temp_dir = TempDir::new()?;
     let download_path = temp_dir.path().join("OWML.zip");
     download_zip(url, Some(OWML_UNIQUE_NAME), &download_path).await?;
-    extract_zip(&download_path, &target_path, "OWML")?;
+    install_owml_from_zip(&download_path, config)?;
+
+    temp_dir.close()?;
+
+    send_analytics_event(AnalyticsEventName::ModRequiredInstall, OWML_UNIQUE_NAME).await;
+
+    Ok(())
+}
+
+/// Install OWML from a local ZIP file to the path specified in `config.owml_path`
+///
+/// ## Errors
+///
+/// If we can't extract the zip file or save the config.
+///
+pub fn install_owml_from_zip(zip_path: &PathBuf, config: &Config) -> Result<()> {
+    let target_path = PathBuf::from(&config.owml_path);
+    extract_zip(zip_path, &target_path, "OWML")?;
 
     if config.owml_path.is_empty() {
         let mut new_config = config.clone();
@@ -272,10 +291,6 @@ pub async fn download_and_install_owml(
         new_config.save()?;
     }
 
-    temp_dir.close()?;
-
-    send_analytics_event(AnalyticsEventName::ModRequiredInstall, OWML_UNIQUE_NAME).await;
-
     Ok(())
 }
 
@@ -325,6 +340,7 @@ pub fn install_mod_from_zip(
             let local_mod = local_db.get_mod(&unique_name);
 
             if let Some(local_mod) = local_mod {
+                try_take_snapshot(config, local_mod, SnapshotReason::Update);
                 remove_old_mod_files(local_mod)?;
             }
 
@@ -341,6 +357,11 @@ pub fn install_mod_from_zip(
                 // First install, generate config
                 generate_config(&config_path)?;
             }
+            record_installed_files(&new_mod)?;
+            if local_mod.is_none() {
+                // Fresh installs are explicit unless whoever installed it marks it as a dependency after
+                DependencyMarks::unmark(config, &[unique_name])?;
+            }
             Ok(new_mod)
         }
         Err(why) => {
@@ -479,6 +500,8 @@ pub async fn install_mods_parallel(
 /// This should be the preferred method when installing a specific mod.
 /// It can also install prereleases and auto-install dependencies (recursively) as well.
 /// This will also send analytics events given you set `ANALYTICS_API_KEY`.
+/// The requested mod counts as explicitly installed, even if it was previously installed as a dependency.
+/// To update a mod without changing that, use [install_mods_parallel] instead.
 ///
 /// ## Errors
 ///
@@ -571,6 +594,8 @@ pub async fn install_mod_from_db(
     };
     let new_mod =
         install_mod_from_url(&target_url, Some(&remote_mod.unique_name), config, local_db).await?;
+    // The user asked for this mod specifically, so it's no longer only here as a dependency
+    DependencyMarks::unmark(config, std::slice::from_ref(unique_name))?;
 
     if recursive {
         let mut to_install: Vec<String> = new_mod.manifest.dependencies.unwrap_or_default();
@@ -615,12 +640,13 @@ pub async fn install_mod_from_db(
                 )
                 .await;
             }
-            installed.append(
-                &mut newly_installed
-                    .iter()
-                    .map(|m| m.manifest.unique_name.to_owned())
-                    .collect(),
-            );
+            let newly_installed_names: Vec<String> = newly_installed
+                .iter()
+                .map(|m| m.manifest.unique_name.to_owned())
+                .filter(|m| m != unique_name)
+                .collect();
+            DependencyMarks::mark_dependencies(config, &newly_installed_names)?;
+            installed.extend(newly_installed_names);
             for new_mod in newly_installed.into_iter() {
                 if let Some(mut deps) = new_mod.manifest.dependencies {
                     to_install.append(&mut deps);
@@ -812,6 +838,29 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_install_mod_from_db_unmarks_dependency() {
+        tokio_test::block_on(async {
+            let mut ctx = TestContext::new();
+            ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+            ctx.fetch_remote_db().await;
+            DependencyMarks::mark_dependencies(&ctx.config, &["Bwc9876.TimeSaver".to_string()])
+                .unwrap();
+            install_mod_from_db(
+                &"Bwc9876.TimeSaver".to_string(),
+                &ctx.config,
+                &ctx.remote_db,
+                &ctx.local_db,
+                false,
+                false,
+            )
+            .await
+            .unwrap();
+            let marks = DependencyMarks::get(&ctx.config).unwrap();
+            assert!(!marks.is_dependency("Bwc9876.TimeSaver"));
+        });
+    }
+
     async fn setup_recursive() -> TestContext {
         let mut ctx = TestContext::new();
         let mut new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
@@ -1,7 +1,7 @@
 use std::{
-    fs::{create_dir_all, read_to_string, File},
+    fs::{self, create_dir_all, read_to_string, remove_dir_all, File},
     io::{BufReader, BufWriter, Write},
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
 use anyhow::{anyhow, Result};
@@ -188,6 +188,71 @@ pub fn create_all_parents(file_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Check that a unique name is safe to use as a folder or file name.
+/// Unique names come from files we don't control, so they have to be a single plain name.
+///
+/// ## Errors
+///
+/// If the unique name is empty, contains a separator, or is something like `..`
+///
+pub(crate) fn check_unique_name(unique_name: &str) -> Result<()> {
+    let mut components = Path::new(unique_name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) if name == unique_name => Ok(()),
+        _ => Err(anyhow!("Invalid unique name: \"{}\"", unique_name)),
+    }
+}
+
+/// Get the folder a mod with the given unique name should be in inside `mods_path`
+///
+/// ## Errors
+///
+/// If the unique name isn't safe to use, see [check_unique_name]
+///
+pub(crate) fn mod_folder_path(mods_path: &Path, unique_name: &str) -> Result<PathBuf> {
+    check_unique_name(unique_name)?;
+    let path = mods_path.join(unique_name);
+    if !path.starts_with(mods_path) || path == mods_path {
+        return Err(anyhow!("Invalid unique name: \"{}\"", unique_name));
+    }
+    Ok(path)
+}
+
+/// Recursively copy a folder and everything in it to `to`, creating `to` if it doesn't exist
+///
+/// ## Errors
+///
+/// If we can't read a folder or copy a file
+///
+pub fn copy_dir_all(from: &Path, to: &Path) -> Result<()> {
+    create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Move a folder to `to`, `to` shouldn't exist yet but its parent should.
+/// If the folder can't be renamed (usually because it's on another drive) it's copied then deleted.
+///
+/// ## Errors
+///
+/// If we can't copy the folder or delete the original
+///
+pub fn move_dir(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_err() {
+        copy_dir_all(from, to)?;
+        remove_dir_all(from)?;
+    }
+    Ok(())
+}
+
 fn fix_bom(str: &str) -> &str {
     str.strip_prefix('\u{FEFF}').unwrap_or(str)
 }
@@ -219,4 +284,28 @@ mod tests {
         assert!(check_file_matches_paths(test_path, &[test_parent]));
         assert!(!check_file_matches_paths(test_path, &[unrelated_parent]),);
     }
+
+    #[test]
+    fn test_check_unique_name() {
+        assert!(check_unique_name("Bwc9876.TimeSaver").is_ok());
+        for name in ["", ".", "..", "../Evil", "Evil/..", "a/b", "a/", "/abs"] {
+            assert!(check_unique_name(name).is_err(), "{name}");
+        }
+        #[cfg(windows)]
+        assert!(check_unique_name("a\\b").is_err());
+    }
+
+    #[test]
+    fn test_mod_folder_path() {
+        let mods_path = Path::new("Mods");
+        assert_eq!(
+            mod_folder_path(mods_path, "Bwc9876.TimeSaver").unwrap(),
+            mods_path.join("Bwc9876.TimeSaver")
+        );
+        for name in ["", ".", "..", "../Evil", "Evil/..", "a/b", "a/", "/abs"] {
+            assert!(mod_folder_path(mods_path, name).is_err(), "{name}");
+        }
+        #[cfg(windows)]
+        assert!(mod_folder_path(mods_path, "a\\b").is_err());
+    }
 }
@@ -0,0 +1,334 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use time::{macros::format_description, OffsetDateTime};
+
+use crate::{
+    constants::GAME_LOGS_DIR_NAME,
+    file::{create_all_parents, get_app_path},
+    socket::{unix_millis, LogServerSender, SocketMessage},
+};
+
+/// A [SocketMessage] along with when it was received, each line of a game log file is one of these
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggedMessage {
+    /// When the message was received, in milliseconds since the Unix epoch
+    pub received_at: u64,
+    /// The message itself
+    #[serde(flatten)]
+    pub message: SocketMessage,
+}
+
+impl LoggedMessage {
+    /// Stamp a message with when it was received, this is [SocketMessage::received_at] if the log server set it
+    /// and the current time otherwise.
+    pub fn now(mut message: SocketMessage) -> Self {
+        Self {
+            // Taken out of the message so it isn't written twice
+            received_at: message.received_at.take().unwrap_or_else(unix_millis),
+            message,
+        }
+    }
+}
+
+fn session_path(game_logs_dir: &Path, time: SystemTime, port: u16) -> Result<PathBuf> {
+    let time = OffsetDateTime::from(time);
+    let date = time.format(format_description!("[year]-[month]-[day]"))?;
+    let time = time.format(format_description!("[hour]-[minute]-[second]"))?;
+    Ok(game_logs_dir
+        .join(date)
+        .join(format!("{time}_{port}.jsonl")))
+}
+
+/// Get the path to store a new game log session for the given port in.
+/// Sessions are stored in the app data directory under `game_logs/<date>/<time>_<port>.jsonl` (in UTC).
+///
+/// ## Errors
+///
+/// If we can't get the app data directory.
+///
+pub fn new_session_path(port: u16) -> Result<PathBuf> {
+    session_path(
+        &get_app_path()?.join(GAME_LOGS_DIR_NAME),
+        SystemTime::now(),
+        port,
+    )
+}
+
+/// Writes [SocketMessage]s to a file as JSON Lines, each stamped with when it was received.
+pub struct GameLogWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl GameLogWriter {
+    /// Create a writer that appends to the given file, creating it (and its parent folders) if needed.
+    ///
+    /// ## Errors
+    ///
+    /// If we can't create or open the file.
+    ///
+    pub fn new(path: &Path) -> Result<Self> {
+        create_all_parents(path)?;
+        let file = File::options().append(true).create(true).open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// Create a writer for a new session on the given port, see [new_session_path].
+    ///
+    /// ## Errors
+    ///
+    /// If we can't get the app data directory or create the file.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use owmods_core::game_logs::GameLogWriter;
+    /// use owmods_core::socket::{SocketMessage, SocketMessageType};
+    ///
+    /// let mut writer = GameLogWriter::for_session(12345).unwrap();
+    /// writer.write(&SocketMessage::make_internal("Hello!", SocketMessageType::Info)).unwrap();
+    /// println!("Saved to {}", writer.path().display());
+    /// ```
+    ///
+    pub fn for_session(port: u16) -> Result<Self> {
+        Self::new(&new_session_path(port)?)
+    }
+
+    /// The path of the file being written to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write a message that was just received
+    ///
+    /// ## Errors
+    ///
+    /// If we can't write to the file.
+    ///
+    pub fn write(&mut self, message: &SocketMessage) -> Result<()> {
+        self.write_logged(&LoggedMessage::now(message.clone()))
+    }
+
+    /// Write a message that's already been stamped
+    ///
+    /// ## Errors
+    ///
+    /// If we can't write to the file.
+    ///
+    pub fn write_logged(&mut self, message: &LoggedMessage) -> Result<()> {
+        writeln!(self.writer, "{}", serde_json::to_string(message)?)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Read a game log session written by [GameLogWriter].
+/// Lines that can't be read (for example if the manager was closed mid-write) are skipped with a warning.
+/// Sessions compressed by [crate::log_retention] (ending in `.gz`) can be read too.
+/// Each message's [SocketMessage::received_at] is set to when it was originally received, so replays keep the original timings.
+///
+/// ## Errors
+///
+/// If we can't open or read the file.
+///
+pub fn read_game_log(path: &Path) -> Result<Vec<LoggedMessage>> {
+    let file = File::open(path)?;
+    let reader: BufReader<Box<dyn Read>> = if path.extension().is_some_and(|e| e == "gz") {
+        BufReader::new(Box::new(GzDecoder::new(file)))
+    } else {
+        BufReader::new(Box::new(file))
+    };
+    let mut messages: Vec<LoggedMessage> = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<LoggedMessage>(&line) {
+            Ok(mut logged) => {
+                logged.message.received_at = Some(logged.received_at);
+                messages.push(logged);
+            }
+            Err(why) => warn!("Skipping line {} of {}: {}", i + 1, path.display(), why),
+        }
+    }
+    Ok(messages)
+}
+
+/// Replay a game log session into a log server channel, as if the messages were coming from the game.
+///
+/// ## Returns
+///
+/// The number of messages that were replayed
+///
+/// ## Errors
+///
+/// If we can't read the file or the receiving end of the channel was closed.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::game_logs::replay_game_log;
+/// use std::path::Path;
+///
+/// # tokio_test::block_on(async {
+/// let (tx, mut rx) = tokio::sync::mpsc::channel(32);
+/// let path = Path::new("session.jsonl");
+///
+/// tokio::join!(async { replay_game_log(path, tx).await.unwrap() }, async {
+///     while let Some(message) = rx.recv().await {
+///         println!("{}", message.message);
+///     }
+/// });
+/// # });
+/// ```
+///
+pub async fn replay_game_log(path: &Path, tx: LogServerSender) -> Result<usize> {
+    let messages = read_game_log(path)?;
+    let count = messages.len();
+    for logged in messages {
+        tx.send(logged.message).await?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use flate2::{write::GzEncoder, Compression};
+    use tokio::sync::mpsc;
+
+    use crate::{socket::SocketMessageType, test_utils::make_test_dir};
+
+    use super::*;
+
+    #[test]
+    fn test_session_path() {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1700000000);
+        let path = session_path(Path::new("game_logs"), time, 1234).unwrap();
+        assert_eq!(
+            path,
+            Path::new("game_logs")
+                .join("2023-11-14")
+                .join("22-13-20_1234.jsonl")
+        );
+    }
+
+    #[test]
+    fn test_write_read_game_log() {
+        let dir = make_test_dir();
+        let path = dir.path().join("logs").join("session.jsonl");
+        let mut writer = GameLogWriter::new(&path).unwrap();
+        let first = SocketMessage::make_internal("Hello", SocketMessageType::Info);
+        let second = SocketMessage::make_internal("Oh no", SocketMessageType::Error);
+        writer.write(&first).unwrap();
+        writer.write(&second).unwrap();
+        fs::write(
+            &path,
+            format!("{}not json\n\n", fs::read_to_string(&path).unwrap()),
+        )
+        .unwrap();
+        let messages = read_game_log(&path).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].message.is_repeat_of(&first));
+        assert!(messages[1].message.is_repeat_of(&second));
+        assert!(messages[0].received_at <= messages[1].received_at);
+        assert_eq!(
+            messages[0].message.received_at,
+            Some(messages[0].received_at)
+        );
+    }
+
+    #[test]
+    fn test_game_log_keeps_server_stamps() {
+        let dir = make_test_dir();
+        let path = dir.path().join("session.jsonl");
+        let message = SocketMessage {
+            timestamp: Some(900),
+            received_at: Some(1000),
+            sequence: Some(3),
+            ..SocketMessage::make_internal("Hello", SocketMessageType::Info)
+        };
+        GameLogWriter::new(&path).unwrap().write(&message).unwrap();
+        let line = fs::read_to_string(&path).unwrap();
+        assert_eq!(line.matches("receivedAt").count(), 1);
+        let messages = read_game_log(&path).unwrap();
+        assert_eq!(messages[0].received_at, 1000);
+        assert_eq!(messages[0].message, message);
+    }
+
+    #[test]
+    fn test_read_compressed_game_log() {
+        let dir = make_test_dir();
+        let path = dir.path().join("session.jsonl.gz");
+        let message = SocketMessage::make_internal("Hello", SocketMessageType::Info);
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        writeln!(
+            encoder,
+            "{}",
+            serde_json::to_string(&LoggedMessage::now(message.clone())).unwrap()
+        )
+        .unwrap();
+        encoder.finish().unwrap();
+        let messages = read_game_log(&path).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].message.is_repeat_of(&message));
+    }
+
+    #[test]
+    fn test_read_game_log_from_game_format() {
+        let dir = make_test_dir();
+        let path = dir.path().join("session.jsonl");
+        fs::write(
+            &path,
+            "{\"receivedAt\": 5, \"type\": 2, \"message\": \"Careful\", \"senderName\": \"Test\"}\n",
+        )
+        .unwrap();
+        let messages = read_game_log(&path).unwrap();
+        assert_eq!(messages[0].received_at, 5);
+        assert_eq!(messages[0].message.message_type, SocketMessageType::Warning);
+        assert_eq!(messages[0].message.sender_name.as_deref(), Some("Test"));
+    }
+
+    #[test]
+    fn test_replay_game_log() {
+        tokio_test::block_on(async {
+            let dir = make_test_dir();
+            let path = dir.path().join("session.jsonl");
+            let mut writer = GameLogWriter::new(&path).unwrap();
+            for i in 0..5 {
+                writer
+                    .write(&SocketMessage::make_internal(
+                        &format!("Message {i}"),
+                        SocketMessageType::Message,
+                    ))
+                    .unwrap();
+            }
+            let (tx, mut rx) = mpsc::channel(2);
+            let (count, received) = tokio::join!(replay_game_log(&path, tx), async {
+                let mut received = vec![];
+                while let Some(msg) = rx.recv().await {
+                    received.push(msg.message);
+                }
+                received
+            });
+            assert_eq!(count.unwrap(), 5);
+            assert_eq!(received[4], "Message 4");
+        });
+    }
+}
@@ -0,0 +1,596 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use typeshare::typeshare;
+
+use crate::db::{LocalDatabase, RemoteDatabase};
+
+/// A mod in the dependency graph
+#[typeshare]
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModGraphNode {
+    /// The unique name of the mod
+    pub unique_name: String,
+    /// The display name of the mod, this is the unique name if the mod isn't installed and wasn't found in the remote database
+    pub name: String,
+    /// The installed version of the mod, or the latest version in the remote database if it isn't installed
+    pub version: Option<String>,
+    /// Whether the mod is installed
+    pub installed: bool,
+    /// Whether the mod is enabled, always false if the mod isn't installed
+    pub enabled: bool,
+}
+
+/// The kind of relation an edge represents
+#[typeshare]
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModGraphEdgeKind {
+    /// `from` depends on `to`
+    Dependency,
+    /// `from` declares it conflicts with `to`
+    Conflict,
+}
+
+/// A relation between two mods in the dependency graph
+#[typeshare]
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModGraphEdge {
+    /// The unique name of the mod that declares this relation
+    pub from: String,
+    /// The unique name of the mod the relation points to
+    pub to: String,
+    /// What kind of relation this is
+    pub kind: ModGraphEdgeKind,
+}
+
+/// The dependency and conflict graph of installed mods
+#[typeshare]
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ModGraph {
+    /// All mods in the graph, sorted by unique name
+    pub nodes: Vec<ModGraphNode>,
+    /// All dependencies and conflicts between mods in the graph
+    pub edges: Vec<ModGraphEdge>,
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl ModGraph {
+    /// Build the graph from all valid mods in the local database.
+    ///
+    /// Dependencies and conflicts that aren't installed are still added as nodes,
+    /// pass a remote database to fill in their name and version.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use owmods_core::graph::ModGraph;
+    /// use owmods_core::db::LocalDatabase;
+    /// use owmods_core::config::Config;
+    ///
+    /// let config = Config::get(None).unwrap();
+    /// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+    ///
+    /// let graph = ModGraph::build(&local_db, None);
+    /// println!("{}", graph.to_dot());
+    /// ```
+    ///
+    pub fn build(local_db: &LocalDatabase, remote_db: Option<&RemoteDatabase>) -> Self {
+        let mut nodes: BTreeMap<String, ModGraphNode> = BTreeMap::new();
+        let mut edges: Vec<ModGraphEdge> = vec![];
+
+        for local_mod in local_db.valid() {
+            nodes.insert(
+                local_mod.manifest.unique_name.clone(),
+                ModGraphNode {
+                    unique_name: local_mod.manifest.unique_name.clone(),
+                    name: local_mod.manifest.name.clone(),
+                    version: Some(local_mod.manifest.version.clone()),
+                    installed: true,
+                    enabled: local_mod.enabled,
+                },
+            );
+            let relations = [
+                (
+                    &local_mod.manifest.dependencies,
+                    ModGraphEdgeKind::Dependency,
+                ),
+                (&local_mod.manifest.conflicts, ModGraphEdgeKind::Conflict),
+            ];
+            for (targets, kind) in relations {
+                for target in targets.iter().flatten() {
+                    edges.push(ModGraphEdge {
+                        from: local_mod.manifest.unique_name.clone(),
+                        to: target.clone(),
+                        kind,
+                    });
+                }
+            }
+        }
+
+        for edge in edges.iter() {
+            if !nodes.contains_key(&edge.to) {
+                let remote_mod = remote_db.and_then(|db| db.get_mod(&edge.to));
+                nodes.insert(
+                    edge.to.clone(),
+                    ModGraphNode {
+                        unique_name: edge.to.clone(),
+                        name: remote_mod
+                            .map(|m| m.name.clone())
+                            .unwrap_or_else(|| edge.to.clone()),
+                        version: remote_mod.map(|m| m.version.clone()),
+                        installed: false,
+                        enabled: false,
+                    },
+                );
+            }
+        }
+
+        edges.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+
+        Self {
+            nodes: nodes.into_values().collect(),
+            edges,
+        }
+    }
+
+    /// Get a node in the graph by its unique name
+    pub fn get_node(&self, unique_name: &str) -> Option<&ModGraphNode> {
+        self.nodes.iter().find(|n| n.unique_name == unique_name)
+    }
+
+    /// Get the unique names of the mods the given mod has a relation of `kind` with
+    pub fn targets(&self, unique_name: &str, kind: ModGraphEdgeKind) -> Vec<&str> {
+        self.edges
+            .iter()
+            .filter(|e| e.kind == kind && e.from == unique_name)
+            .map(|e| e.to.as_str())
+            .collect()
+    }
+
+    /// Whether any mods in the graph are depended on or conflicted with but aren't installed
+    pub fn has_missing(&self) -> bool {
+        self.nodes.iter().any(|n| !n.installed)
+    }
+
+    /// Get only the part of the graph that's reachable from the given mod through dependencies.
+    /// If `reverse` is set, this follows dependents instead.
+    ///
+    /// Conflicts of any mod that's kept are kept as well.
+    ///
+    pub fn subgraph(&self, root: &str, reverse: bool) -> Self {
+        let mut keep: HashSet<&str> = HashSet::new();
+        let mut to_visit: Vec<&str> = vec![root];
+        while let Some(current) = to_visit.pop() {
+            if !keep.insert(current) {
+                continue;
+            }
+            for edge in self
+                .edges
+                .iter()
+                .filter(|e| e.kind == ModGraphEdgeKind::Dependency)
+            {
+                if reverse && edge.to == current {
+                    to_visit.push(&edge.from);
+                } else if !reverse && edge.from == current {
+                    to_visit.push(&edge.to);
+                }
+            }
+        }
+        let edges: Vec<ModGraphEdge> = self
+            .edges
+            .iter()
+            .filter(|e| {
+                keep.contains(e.from.as_str())
+                    && (e.kind == ModGraphEdgeKind::Conflict || keep.contains(e.to.as_str()))
+            })
+            .cloned()
+            .collect();
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|n| {
+                keep.contains(n.unique_name.as_str()) || edges.iter().any(|e| e.to == n.unique_name)
+            })
+            .cloned()
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// Export the graph in the [DOT format](https://graphviz.org/doc/info/lang.html).
+    ///
+    /// Mods that aren't installed are dashed, disabled mods are gray, and conflicts are red dashed edges.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph mods {\n");
+        for node in self.nodes.iter() {
+            let label = match node.version.as_ref() {
+                Some(version) => format!("{}\\nv{}", escape_dot(&node.name), escape_dot(version)),
+                None => escape_dot(&node.name),
+            };
+            let style = if !node.installed {
+                ", style=dashed"
+            } else if !node.enabled {
+                ", color=gray, fontcolor=gray"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "    \"{}\" [label=\"{}\"{}];\n",
+                escape_dot(&node.unique_name),
+                label,
+                style
+            ));
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.kind {
+                ModGraphEdgeKind::Dependency => "",
+                ModGraphEdgeKind::Conflict => " [color=red, style=dashed, label=\"conflicts\"]",
+            };
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\"{};\n",
+                escape_dot(&edge.from),
+                escape_dot(&edge.to),
+                style
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Export the graph as JSON
+    ///
+    /// ## Errors
+    ///
+    /// If we can't serialize the graph
+    ///
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    fn installed_names(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|n| n.installed)
+            .map(|n| n.unique_name.as_str())
+            .collect()
+    }
+
+    fn node_label(&self, unique_name: &str) -> String {
+        match self.get_node(unique_name) {
+            Some(node) => {
+                let mut label = if node.name == node.unique_name {
+                    node.unique_name.clone()
+                } else {
+                    format!("{} ({})", node.name, node.unique_name)
+                };
+                if let Some(version) = node.version.as_ref() {
+                    label.push_str(&format!(" v{}", version));
+                }
+                if !node.installed {
+                    label.push_str(" [missing]");
+                } else if !node.enabled {
+                    label.push_str(" [disabled]");
+                }
+                label
+            }
+            None => format!("{} [missing]", unique_name),
+        }
+    }
+
+    /// Render the dependencies of a mod as a tree for the terminal.
+    /// If no mod is given, a tree is rendered for every installed mod that no other installed mod depends on,
+    /// then for any installed mod that wasn't in one of those trees (mods that are only depended on in a cycle).
+    ///
+    /// Circular dependencies are marked and not followed again, conflicts are listed under the mod that declares them.
+    ///
+    pub fn to_tree(&self, root: Option<&str>) -> String {
+        let roots: Vec<&str> = match root {
+            Some(root) => vec![root],
+            None => self
+                .nodes
+                .iter()
+                .filter(|n| {
+                    n.installed
+                        && !self.edges.iter().any(|e| {
+                            e.kind == ModGraphEdgeKind::Dependency && e.to == n.unique_name
+                        })
+                })
+                .map(|n| n.unique_name.as_str())
+                .collect(),
+        };
+        let rest = if root.is_none() {
+            self.installed_names()
+        } else {
+            vec![]
+        };
+        render_trees(
+            &roots,
+            &rest,
+            &|unique_name| self.node_label(unique_name),
+            &|unique_name| {
+                let mut children: Vec<TreeChild> = self
+                    .targets(unique_name, ModGraphEdgeKind::Dependency)
+                    .into_iter()
+                    .map(|d| TreeChild::Node(d.to_string()))
+                    .collect();
+                children.extend(
+                    self.targets(unique_name, ModGraphEdgeKind::Conflict)
+                        .into_iter()
+                        .map(|c| TreeChild::Note(format!("conflicts with {}", self.node_label(c)))),
+                );
+                children
+            },
+        )
+    }
+}
+
+/// Render the mods that depend on a mod as a tree for the terminal, using [LocalDatabase::dependent].
+/// If no mod is given, a tree is rendered for every installed mod that doesn't depend on any installed mod,
+/// then for any installed mod that wasn't in one of those trees (mods that only depend on mods in a cycle).
+///
+/// ## Errors
+///
+/// If the given mod isn't installed
+///
+pub fn dependents_tree(local_db: &LocalDatabase, root: Option<&str>) -> Result<String> {
+    let graph = ModGraph::build(local_db, None);
+    let roots: Vec<&str> = match root {
+        Some(root) => {
+            local_db
+                .get_mod(root)
+                .ok_or_else(|| anyhow!("Mod {} Is Not Installed", root))?;
+            vec![root]
+        }
+        None => graph
+            .nodes
+            .iter()
+            .filter(|n| {
+                n.installed
+                    && graph
+                        .targets(&n.unique_name, ModGraphEdgeKind::Dependency)
+                        .iter()
+                        .all(|d| local_db.get_mod(d).is_none())
+            })
+            .map(|n| n.unique_name.as_str())
+            .collect(),
+    };
+    let rest = if root.is_none() {
+        graph.installed_names()
+    } else {
+        vec![]
+    };
+    Ok(render_trees(
+        &roots,
+        &rest,
+        &|unique_name| graph.node_label(unique_name),
+        &|unique_name| {
+            let mut dependents: Vec<TreeChild> = local_db
+                .get_mod(unique_name)
+                .map(|m| {
+                    local_db
+                        .dependent(m)
+                        .map(|d| TreeChild::Node(d.manifest.unique_name.clone()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            dependents.sort_by(|a, b| a.text().cmp(b.text()));
+            dependents
+        },
+    ))
+}
+
+enum TreeChild {
+    Node(String),
+    Note(String),
+}
+
+impl TreeChild {
+    fn text(&self) -> &str {
+        match self {
+            TreeChild::Node(text) | TreeChild::Note(text) => text,
+        }
+    }
+}
+
+// Renders a tree for each root, then for each of `rest` that wasn't in any of the trees so far
+fn render_trees(
+    roots: &[&str],
+    rest: &[&str],
+    label: &dyn Fn(&str) -> String,
+    children: &dyn Fn(&str) -> Vec<TreeChild>,
+) -> String {
+    let mut out = String::new();
+    let mut visited: HashSet<String> = HashSet::new();
+    let rest = rest.iter().filter(|r| !roots.contains(r));
+    for root in roots.iter().chain(rest) {
+        if !visited.insert(root.to_string()) && !roots.contains(root) {
+            continue;
+        }
+        out.push_str(&label(root));
+        out.push('\n');
+        let mut stack = vec![root.to_string()];
+        render_children(
+            &mut out,
+            root,
+            "",
+            &mut stack,
+            &mut visited,
+            label,
+            children,
+        );
+    }
+    out
+}
+
+fn render_children(
+    out: &mut String,
+    unique_name: &str,
+    prefix: &str,
+    stack: &mut Vec<String>,
+    visited: &mut HashSet<String>,
+    label: &dyn Fn(&str) -> String,
+    children: &dyn Fn(&str) -> Vec<TreeChild>,
+) {
+    let children_list = children(unique_name);
+    let count = children_list.len();
+    for (i, child) in children_list.into_iter().enumerate() {
+        let last = i == count - 1;
+        let (branch, next_prefix) = if last {
+            ("└── ", format!("{}    ", prefix))
+        } else {
+            ("├── ", format!("{}│   ", prefix))
+        };
+        out.push_str(prefix);
+        out.push_str(branch);
+        match child {
+            TreeChild::Note(text) => {
+                out.push_str(&text);
+                out.push('\n');
+            }
+            TreeChild::Node(child) => {
+                visited.insert(child.clone());
+                out.push_str(&label(&child));
+                if stack.contains(&child) {
+                    out.push_str(" [circular]\n");
+                } else {
+                    out.push('\n');
+                    stack.push(child.clone());
+                    render_children(out, &child, &next_prefix, stack, visited, label, children);
+                    stack.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::mods::local::{LocalMod, UnsafeLocalMod};
+
+    use super::*;
+
+    fn make_db() -> LocalDatabase {
+        let mut mod_a = LocalMod::get_test(0);
+        mod_a.manifest.dependencies = Some(vec![
+            "Example.TestMod1".to_string(),
+            "Example.Missing".to_string(),
+        ]);
+        mod_a.manifest.conflicts = Some(vec!["Example.TestMod2".to_string()]);
+        let mut mod_b = LocalMod::get_test(1);
+        mod_b.manifest.dependencies = Some(vec!["Example.TestMod0".to_string()]);
+        let mut mod_c = LocalMod::get_test(2);
+        mod_c.enabled = false;
+        let mut db = LocalDatabase::default();
+        for local_mod in [mod_a, mod_b, mod_c] {
+            db.mods.insert(
+                local_mod.manifest.unique_name.clone(),
+                UnsafeLocalMod::Valid(local_mod),
+            );
+        }
+        db
+    }
+
+    #[test]
+    fn test_build_graph() {
+        let db = make_db();
+        let graph = ModGraph::build(&db, None);
+        assert_eq!(graph.nodes.len(), 4);
+        assert!(graph.has_missing());
+        assert!(!graph.get_node("Example.Missing").unwrap().installed);
+        assert_eq!(
+            graph.targets("Example.TestMod0", ModGraphEdgeKind::Dependency),
+            vec!["Example.Missing", "Example.TestMod1"]
+        );
+        assert_eq!(
+            graph.targets("Example.TestMod0", ModGraphEdgeKind::Conflict),
+            vec!["Example.TestMod2"]
+        );
+    }
+
+    #[test]
+    fn test_subgraph() {
+        let db = make_db();
+        let graph = ModGraph::build(&db, None);
+        let sub = graph.subgraph("Example.Missing", true);
+        let names: Vec<&str> = sub.nodes.iter().map(|n| n.unique_name.as_str()).collect();
+        // TestMod2 is kept because TestMod0 conflicts with it
+        assert_eq!(
+            names,
+            vec![
+                "Example.Missing",
+                "Example.TestMod0",
+                "Example.TestMod1",
+                "Example.TestMod2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let db = make_db();
+        let dot = ModGraph::build(&db, None).to_dot();
+        assert!(dot.starts_with("digraph mods {"));
+        assert!(dot.contains("\"Example.TestMod0\" -> \"Example.TestMod1\";"));
+        assert!(dot.contains("\"Example.Missing\" [label=\"Example.Missing\", style=dashed];"));
+        assert!(dot.contains("\"Example.TestMod0\" -> \"Example.TestMod2\" [color=red"));
+    }
+
+    #[test]
+    fn test_to_tree_circular() {
+        let db = make_db();
+        let tree = ModGraph::build(&db, None).to_tree(Some("Example.TestMod0"));
+        let lines: Vec<&str> = tree.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("├── Example.Missing [missing]"));
+        assert!(lines[2].starts_with("├── "));
+        assert!(lines[3].ends_with("[circular]"));
+        assert!(lines[4].starts_with("└── conflicts with "));
+        assert!(lines[4].ends_with("[disabled]"));
+    }
+
+    #[test]
+    fn test_dependents_tree() {
+        let db = make_db();
+        let tree = dependents_tree(&db, Some("Example.TestMod1")).unwrap();
+        let lines: Vec<&str> = tree.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("└── "));
+        assert!(lines[2].ends_with("[circular]"));
+        assert!(dependents_tree(&db, Some("Example.Missing")).is_err());
+    }
+
+    #[test]
+    fn test_to_tree_all_cycle() {
+        let db = make_db();
+        // TestMod0 and TestMod1 depend on each other, so neither is a root
+        let tree = ModGraph::build(&db, None).to_tree(None);
+        let roots: Vec<&str> = tree
+            .lines()
+            .filter(|l| !l.starts_with([' ', '├', '└', '│']))
+            .collect();
+        assert_eq!(roots.len(), 2);
+        assert!(roots[0].contains("(Example.TestMod2)"));
+        assert!(roots[1].contains("(Example.TestMod0)"));
+        assert_eq!(tree.matches("[circular]").count(), 1);
+    }
+
+    #[test]
+    fn test_dependents_tree_all_cycle() {
+        let db = make_db();
+        let tree = dependents_tree(&db, None).unwrap();
+        let roots: Vec<&str> = tree
+            .lines()
+            .filter(|l| !l.starts_with([' ', '├', '└', '│']))
+            .collect();
+        assert_eq!(roots.len(), 2);
+        assert!(roots[1].contains("(Example.TestMod0)"));
+        assert_eq!(tree.matches("(Example.TestMod1)").count(), 1);
+    }
+}
@@ -0,0 +1,349 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{copy, BufReader},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use glob::glob;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use typeshare::typeshare;
+
+use crate::{
+    config::Config,
+    constants::INSTALLED_FILES_NAME,
+    db::{LocalDatabase, RemoteDatabase},
+    download::install_mods_parallel,
+    file::{check_file_matches_paths, deserialize_from_json, serialize_to_json},
+    mods::local::{get_paths_to_preserve, LocalMod},
+};
+
+/// A single file that was installed as part of a mod
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct InstalledFile {
+    /// The path of the file relative to the mod's folder, always separated by `/`
+    pub path: String,
+    /// The size of the file in bytes
+    pub size: u64,
+    /// The SHA-256 hash of the file, hex encoded
+    pub hash: String,
+}
+
+/// The list of files that were installed for a mod, this is stored in the mod's folder at install time
+/// so we can check if the mod was modified later on.
+#[derive(Serialize, Deserialize, Default)]
+pub struct InstalledFiles {
+    /// The files that were installed
+    pub files: Vec<InstalledFile>,
+}
+
+impl InstalledFiles {
+    /// Get the path the file list is stored at for a mod
+    pub fn path(mod_path: &Path) -> PathBuf {
+        mod_path.join(INSTALLED_FILES_NAME)
+    }
+
+    /// Get the file list for the mod at the given path, if there isn't one we get [None].
+    ///
+    /// ## Errors
+    ///
+    /// If the file exists but we can't read or deserialize it.
+    ///
+    pub fn get(mod_path: &Path) -> Result<Option<Self>> {
+        let path = Self::path(mod_path);
+        if path.is_file() {
+            Ok(Some(deserialize_from_json(&path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Scan the given mod folder and build a list of all files in it, skipping any paths to preserve.
+    ///
+    /// ## Errors
+    ///
+    /// If we can't read any of the files in the folder.
+    ///
+    pub fn scan(mod_path: &Path, preserve_paths: &[PathBuf]) -> Result<Self> {
+        let mut files = scan_mod_files(mod_path, preserve_paths)?
+            .into_iter()
+            .map(|(rel_path, path)| hash_file(&rel_path, &path))
+            .collect::<Result<Vec<_>>>()?;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(Self { files })
+    }
+
+    /// Save the file list to the given mod folder
+    ///
+    /// ## Errors
+    ///
+    /// If we can't save to the file
+    ///
+    pub fn save(&self, mod_path: &Path) -> Result<()> {
+        serialize_to_json(self, &Self::path(mod_path), false)
+    }
+}
+
+/// The result of checking a mod's files against what was installed
+#[typeshare]
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ModIntegrityReport {
+    /// The unique name of the mod this report is for
+    pub unique_name: String,
+    /// Files that were installed but aren't there anymore
+    pub missing: Vec<String>,
+    /// Files that are there but have changed since install
+    pub modified: Vec<String>,
+    /// Files that weren't installed with the mod
+    pub extra: Vec<String>,
+}
+
+impl ModIntegrityReport {
+    /// Whether the mod's files match exactly what was installed
+    pub fn is_intact(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// Turn a path relative to a mod's folder into a string that's always separated by `/`
+pub(crate) fn to_record_path(rel_path: &Path) -> String {
+    rel_path
+        .iter()
+        .map(|c| c.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn scan_mod_files(mod_path: &Path, preserve_paths: &[PathBuf]) -> Result<HashMap<String, PathBuf>> {
+    let glob_matches = glob(mod_path.join("**").join("*").to_str().unwrap())?;
+    let mut files: HashMap<String, PathBuf> = HashMap::new();
+    for glob_match in glob_matches {
+        let path = glob_match?;
+        if !path.is_file() {
+            continue;
+        }
+        let rel_path = path.strip_prefix(mod_path)?;
+        if rel_path == Path::new(INSTALLED_FILES_NAME)
+            || check_file_matches_paths(rel_path, preserve_paths)
+        {
+            continue;
+        }
+        files.insert(to_record_path(rel_path), path);
+    }
+    Ok(files)
+}
+
+fn hash_file(rel_path: &str, path: &Path) -> Result<InstalledFile> {
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut hasher = Sha256::new();
+    copy(&mut BufReader::new(file), &mut hasher)?;
+    let hash = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(InstalledFile {
+        path: rel_path.to_string(),
+        size,
+        hash,
+    })
+}
+
+/// Record the files of a freshly installed (or updated) mod so we can verify them later.
+/// Paths the mod wants to preserve (including `config.json` and `save.json`) aren't recorded.
+///
+/// ## Errors
+///
+/// If we can't read the mod's files or save the list.
+///
+pub fn record_installed_files(local_mod: &LocalMod) -> Result<()> {
+    let mod_path = PathBuf::from(&local_mod.mod_path);
+    debug!(
+        "Recording installed files for {}",
+        local_mod.manifest.unique_name
+    );
+    let installed = InstalledFiles::scan(&mod_path, &get_paths_to_preserve(Some(local_mod)))?;
+    installed.save(&mod_path)
+}
+
+/// Check a mod's files against the files recorded when it was installed.
+/// Paths the mod wants to preserve are skipped as they're expected to change.
+///
+/// ## Returns
+///
+/// A report of missing, modified, and extra files, or [None] if the mod has no record of its installed files
+/// (for example if it was installed before the manager started keeping track).
+///
+/// ## Errors
+///
+/// If we can't read the record or the mod's files.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::integrity::verify_mod;
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+/// let local_mod = local_db.get_mod("Bwc9876.TimeSaver").unwrap();
+///
+/// if let Some(report) = verify_mod(local_mod).unwrap() {
+///     for missing in report.missing {
+///         println!("{} is missing", missing);
+///     }
+/// }
+/// ```
+///
+pub fn verify_mod(local_mod: &LocalMod) -> Result<Option<ModIntegrityReport>> {
+    let mod_path = PathBuf::from(&local_mod.mod_path);
+    let installed = InstalledFiles::get(&mod_path)?;
+    if let Some(installed) = installed {
+        let mut current = scan_mod_files(&mod_path, &get_paths_to_preserve(Some(local_mod)))?;
+        let mut report = ModIntegrityReport {
+            unique_name: local_mod.manifest.unique_name.clone(),
+            ..Default::default()
+        };
+        for file in installed.files.iter() {
+            if let Some(path) = current.remove(&file.path) {
+                // Only bother hashing if the size is the same
+                let changed = path.metadata()?.len() != file.size
+                    || hash_file(&file.path, &path)?.hash != file.hash;
+                if changed {
+                    report.modified.push(file.path.clone());
+                }
+            } else {
+                report.missing.push(file.path.clone());
+            }
+        }
+        report.extra = current.into_keys().collect();
+        report.extra.sort();
+        Ok(Some(report))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Verify all valid mods in the local database, see [verify_mod].
+///
+/// ## Returns
+///
+/// A tuple containing:
+/// - Reports for all mods that have a record of their files and **don't match it**
+/// - The unique names of mods that don't have a record and can't be verified
+///
+/// ## Errors
+///
+/// If we can't read the record or files of any mod.
+///
+pub fn verify_mods(local_db: &LocalDatabase) -> Result<(Vec<ModIntegrityReport>, Vec<String>)> {
+    let mut reports: Vec<ModIntegrityReport> = vec![];
+    let mut untracked: Vec<String> = vec![];
+    for local_mod in local_db.valid() {
+        match verify_mod(local_mod)? {
+            Some(report) => {
+                if !report.is_intact() {
+                    reports.push(report);
+                }
+            }
+            None => untracked.push(local_mod.manifest.unique_name.clone()),
+        }
+    }
+    reports.sort_by(|a, b| a.unique_name.cmp(&b.unique_name));
+    untracked.sort();
+    Ok((reports, untracked))
+}
+
+/// Repair mods by reinstalling the ones that failed verification from the database.
+/// Note this installs the **latest** version of the mod from the database, not the version that was installed
+/// (the database only has the latest version). A warning is logged for each mod whose version will change.
+///
+/// ## Returns
+///
+/// The unique names of mods that couldn't be repaired because they aren't in the database.
+///
+/// ## Errors
+///
+/// If we can't reinstall any of the mods.
+///
+pub async fn repair_mods(
+    reports: &[ModIntegrityReport],
+    config: &Config,
+    remote_db: &RemoteDatabase,
+    local_db: &LocalDatabase,
+) -> Result<Vec<String>> {
+    let (to_install, not_found): (Vec<String>, Vec<String>) = reports
+        .iter()
+        .filter(|r| !r.is_intact())
+        .map(|r| r.unique_name.clone())
+        .partition(|unique_name| remote_db.get_mod(unique_name).is_some());
+    for unique_name in to_install.iter() {
+        let installed_version = local_db.get_mod(unique_name).map(|m| &m.manifest.version);
+        let remote_version = remote_db.get_mod(unique_name).map(|m| &m.version);
+        if let (Some(installed), Some(remote)) = (installed_version, remote_version) {
+            if installed != remote {
+                warn!("Repairing {unique_name} will update it from {installed} to {remote}");
+            }
+        }
+    }
+    if !to_install.is_empty() {
+        info!("Reinstalling {} mod(s)", to_install.len());
+        install_mods_parallel(to_install, config, remote_db, local_db).await?;
+    }
+    Ok(not_found)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs::{remove_file, write};
+
+    use crate::test_utils::TestContext;
+
+    use super::*;
+
+    #[test]
+    fn test_install_records_files() {
+        let mut ctx = TestContext::new();
+        let new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", false);
+        let installed = InstalledFiles::get(&PathBuf::from(&new_mod.mod_path))
+            .unwrap()
+            .unwrap();
+        assert!(installed.files.iter().any(|f| f.path == "manifest.json"));
+        assert!(installed.files.iter().all(|f| f.path != "config.json"));
+        let report = verify_mod(&new_mod).unwrap().unwrap();
+        assert!(report.is_intact());
+    }
+
+    #[test]
+    fn test_verify_mod_changes() {
+        let mut ctx = TestContext::new();
+        let new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", false);
+        let mod_path = PathBuf::from(&new_mod.mod_path);
+        remove_file(mod_path.join("TimeSaver.dll")).unwrap();
+        write(mod_path.join("manifest.json"), "{}").unwrap();
+        write(mod_path.join("extra.txt"), "Hello").unwrap();
+        write(mod_path.join("config.json"), "{}").unwrap();
+        let report = verify_mod(&new_mod).unwrap().unwrap();
+        assert_eq!(report.missing, vec!["TimeSaver.dll".to_string()]);
+        assert_eq!(report.modified, vec!["manifest.json".to_string()]);
+        assert_eq!(report.extra, vec!["extra.txt".to_string()]);
+    }
+
+    #[test]
+    fn test_verify_mods_untracked() {
+        let mut ctx = TestContext::new();
+        let new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        remove_file(InstalledFiles::path(&PathBuf::from(&new_mod.mod_path))).unwrap();
+        assert!(verify_mod(&new_mod).unwrap().is_none());
+        let (reports, untracked) = verify_mods(&ctx.local_db).unwrap();
+        assert!(reports.is_empty());
+        assert_eq!(untracked, vec!["Bwc9876.TimeSaver".to_string()]);
+    }
+}
@@ -1,6 +1,14 @@
-use std::path::Path;
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
 
 use crate::{
     analytics::{send_analytics_event, AnalyticsEventName},
@@ -8,9 +16,317 @@ use crate::{
     db::{LocalDatabase, RemoteDatabase},
     download::install_mods_parallel,
     file::deserialize_from_json,
-    toggle::toggle_mod,
+    toggle::toggle_mod_with_snapshot,
 };
 
+/// A mod in a list of mods to import
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportEntry {
+    /// The unique name of the mod
+    pub unique_name: String,
+    /// The version of the mod the list was made with, if the list has versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+// Lists can either be plain unique names (like the ones made by [export_mods]) or entries with versions
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawImportEntry {
+    Name(String),
+    Entry(ImportEntry),
+}
+
+/// Read a list of mods to import from a JSON file.
+/// The file can either be an array of unique names (like the one exported by [export_mods]),
+/// or an array of objects with a `uniqueName` and optionally a `version`.
+///
+/// ## Errors
+///
+/// If we can't read the file or it isn't in either format
+///
+pub fn read_import_list(file_path: &Path) -> Result<Vec<ImportEntry>> {
+    let raw: Vec<RawImportEntry> = deserialize_from_json(file_path)?;
+    Ok(raw
+        .into_iter()
+        .map(|e| match e {
+            RawImportEntry::Name(unique_name) => ImportEntry {
+                unique_name,
+                version: None,
+            },
+            RawImportEntry::Entry(entry) => entry,
+        })
+        .collect())
+}
+
+// Bumped if the format of share codes ever changes, so old codes can still be read
+const SHARE_CODE_VERSION: char = '1';
+
+// Decoded share codes bigger than this are rejected, real lists are nowhere near this
+const MAX_SHARE_CODE_SIZE: u64 = 1024 * 1024;
+
+/// Encode a list of mods into a share code, a short URL-safe string that can be pasted anywhere.
+/// Versions are included in the code if the entries have them.
+/// Share codes can also be opened as `owmods://import/<code>` links.
+///
+/// ## Errors
+///
+/// If we can't compress the list
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::io::{encode_share_code, decode_share_code, ImportEntry};
+///
+/// let list = vec![ImportEntry {
+///     unique_name: "Bwc9876.TimeSaver".to_string(),
+///     version: None,
+/// }];
+/// let code = encode_share_code(&list).unwrap();
+///
+/// assert_eq!(decode_share_code(&code).unwrap(), list);
+/// ```
+///
+pub fn encode_share_code(list: &[ImportEntry]) -> Result<String> {
+    let text = list
+        .iter()
+        .map(|e| match &e.version {
+            Some(version) => format!("{}\t{}", e.unique_name, version),
+            None => e.unique_name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(text.as_bytes())?;
+    let compressed = encoder.finish()?;
+    Ok(format!(
+        "{}{}",
+        SHARE_CODE_VERSION,
+        URL_SAFE_NO_PAD.encode(compressed)
+    ))
+}
+
+/// Decode a share code made by [encode_share_code] into a list of mods.
+/// Surrounding whitespace and a leading `owmods://import/` are ignored, so pasted links work too.
+/// Mods in the list aren't checked against any database, so unknown mods are kept as-is.
+///
+/// ## Errors
+///
+/// If the code is invalid, or was made by a newer version of the manager
+///
+pub fn decode_share_code(code: &str) -> Result<Vec<ImportEntry>> {
+    let code = code.trim();
+    let code = code.strip_prefix("owmods://import/").unwrap_or(code);
+    let mut chars = code.chars();
+    match chars.next() {
+        Some(SHARE_CODE_VERSION) => {}
+        Some(_) => return Err(anyhow!("Unsupported share code, try updating the manager")),
+        None => return Err(anyhow!("Share code is empty")),
+    }
+    let compressed = URL_SAFE_NO_PAD
+        .decode(chars.as_str())
+        .context("Invalid share code")?;
+    let mut text = String::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_SHARE_CODE_SIZE)
+        .read_to_string(&mut text)
+        .context("Invalid share code")?;
+    Ok(text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| match l.split_once('\t') {
+            Some((unique_name, version)) => ImportEntry {
+                unique_name: unique_name.to_string(),
+                version: Some(version.to_string()),
+            },
+            None => ImportEntry {
+                unique_name: l.to_string(),
+                version: None,
+            },
+        })
+        .collect())
+}
+
+/// What importing a list would do to a mod
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportAction {
+    /// The mod isn't installed and will be installed from the database
+    Install,
+    /// The mod is installed but disabled, it will be enabled
+    Enable,
+    /// The mod is enabled but not in the list, it will be disabled
+    Disable,
+    /// Nothing will change for this mod
+    Untouched,
+    /// The mod isn't installed or in the database, it'll be skipped
+    Unknown,
+}
+
+/// How importing a list would affect a single mod
+#[typeshare]
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreviewEntry {
+    /// The unique name of the mod
+    pub unique_name: String,
+    /// The name of the mod, this is the unique name if the mod isn't installed or in the database
+    pub name: String,
+    /// What will happen to the mod
+    pub action: ImportAction,
+    /// Whether the mod is in the list being imported
+    pub in_list: bool,
+    /// The version that's currently installed, if any
+    pub installed_version: Option<String>,
+    /// The version the list was made with, if the list has versions
+    pub list_version: Option<String>,
+    /// The version in the database, if the mod is in it
+    pub remote_version: Option<String>,
+}
+
+impl ImportPreviewEntry {
+    /// Whether the version the list was made with is different from the one that'll be installed / is installed
+    pub fn version_mismatch(&self) -> bool {
+        let target = self
+            .installed_version
+            .as_ref()
+            .or(self.remote_version.as_ref());
+        matches!((&self.list_version, target), (Some(l), Some(t)) if l != t)
+    }
+}
+
+/// A preview of what importing a list of mods would do, see [preview_import]
+#[typeshare]
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreview {
+    /// Every mod in the list and every installed mod, sorted by action then name
+    pub entries: Vec<ImportPreviewEntry>,
+    /// The unique names of mods in the list that aren't installed or in the database
+    pub unknown: Vec<String>,
+}
+
+impl ImportPreview {
+    /// Get the entries that will be affected by the given action
+    pub fn with_action(&self, action: ImportAction) -> impl Iterator<Item = &ImportPreviewEntry> {
+        self.entries.iter().filter(move |e| e.action == action)
+    }
+
+    /// Whether importing would change anything
+    pub fn has_changes(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|e| !matches!(e.action, ImportAction::Untouched | ImportAction::Unknown))
+    }
+}
+
+/// Compare a list of mods to import with the installed mods without changing anything.
+/// This matches what [import_mods] would do with the same list.
+///
+/// ## Returns
+///
+/// An [ImportPreview] containing an entry for every mod in the list and every installed mod.
+/// Note mods that would be installed may also install their dependencies, these aren't included.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::io::{preview_import, read_import_list, ImportAction};
+/// use owmods_core::db::{LocalDatabase, RemoteDatabase};
+/// use owmods_core::config::Config;
+/// use std::path::PathBuf;
+///
+/// # tokio_test::block_on(async {
+/// let config = Config::get(None).unwrap();
+/// let remote_db = RemoteDatabase::fetch(&config.database_url).await.unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// let list = read_import_list(&PathBuf::from("exported_list.json")).unwrap();
+/// let preview = preview_import(&list, &local_db, &remote_db, true);
+///
+/// for entry in preview.with_action(ImportAction::Install) {
+///     println!("Would install {}", entry.name);
+/// }
+/// # });
+/// ```
+///
+pub fn preview_import(
+    list: &[ImportEntry],
+    local_db: &LocalDatabase,
+    remote_db: &RemoteDatabase,
+    disable_missing: bool,
+) -> ImportPreview {
+    let mut preview = ImportPreview::default();
+    for entry in list.iter() {
+        if preview
+            .entries
+            .iter()
+            .any(|e| e.unique_name == entry.unique_name)
+        {
+            continue;
+        }
+        let local_mod = local_db.get_mod(&entry.unique_name);
+        let remote_mod = remote_db.get_mod(&entry.unique_name);
+        let action = match (local_mod, remote_mod) {
+            (Some(local_mod), _) if local_mod.enabled => ImportAction::Untouched,
+            (Some(_), _) => ImportAction::Enable,
+            (None, Some(_)) => ImportAction::Install,
+            (None, None) => ImportAction::Unknown,
+        };
+        if action == ImportAction::Unknown {
+            preview.unknown.push(entry.unique_name.clone());
+        }
+        preview.entries.push(ImportPreviewEntry {
+            unique_name: entry.unique_name.clone(),
+            name: local_mod
+                .map(|m| m.manifest.name.clone())
+                .or_else(|| remote_mod.map(|m| m.name.clone()))
+                .unwrap_or_else(|| entry.unique_name.clone()),
+            action,
+            in_list: true,
+            installed_version: local_mod.map(|m| m.manifest.version.clone()),
+            list_version: entry.version.clone(),
+            remote_version: remote_mod.map(|m| m.version.clone()),
+        });
+    }
+    for local_mod in local_db.valid() {
+        let unique_name = &local_mod.manifest.unique_name;
+        if list.iter().any(|e| &e.unique_name == unique_name) {
+            continue;
+        }
+        let action = if disable_missing && local_mod.enabled {
+            ImportAction::Disable
+        } else {
+            ImportAction::Untouched
+        };
+        preview.entries.push(ImportPreviewEntry {
+            unique_name: unique_name.clone(),
+            name: local_mod.manifest.name.clone(),
+            action,
+            in_list: false,
+            installed_version: Some(local_mod.manifest.version.clone()),
+            list_version: None,
+            remote_version: remote_db.get_mod(unique_name).map(|m| m.version.clone()),
+        });
+    }
+    preview
+        .entries
+        .sort_by(|a, b| (a.action as u8, &a.name).cmp(&(b.action as u8, &b.name)));
+    preview
+}
+
+/// The format to export a list of mods in, see [export_mods_as]
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A pretty-printed JSON array that can be saved to a file
+    Json,
+    /// A share code, see [encode_share_code]
+    ShareCode,
+}
+
 /// Export all installed **and enabled** mods in the database
 ///
 /// ## Returns
@@ -36,12 +352,58 @@ use crate::{
 /// ```
 ///
 pub fn export_mods(db: &LocalDatabase) -> Result<String> {
-    let enabled_mods: Vec<&String> = db.active().map(|m| &m.manifest.unique_name).collect();
-    let result = serde_json::to_string_pretty(&enabled_mods)?;
-    Ok(result)
+    export_mods_as(db, ExportFormat::Json, false)
 }
 
-/// Import mods from a JSON file that contains an array or unique name (like the one exported by [export_mods]).
+/// Export all installed **and enabled** mods in the database in the given format
+///
+/// ## Returns
+///
+/// Depending on `format`, either a JSON array of the mods or a share code (see [encode_share_code]).
+/// If `include_versions` is set each mod's installed version is included, otherwise the JSON array is only unique names.
+///
+/// ## Errors
+///
+/// If we can't serialize to JSON or create the share code
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::io::{export_mods_as, ExportFormat};
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+/// let code = export_mods_as(&local_db, ExportFormat::ShareCode, true).unwrap();
+///
+/// println!("Share this link: owmods://import/{}", code);
+/// ```
+///
+pub fn export_mods_as(
+    db: &LocalDatabase,
+    format: ExportFormat,
+    include_versions: bool,
+) -> Result<String> {
+    let entries: Vec<ImportEntry> = db
+        .active()
+        .map(|m| ImportEntry {
+            unique_name: m.manifest.unique_name.clone(),
+            version: include_versions.then(|| m.manifest.version.clone()),
+        })
+        .collect();
+    match format {
+        ExportFormat::Json if include_versions => Ok(serde_json::to_string_pretty(&entries)?),
+        ExportFormat::Json => {
+            let unique_names: Vec<&String> = entries.iter().map(|e| &e.unique_name).collect();
+            Ok(serde_json::to_string_pretty(&unique_names)?)
+        }
+        ExportFormat::ShareCode => encode_share_code(&entries),
+    }
+}
+
+/// Import mods from a JSON file that contains an array or unique name (like the one exported by [export_mods]), see [read_import_list].
+/// Use [preview_import] to see what this would do first.
 /// Mods that aren't in the remote database will be ignored and will only log a warning.
 /// Optionally, this can also disable all current mods not found in this list.
 ///
@@ -116,21 +478,70 @@ pub async fn import_mods(
     file_path: &Path,
     disable_missing: bool,
 ) -> Result<()> {
-    let unique_names: Vec<String> = deserialize_from_json(file_path)?;
+    let list = read_import_list(file_path)?;
+    import_mod_list(config, local_db, remote_db, &list, disable_missing).await
+}
+
+/// Import an already read list of mods, like one from [read_import_list] or [decode_share_code].
+/// This behaves exactly like [import_mods], versions in the list are ignored and the latest version is installed.
+///
+/// ## Errors
+///
+/// If we can't install any mods (that are in the remote database) for whatever reason.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::io::{import_mod_list, decode_share_code};
+/// use owmods_core::db::{LocalDatabase, RemoteDatabase};
+/// use owmods_core::config::Config;
+///
+/// # tokio_test::block_on(async {
+/// let config = Config::get(None).unwrap();
+/// let remote_db = RemoteDatabase::fetch(&config.database_url).await.unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// let list = decode_share_code("1cypPtrQwN9MLycxNDU4sSy0CAA").unwrap();
+/// import_mod_list(&config, &local_db, &remote_db, &list, false).await.unwrap();
+/// # });
+/// ```
+///
+pub async fn import_mod_list(
+    config: &Config,
+    local_db: &LocalDatabase,
+    remote_db: &RemoteDatabase,
+    list: &[ImportEntry],
+    disable_missing: bool,
+) -> Result<()> {
     let mut needed_install: Vec<String> = vec![];
 
     if disable_missing {
         for local_mod in local_db.valid() {
             if local_mod.enabled {
-                toggle_mod(&local_mod.manifest.unique_name, local_db, false, false)?;
+                toggle_mod_with_snapshot(
+                    &local_mod.manifest.unique_name,
+                    config,
+                    local_db,
+                    false,
+                    false,
+                )?;
             }
         }
     }
-    for name in unique_names.iter() {
+    for entry in list.iter() {
+        let name = &entry.unique_name;
         let local_mod = local_db.get_mod(name);
         if let Some(local_mod) = local_mod {
-            toggle_mod(&local_mod.manifest.unique_name, local_db, true, false)?;
-        } else {
+            toggle_mod_with_snapshot(
+                &local_mod.manifest.unique_name,
+                config,
+                local_db,
+                true,
+                false,
+            )?;
+        } else if remote_db.get_mod(name).is_none() {
+            warn!("{} isn't in the database, skipping", name);
+        } else if !needed_install.contains(name) {
             needed_install.push(name.to_string());
         }
     }
@@ -149,7 +560,11 @@ mod tests {
 
     use std::{fs, path::PathBuf};
 
-    use crate::test_utils::{get_test_file, TestContext};
+    use crate::{
+        mods::{local::LocalMod, remote::RemoteMod},
+        test_utils::{get_test_file, TestContext},
+        toggle::toggle_mod,
+    };
 
     use super::*;
 
@@ -159,6 +574,84 @@ mod tests {
         path
     }
 
+    #[test]
+    fn test_read_import_list() {
+        let ctx = TestContext::new();
+        let path = make_list_json(
+            &ctx,
+            r#"["Example.TestMod0", {"uniqueName": "Example.TestMod1", "version": "1.0.0"}]"#,
+        );
+        let list = read_import_list(&path).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].version, None);
+        assert_eq!(list[1].unique_name, "Example.TestMod1");
+        assert_eq!(list[1].version.as_deref(), Some("1.0.0"));
+    }
+
+    #[test]
+    fn test_preview_import() {
+        let mut ctx = TestContext::new();
+        let mut disabled = LocalMod::get_test(0);
+        disabled.enabled = false;
+        ctx.insert_test_mod(&disabled);
+        ctx.insert_test_mod(&LocalMod::get_test(1));
+        ctx.insert_test_mod(&LocalMod::get_test(2));
+        let remote_mod = RemoteMod::get_test(3);
+        ctx.remote_db
+            .mods
+            .insert(remote_mod.unique_name.clone(), remote_mod);
+        let list: Vec<ImportEntry> = [
+            "Example.TestMod0",
+            "Example.TestMod1",
+            "Example.TestMod3",
+            "Example.Unknown",
+        ]
+        .iter()
+        .map(|n| ImportEntry {
+            unique_name: n.to_string(),
+            version: None,
+        })
+        .collect();
+        let preview = preview_import(&list, &ctx.local_db, &ctx.remote_db, true);
+        assert_eq!(
+            action_of(&preview, "Example.TestMod0"),
+            ImportAction::Enable
+        );
+        assert_eq!(
+            action_of(&preview, "Example.TestMod1"),
+            ImportAction::Untouched
+        );
+        assert_eq!(
+            action_of(&preview, "Example.TestMod2"),
+            ImportAction::Disable
+        );
+        assert_eq!(
+            action_of(&preview, "Example.TestMod3"),
+            ImportAction::Install
+        );
+        assert_eq!(
+            action_of(&preview, "Example.Unknown"),
+            ImportAction::Unknown
+        );
+        assert_eq!(preview.unknown, vec!["Example.Unknown".to_string()]);
+        assert_eq!(preview.entries[0].action, ImportAction::Install);
+        assert!(preview.has_changes());
+        let preview = preview_import(&list, &ctx.local_db, &ctx.remote_db, false);
+        assert_eq!(
+            action_of(&preview, "Example.TestMod2"),
+            ImportAction::Untouched
+        );
+    }
+
+    fn action_of(preview: &ImportPreview, name: &str) -> ImportAction {
+        preview
+            .entries
+            .iter()
+            .find(|e| e.unique_name == name)
+            .unwrap()
+            .action
+    }
+
     #[test]
     fn test_export_mods() {
         let test_dir = get_test_file("");
@@ -168,6 +661,42 @@ mod tests {
         assert!(!result.contains("Bwc9876.SaveEditor"));
     }
 
+    #[test]
+    fn test_export_mods_versions() {
+        let test_dir = get_test_file("");
+        let db = LocalDatabase::fetch(test_dir.to_str().unwrap()).unwrap();
+        let version = &db.get_mod("Bwc9876.TimeSaver").unwrap().manifest.version;
+        let result = export_mods_as(&db, ExportFormat::Json, true).unwrap();
+        let ctx = TestContext::new();
+        let path = make_list_json(&ctx, &result);
+        let list = read_import_list(&path).unwrap();
+        assert_eq!(list[0].version.as_ref(), Some(version));
+        let code = export_mods_as(&db, ExportFormat::ShareCode, true).unwrap();
+        assert_eq!(decode_share_code(&code).unwrap(), list);
+    }
+
+    #[test]
+    fn test_share_code() {
+        let list: Vec<ImportEntry> = (0..20)
+            .map(|i| ImportEntry {
+                unique_name: format!("Example.TestMod{i}"),
+                version: (i % 2 == 0).then(|| "1.2.3".to_string()),
+            })
+            .collect();
+        let code = encode_share_code(&list).unwrap();
+        assert!(code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode_share_code(&code).unwrap(), list);
+        let link = format!("  owmods://import/{code}\n");
+        assert_eq!(decode_share_code(&link).unwrap(), list);
+        let decoded = decode_share_code("1cypPtrQwN9MLycxNDU4sSy0CAA").unwrap();
+        assert_eq!(decoded[0].unique_name, "Bwc9876.TimeSaver");
+        assert!(decode_share_code("").is_err());
+        assert!(decode_share_code("1!!!").is_err());
+        assert!(decode_share_code(&code.replacen('1', "2", 1)).is_err());
+    }
+
     #[test]
     fn test_import_mods() {
         tokio_test::block_on(async {
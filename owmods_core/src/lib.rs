@@ -10,6 +10,12 @@ pub mod alerts;
 /// Send analytics events.
 pub mod analytics;
 
+/// Track mods installed as dependencies and remove them once they're no longer needed.
+pub mod autoremove;
+
+/// Find and clean up leftovers in the `Mods` folder.
+pub mod clean;
+
 /// Work with the configuration of the app.
 pub mod config;
 
@@ -28,12 +34,45 @@ pub mod file;
 /// Run the game and setup prerequisites on Linux.
 pub mod game;
 
+/// Save game logs to disk and replay them.
+pub mod game_logs;
+
+/// Build and export the dependency graph of installed mods.
+pub mod graph;
+
+/// Record and verify the files of installed mods.
+pub mod integrity;
+
 /// Import and export mods from JSON arrays.
 pub mod io;
 
+/// Find exceptions in game logs and figure out which mods caused them.
+pub mod log_analysis;
+
+/// Export game logs as text, HTML, or JSON to share them.
+pub mod log_export;
+
+/// Buffer game logs so they aren't lost when they can't be handled fast enough.
+pub mod log_queue;
+
+/// Compress and delete old manager and game logs.
+pub mod log_retention;
+
+/// Send game logs to multiple places at once.
+pub mod log_sinks;
+
+/// Migrate mods and their settings from another OWML install or `Mods` folder.
+pub mod migrate;
+
+/// Export and import modpacks, archives of mods and their settings that can be shared offline.
+pub mod modpack;
+
 /// Work with local and remote mods.
 pub mod mods;
 
+/// Read and change the settings of mods.
+pub mod mod_settings;
+
 /// Work with the OWML config.
 pub mod owml;
 
@@ -49,9 +88,15 @@ pub mod remove;
 /// Listen to logs from the game.
 pub mod socket;
 
+/// Take snapshots of mod settings and restore them.
+pub mod snapshots;
+
 /// Enable/Disable mods.
 pub mod toggle;
 
+/// Keep uninstalled mods in a trash folder so they can be restored.
+pub mod trash;
+
 /// Check for and update mods.
 pub mod updates;
 
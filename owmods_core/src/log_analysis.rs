@@ -0,0 +1,467 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    db::LocalDatabase,
+    log_sinks::LogSink,
+    socket::{SocketMessage, SocketMessageType},
+};
+
+lazy_static! {
+    static ref EXCEPTION_RE: Regex =
+        Regex::new(r"(?m)\b((?:[A-Za-z_]\w*\.)*[A-Za-z_]\w*Exception)\b(?::[ \t]*(.*))?").unwrap();
+    static ref FRAME_RE: Regex = Regex::new(
+        r"(?m)^\s*at\s+(?:\(wrapper [^)]*\)\s+)?(.+?)(?:\s+\[0x[0-9a-fA-F]+\])?(?:\s+in\s+.*)?\s*$"
+    )
+    .unwrap();
+}
+
+/// Namespaces that belong to the runtime, Unity, or the modding framework, frames in these are never blamed for an exception
+const FRAMEWORK_NAMESPACES: [&str; 9] = [
+    "System",
+    "UnityEngine",
+    "Unity",
+    "Mono",
+    "MonoMod",
+    "HarmonyLib",
+    "OWML",
+    "Newtonsoft",
+    "Microsoft",
+];
+
+/// How many sources to show in [LogAnalysis::top_sources]
+const MAX_TOP_SOURCES: usize = 5;
+
+/// How many mods to suggest in [LogAnalysis::suspects]
+const MAX_SUSPECTS: usize = 3;
+
+/// An exception found in the logs, repeats of the same exception (same type, source, and first stack frame) are counted together
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggedException {
+    /// The full name of the exception type, ex: `System.NullReferenceException`
+    pub exception_type: String,
+    /// The exception's message, from the first time it was logged
+    pub message: String,
+    /// The stack frames, from the first time it was logged
+    pub frames: Vec<String>,
+    /// What we think caused the exception, a mod's unique name if we could figure it out, otherwise the sender name
+    pub source: String,
+    /// How many times this exception was logged
+    pub count: u32,
+    /// Whether this exception was ever logged as fatal
+    pub fatal: bool,
+}
+
+/// Something that logged errors
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorSource {
+    /// A mod's unique name if we could figure it out, otherwise the sender name
+    pub name: String,
+    /// How many error and fatal messages this source logged
+    pub errors: u32,
+    /// How many of those errors were exceptions
+    pub exceptions: u32,
+}
+
+/// A summary of a game session's logs, see [LogAnalyzer]
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogAnalysis {
+    /// The total amount of messages
+    pub total_messages: u32,
+    /// The amount of warnings
+    pub warnings: u32,
+    /// The amount of errors (including fatal errors)
+    pub errors: u32,
+    /// The first fatal message, this is usually what crashed the game
+    pub first_fatal: Option<SocketMessage>,
+    /// Exceptions found, most logged first
+    pub exceptions: Vec<LoggedException>,
+    /// What logged the most errors, most errors first
+    pub top_sources: Vec<ErrorSource>,
+    /// Unique names of mods that are most likely causing problems, most likely first
+    pub suspects: Vec<String>,
+}
+
+impl LogAnalysis {
+    /// Whether there's nothing worth reporting (no errors, no exceptions)
+    pub fn is_clean(&self) -> bool {
+        self.errors == 0 && self.exceptions.is_empty()
+    }
+}
+
+fn frame_namespace(frame: &str) -> Option<&str> {
+    let method = frame.split(" (").next().unwrap_or(frame);
+    let mut parts = method.split('.');
+    let root = parts.next()?;
+    // Namespace.Class.Method at least, frames without a namespace are from the game itself
+    if parts.count() < 2 {
+        None
+    } else {
+        Some(root)
+    }
+}
+
+/// Analyzes messages from a game session as they come in, call [LogAnalyzer::finish] to get a [LogAnalysis].
+///
+/// Exceptions are detected in messages that contain a .NET exception type (ex: `System.NullReferenceException: ...`),
+/// followed by stack frames (`  at Namespace.Class.Method () ...`).
+/// They're blamed on the first stack frame that belongs to an installed mod (matched by DLL name),
+/// falling back to the mod or sender that logged them.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::log_analysis::LogAnalyzer;
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::game_logs::read_game_log;
+/// use std::path::Path;
+///
+/// let local_db = LocalDatabase::fetch("/path/to/owml").unwrap();
+/// let mut analyzer = LogAnalyzer::new(Some(&local_db));
+/// for logged in read_game_log(Path::new("session.jsonl")).unwrap() {
+///     analyzer.add(&logged.message);
+/// }
+/// let analysis = analyzer.finish();
+/// println!("Suspects: {}", analysis.suspects.join(", "));
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct LogAnalyzer {
+    // Lowercase DLL or mod names -> unique names
+    known_mods: HashMap<String, String>,
+    analysis: LogAnalysis,
+    // (source, exception type, first frame) -> index in analysis.exceptions
+    exception_keys: HashMap<(String, String, String), usize>,
+    sources: HashMap<String, ErrorSource>,
+    first_fatal_source: Option<String>,
+}
+
+impl LogAnalyzer {
+    /// Make a new analyzer, if a local database is passed it's used to figure out which mod a stack frame or sender belongs to
+    pub fn new(local_db: Option<&LocalDatabase>) -> Self {
+        let mut known_mods = HashMap::new();
+        if let Some(local_db) = local_db {
+            for local_mod in local_db.valid() {
+                let manifest = &local_mod.manifest;
+                let unique_name = manifest.unique_name.clone();
+                if let Some(dll) = manifest.filename.as_ref() {
+                    let stem = dll.trim_end_matches(".dll").to_ascii_lowercase();
+                    known_mods.insert(stem, unique_name.clone());
+                }
+                known_mods.insert(manifest.name.to_ascii_lowercase(), unique_name.clone());
+                known_mods.insert(unique_name.to_ascii_lowercase(), unique_name);
+            }
+        }
+        Self {
+            known_mods,
+            ..Default::default()
+        }
+    }
+
+    fn resolve(&self, name: &str) -> Option<&String> {
+        self.known_mods.get(&name.to_ascii_lowercase())
+    }
+
+    fn blame(&self, message: &SocketMessage, frames: &[String]) -> String {
+        for namespace in frames.iter().filter_map(|f| frame_namespace(f)) {
+            if let Some(unique_name) = self.resolve(namespace) {
+                return unique_name.clone();
+            }
+            if self.known_mods.is_empty() && !FRAMEWORK_NAMESPACES.contains(&namespace) {
+                return namespace.to_string();
+            }
+        }
+        let sender = message.sender_name.as_deref().unwrap_or("Unknown");
+        self.resolve(sender)
+            .cloned()
+            .unwrap_or_else(|| sender.to_string())
+    }
+
+    fn find_exception(message: &SocketMessage) -> Option<(String, String, Vec<String>)> {
+        let captures = EXCEPTION_RE.captures(&message.message)?;
+        let exception_type = captures.get(1)?.as_str().to_string();
+        let exception_message = captures
+            .get(2)
+            .map(|m| m.as_str().trim().to_string())
+            .unwrap_or_default();
+        let frames = FRAME_RE
+            .captures_iter(&message.message)
+            .filter_map(|c| c.get(1).map(|m| m.as_str().trim().to_string()))
+            .collect::<Vec<_>>();
+        // A message that just mentions an exception isn't one, unless it's logged as an error
+        let is_error = matches!(
+            message.message_type,
+            SocketMessageType::Error | SocketMessageType::Fatal
+        );
+        if frames.is_empty() && !is_error {
+            None
+        } else {
+            Some((exception_type, exception_message, frames))
+        }
+    }
+
+    /// Add a message to the analysis
+    pub fn add(&mut self, message: &SocketMessage) {
+        self.add_repeated(message, 1);
+    }
+
+    /// Add a message that was logged multiple times in a row to the analysis
+    pub fn add_repeated(&mut self, message: &SocketMessage, times: u32) {
+        let analysis = &mut self.analysis;
+        analysis.total_messages = analysis.total_messages.saturating_add(times);
+        let fatal = message.message_type == SocketMessageType::Fatal;
+        match message.message_type {
+            SocketMessageType::Warning => {
+                analysis.warnings = analysis.warnings.saturating_add(times);
+            }
+            SocketMessageType::Error | SocketMessageType::Fatal => {
+                analysis.errors = analysis.errors.saturating_add(times);
+            }
+            _ => {}
+        }
+
+        let exception = Self::find_exception(message);
+        let frames = exception.as_ref().map(|e| e.2.as_slice()).unwrap_or(&[]);
+        let is_error = matches!(
+            message.message_type,
+            SocketMessageType::Error | SocketMessageType::Fatal
+        );
+        if !is_error && exception.is_none() {
+            return;
+        }
+        let source = self.blame(message, frames);
+
+        if fatal && self.analysis.first_fatal.is_none() {
+            self.analysis.first_fatal = Some(message.clone());
+            self.first_fatal_source = Some(source.clone());
+        }
+
+        let entry = self
+            .sources
+            .entry(source.clone())
+            .or_insert_with(|| ErrorSource {
+                name: source.clone(),
+                errors: 0,
+                exceptions: 0,
+            });
+        if is_error {
+            entry.errors = entry.errors.saturating_add(times);
+        }
+
+        if let Some((exception_type, exception_message, frames)) = exception {
+            entry.exceptions = entry.exceptions.saturating_add(times);
+            let key = (
+                source.clone(),
+                exception_type.clone(),
+                frames.first().cloned().unwrap_or_default(),
+            );
+            if let Some(&i) = self.exception_keys.get(&key) {
+                let existing = &mut self.analysis.exceptions[i];
+                existing.count = existing.count.saturating_add(times);
+                existing.fatal |= fatal;
+            } else {
+                self.exception_keys
+                    .insert(key, self.analysis.exceptions.len());
+                self.analysis.exceptions.push(LoggedException {
+                    exception_type,
+                    message: exception_message,
+                    frames,
+                    source,
+                    count: times,
+                    fatal,
+                });
+            }
+        }
+    }
+
+    /// Finish the analysis, sorting exceptions and sources and picking suspects
+    pub fn finish(self) -> LogAnalysis {
+        let mut analysis = self.analysis;
+        // Stable sort keeps exceptions that were logged the same amount in the order they first appeared
+        analysis.exceptions.sort_by_key(|e| Reverse(e.count));
+
+        let mut sources = self.sources.into_values().collect::<Vec<_>>();
+        sources.sort_by(|a, b| {
+            (b.errors, b.exceptions)
+                .cmp(&(a.errors, a.exceptions))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let is_suspect = |name: &String| {
+            if self.known_mods.is_empty() {
+                name != "Unknown" && name != "Manager"
+            } else {
+                self.known_mods.values().any(|n| n == name)
+            }
+        };
+        let mut suspects: Vec<String> = vec![];
+        let by_exceptions = {
+            let mut s = sources.clone();
+            s.sort_by_key(|s| Reverse(s.exceptions));
+            s
+        };
+        for name in self
+            .first_fatal_source
+            .iter()
+            .chain(
+                by_exceptions
+                    .iter()
+                    .filter(|s| s.exceptions > 0)
+                    .map(|s| &s.name),
+            )
+            .chain(sources.iter().map(|s| &s.name))
+        {
+            if suspects.len() >= MAX_SUSPECTS {
+                break;
+            }
+            if is_suspect(name) && !suspects.contains(name) {
+                suspects.push(name.clone());
+            }
+        }
+
+        sources.truncate(MAX_TOP_SOURCES);
+        analysis.top_sources = sources;
+        analysis.suspects = suspects;
+        analysis
+    }
+}
+
+/// Analyze a list of messages, see [LogAnalyzer]
+pub fn analyze_logs<'a>(
+    messages: impl IntoIterator<Item = &'a SocketMessage>,
+    local_db: Option<&LocalDatabase>,
+) -> LogAnalysis {
+    let mut analyzer = LogAnalyzer::new(local_db);
+    for message in messages {
+        analyzer.add(message);
+    }
+    analyzer.finish()
+}
+
+/// Lets a [LogAnalyzer] be used as a sink while keeping a handle to it, to call [LogAnalyzer::finish] once the session is over
+impl LogSink for Arc<Mutex<LogAnalyzer>> {
+    fn write(&mut self, message: &SocketMessage) -> Result<()> {
+        self.lock().unwrap().add(message);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::test_utils::get_test_file;
+
+    use super::*;
+
+    fn msg(sender: &str, message: &str, message_type: SocketMessageType) -> SocketMessage {
+        let mut msg = SocketMessage::make_internal(message, message_type);
+        msg.sender_name = Some(sender.to_string());
+        msg.sender_type = Some("ModHelper".to_string());
+        msg
+    }
+
+    const NRE: &str =
+        "System.NullReferenceException: Object reference not set to an instance of an object
+  at TimeSaver.TimeSaver.Update () [0x00012] in <abc123>:0
+  at UnityEngine.Behaviour.Update () [0x00000] in <def456>:0";
+
+    const HARMONY_EXCEPTION: &str = "Error in patch: System.InvalidOperationException: Nope
+  at (wrapper dynamic-method) PlayerCharacterController.DMD<PlayerCharacterController::Update>(PlayerCharacterController)
+  at SaveEditor.Patches.PlayerPatch.Prefix () [0x00001] in <aaa>:0";
+
+    #[test]
+    fn test_frame_namespace() {
+        assert_eq!(
+            frame_namespace("TimeSaver.TimeSaver.Update ()"),
+            Some("TimeSaver")
+        );
+        assert_eq!(frame_namespace("PlayerCharacterController.Update ()"), None);
+        assert_eq!(
+            frame_namespace("PlayerCharacterController.DMD<PlayerCharacterController::Update>(PlayerCharacterController)"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_analyze_exceptions_with_db() {
+        let local_db = LocalDatabase::fetch(get_test_file("").to_str().unwrap()).unwrap();
+        let messages = vec![
+            msg("OWML", "Starting up", SocketMessageType::Info),
+            msg("Some Mod", NRE, SocketMessageType::Error),
+            msg("Some Mod", NRE, SocketMessageType::Error),
+            msg("Other", "Careful", SocketMessageType::Warning),
+            msg("OWML", HARMONY_EXCEPTION, SocketMessageType::Fatal),
+            msg("Some Mod", NRE, SocketMessageType::Error),
+            msg("Other", "Plain error", SocketMessageType::Error),
+        ];
+        let analysis = analyze_logs(&messages, Some(&local_db));
+        assert_eq!(analysis.total_messages, 7);
+        assert_eq!(analysis.warnings, 1);
+        assert_eq!(analysis.errors, 5);
+        assert_eq!(analysis.exceptions.len(), 2);
+
+        let nre = &analysis.exceptions[0];
+        assert_eq!(nre.exception_type, "System.NullReferenceException");
+        assert_eq!(
+            nre.message,
+            "Object reference not set to an instance of an object"
+        );
+        assert_eq!(nre.source, "Bwc9876.TimeSaver");
+        assert_eq!(nre.count, 3);
+        assert_eq!(nre.frames.len(), 2);
+        assert_eq!(nre.frames[0], "TimeSaver.TimeSaver.Update ()");
+        assert!(!nre.fatal);
+
+        let harmony = &analysis.exceptions[1];
+        assert_eq!(harmony.exception_type, "System.InvalidOperationException");
+        assert_eq!(harmony.source, "Bwc9876.SaveEditor");
+        assert!(harmony.fatal);
+
+        assert_eq!(
+            analysis.first_fatal.unwrap().message,
+            HARMONY_EXCEPTION.to_string()
+        );
+        assert_eq!(analysis.top_sources[0].name, "Bwc9876.TimeSaver");
+        assert_eq!(analysis.top_sources[0].errors, 3);
+        // The mod that caused the fatal error comes first, unknown senders aren't suspects
+        assert_eq!(
+            analysis.suspects,
+            vec!["Bwc9876.SaveEditor", "Bwc9876.TimeSaver"]
+        );
+    }
+
+    #[test]
+    fn test_analyze_without_db() {
+        let messages = vec![
+            msg("TimeSaver", NRE, SocketMessageType::Error),
+            msg(
+                "Mod",
+                "Mentions System.Exception in passing",
+                SocketMessageType::Info,
+            ),
+            msg("Mod", "Something broke", SocketMessageType::Error),
+        ];
+        let analysis = analyze_logs(&messages, None);
+        assert_eq!(analysis.exceptions.len(), 1);
+        assert_eq!(analysis.exceptions[0].source, "TimeSaver");
+        assert!(analysis.first_fatal.is_none());
+        assert_eq!(analysis.suspects, vec!["TimeSaver", "Mod"]);
+        assert!(!analysis.is_clean());
+        assert!(analyze_logs(&messages[1..2], None).is_clean());
+    }
+}
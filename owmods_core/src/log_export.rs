@@ -0,0 +1,260 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{file::create_all_parents, log_sinks::format_message, socket::SocketMessage};
+
+/// The format to export logs in, see [export_logs]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogExportFormat {
+    /// Plain text, the same way the CLI prints logs
+    Text,
+    /// A single HTML page that doesn't need anything else to view, messages are coloured by their type
+    Html,
+    /// A JSON array of messages
+    Json,
+}
+
+impl LogExportFormat {
+    /// Pick a format based on a file's extension, `.html` and `.htm` are HTML, `.json` is JSON, and anything else is text
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use owmods_core::log_export::LogExportFormat;
+    /// use std::path::Path;
+    ///
+    /// assert_eq!(LogExportFormat::from_path(Path::new("logs.html")), LogExportFormat::Html);
+    /// assert_eq!(LogExportFormat::from_path(Path::new("logs.JSON")), LogExportFormat::Json);
+    /// assert_eq!(LogExportFormat::from_path(Path::new("logs.txt")), LogExportFormat::Text);
+    /// ```
+    ///
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "html" | "htm" => Self::Html,
+            "json" => Self::Json,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// A message to export, along with when it was received and how many times in a row it was sent
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedMessage {
+    /// When the message was received, as it should be shown.
+    /// This isn't called `timestamp` as that's already a field of [SocketMessage], which is flattened into this
+    pub display_time: String,
+    /// How many times in a row the message was sent
+    pub amount: u32,
+    /// The message itself
+    #[serde(flatten)]
+    pub message: SocketMessage,
+}
+
+fn export_text(messages: &[ExportedMessage], title: &str) -> String {
+    let mut out = format!("{title}\n{} message(s)\n\n", messages.len());
+    for msg in messages {
+        let prefix = if msg.amount > 1 {
+            format!("[{}][x{}] ", msg.display_time, msg.amount)
+        } else {
+            format!("[{}] ", msg.display_time)
+        };
+        let spacing = " ".repeat(prefix.len());
+        for (i, line) in format_message(&msg.message).lines().enumerate() {
+            out.push_str(if i == 0 { &prefix } else { &spacing });
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn escape_html(str: &str) -> String {
+    str.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const HTML_STYLE: &str = "
+body { background: #121212; color: #eeeeee; font-family: sans-serif; margin: 1rem; }
+table { border-collapse: collapse; width: 100%; }
+th, td { padding: 0.25rem 0.5rem; border-bottom: 1px solid #333333; text-align: left; vertical-align: top; }
+td { font-family: monospace; }
+td.message { white-space: pre-wrap; word-break: break-word; width: 100%; }
+td.time, td.sender { white-space: nowrap; }
+tr.info td.message { color: #4fc3f7; }
+tr.success td.message { color: #81c784; }
+tr.warning td.message { color: #ffb74d; }
+tr.error td.message, tr.fatal td.message { color: #e57373; }
+tr.fatal td.message { font-weight: bold; }
+tr.debug td.message { color: #9e9e9e; }
+";
+
+fn export_html(messages: &[ExportedMessage], title: &str) -> String {
+    let title = escape_html(title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>{} message(s)</p>\n<table>\n<thead><tr><th>Time</th><th>Type</th><th>Sender</th><th>Amount</th><th>Message</th></tr></thead>\n<tbody>\n",
+        messages.len()
+    );
+    let unknown = "Unknown";
+    for msg in messages {
+        let message_type = format!("{:?}", msg.message.message_type);
+        let client = msg
+            .message
+            .client_id
+            .map(|id| format!("#{id} "))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "<tr class=\"{}\"><td class=\"time\">{}</td><td>{}</td><td class=\"sender\">{}{}::{}</td><td>{}</td><td class=\"message\">{}</td></tr>\n",
+            message_type.to_lowercase(),
+            escape_html(&msg.display_time),
+            message_type,
+            client,
+            escape_html(msg.message.sender_name.as_deref().unwrap_or(unknown)),
+            escape_html(msg.message.sender_type.as_deref().unwrap_or(unknown)),
+            msg.amount,
+            escape_html(&msg.message.message)
+        ));
+    }
+    out.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+    out
+}
+
+/// Export messages as text, HTML, or JSON so they can be shared (in a bug report for example).
+/// `title` is shown at the top of text and HTML exports.
+///
+/// ## Returns
+///
+/// The exported logs
+///
+/// ## Errors
+///
+/// If we can't serialize the messages to JSON
+///
+/// ## Examples
+///
+/// ```
+/// use owmods_core::log_export::{export_logs, ExportedMessage, LogExportFormat};
+/// use owmods_core::socket::{SocketMessage, SocketMessageType};
+///
+/// let messages = vec![ExportedMessage {
+///     display_time: "12:00:00 PM".to_string(),
+///     amount: 1,
+///     message: SocketMessage::make_internal("Hello!", SocketMessageType::Info),
+/// }];
+/// let text = export_logs(&messages, LogExportFormat::Text, "Game Logs").unwrap();
+/// assert!(text.ends_with("[12:00:00 PM] [Manager::LogServer][Info] Hello!\n"));
+/// ```
+///
+pub fn export_logs(
+    messages: &[ExportedMessage],
+    format: LogExportFormat,
+    title: &str,
+) -> Result<String> {
+    Ok(match format {
+        LogExportFormat::Text => export_text(messages, title),
+        LogExportFormat::Html => export_html(messages, title),
+        LogExportFormat::Json => serde_json::to_string_pretty(messages)?,
+    })
+}
+
+/// Export messages to a file, see [export_logs]
+///
+/// ## Errors
+///
+/// If we can't create or write to the file
+///
+pub fn write_log_export(
+    path: &Path,
+    messages: &[ExportedMessage],
+    format: LogExportFormat,
+    title: &str,
+) -> Result<()> {
+    create_all_parents(path)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(export_logs(messages, format, title)?.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{socket::SocketMessageType, test_utils::make_test_dir};
+
+    use super::*;
+
+    fn make_messages() -> Vec<ExportedMessage> {
+        vec![
+            ExportedMessage {
+                display_time: "1:00:00 PM".to_string(),
+                amount: 1,
+                message: SocketMessage::make_internal("First\nSecond", SocketMessageType::Info),
+            },
+            ExportedMessage {
+                display_time: "1:00:01 PM".to_string(),
+                amount: 3,
+                message: SocketMessage::make_internal("<b>Oh no</b>", SocketMessageType::Error)
+                    .with_client_id(2),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_export_text() {
+        let text = export_logs(&make_messages(), LogExportFormat::Text, "Logs").unwrap();
+        // Continuation lines line up with the first line's message
+        let spacing = " ".repeat("[1:00:00 PM] [Manager::LogServer][Info] ".len());
+        let expected = format!(
+            "Logs\n2 message(s)\n\n\
+            [1:00:00 PM] [Manager::LogServer][Info] First\n\
+            {spacing}Second\n\
+            [1:00:01 PM][x3] [#2][Manager::LogServer][Error] <b>Oh no</b>\n"
+        );
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn test_export_html() {
+        let html = export_logs(&make_messages(), LogExportFormat::Html, "Logs & Stuff").unwrap();
+        assert!(html.contains("<title>Logs &amp; Stuff</title>"));
+        assert!(html.contains("<tr class=\"error\">"));
+        assert!(html.contains("#2 Manager::LogServer"));
+        assert!(html.contains("&lt;b&gt;Oh no&lt;/b&gt;"));
+        assert!(!html.contains("<b>Oh no</b>"));
+        assert!(html.contains("<td class=\"message\">First\nSecond</td>"));
+    }
+
+    #[test]
+    fn test_write_json_export() {
+        let dir = make_test_dir();
+        let path = dir.path().join("export").join("logs.json");
+        let messages = make_messages();
+        write_log_export(&path, &messages, LogExportFormat::from_path(&path), "Logs").unwrap();
+        let read: Vec<ExportedMessage> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(read, messages);
+    }
+
+    #[test]
+    fn test_json_export_sender_timestamp() {
+        let mut messages = make_messages();
+        messages[0].message.timestamp = Some(1700000000000);
+        let json = export_logs(&messages, LogExportFormat::Json, "Logs").unwrap();
+        assert_eq!(json.matches("\"timestamp\"").count(), 1);
+        let read: Vec<ExportedMessage> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, messages);
+    }
+}
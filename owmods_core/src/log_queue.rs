@@ -0,0 +1,311 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use log::{error, info, warn};
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use tokio::sync::Notify;
+
+use crate::socket::{unix_millis, LogServerSender, SocketMessage};
+
+/// How many messages to keep in memory before spilling to disk
+const MEMORY_CAPACITY: usize = 1000;
+
+/// Stats on how well the consumer of a [crate::socket::LogServer] is keeping up, see [crate::socket::LogServer::metrics]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQueueMetrics {
+    /// Messages received, including ones from the server itself
+    pub received: u64,
+    /// Messages sent to the consumer
+    pub delivered: u64,
+    /// Messages that were written to a temporary file because the consumer was lagging behind
+    pub spilled: u64,
+    /// Messages waiting to be sent to the consumer right now
+    pub lag: u64,
+    /// The most messages that were waiting at once
+    pub max_lag: u64,
+    /// Messages that were lost, this only happens if the consumer stops listening or the temporary file can't be used
+    pub dropped: u64,
+}
+
+/// Messages written to disk, read back in order once the in-memory queue is empty
+struct Spill {
+    // Kept so the file is deleted once we're done with it
+    _file: NamedTempFile,
+    writer: BufWriter<File>,
+    reader: BufReader<File>,
+    pending: usize,
+}
+
+impl Spill {
+    fn new() -> Result<Self> {
+        let file = NamedTempFile::new()?;
+        let writer = BufWriter::new(file.reopen()?);
+        let reader = BufReader::new(file.reopen()?);
+        Ok(Self {
+            _file: file,
+            writer,
+            reader,
+            pending: 0,
+        })
+    }
+
+    fn push(&mut self, message: &SocketMessage) -> Result<()> {
+        writeln!(self.writer, "{}", serde_json::to_string(message)?)?;
+        self.pending += 1;
+        Ok(())
+    }
+
+    // Read up to `max` messages back, messages that can't be read are returned as errors so they can be counted as dropped
+    fn take(&mut self, max: usize) -> Vec<Result<SocketMessage>> {
+        if let Err(why) = self.writer.flush() {
+            error!("Couldn't flush spilled logs: {why:?}");
+        }
+        let mut messages = vec![];
+        let mut line = String::new();
+        while messages.len() < max && self.pending > 0 {
+            line.clear();
+            self.pending -= 1;
+            match self.reader.read_line(&mut line) {
+                Ok(0) => {
+                    messages.push(Err(anyhow::anyhow!("Spilled logs ended early")));
+                }
+                Ok(_) => messages.push(serde_json::from_str(line.trim()).map_err(|e| e.into())),
+                Err(why) => messages.push(Err(why.into())),
+            }
+        }
+        messages
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    memory: VecDeque<SocketMessage>,
+    spill: Option<Spill>,
+    producers: usize,
+    consumer_gone: bool,
+    sequence: u32,
+    metrics: LogQueueMetrics,
+}
+
+impl QueueState {
+    fn update_lag(&mut self) {
+        let lag = self.memory.len() + self.spill.as_ref().map(|s| s.pending).unwrap_or(0);
+        self.metrics.lag = lag as u64;
+        self.metrics.max_lag = self.metrics.max_lag.max(self.metrics.lag);
+    }
+
+    fn push(&mut self, mut message: SocketMessage) {
+        self.metrics.received += 1;
+        // Stamped here (under the lock) so sequence numbers match the order messages are delivered in
+        self.sequence += 1;
+        message.sequence = Some(self.sequence);
+        message.received_at = Some(unix_millis());
+        if self.consumer_gone {
+            self.metrics.dropped += 1;
+            return;
+        }
+        // Once we start spilling everything goes to disk until it's caught up, so messages stay in order
+        if self.spill.is_none() && self.memory.len() < MEMORY_CAPACITY {
+            self.memory.push_back(message);
+        } else {
+            if self.spill.is_none() {
+                warn!("Logs incoming faster than they can be handled, buffering them to disk");
+                match Spill::new() {
+                    Ok(spill) => self.spill = Some(spill),
+                    Err(why) => {
+                        error!("Couldn't make a file to buffer logs, dropping log: {why:?}");
+                        self.metrics.dropped += 1;
+                        return;
+                    }
+                }
+            }
+            let spill = self.spill.as_mut().unwrap();
+            if let Err(why) = spill.push(&message) {
+                error!("Couldn't buffer log to disk, dropping it: {why:?}");
+                self.metrics.dropped += 1;
+            } else {
+                self.metrics.spilled += 1;
+            }
+        }
+        self.update_lag();
+    }
+
+    fn pop(&mut self) -> Option<SocketMessage> {
+        loop {
+            if let Some(message) = self.memory.pop_front() {
+                self.update_lag();
+                return Some(message);
+            }
+            let spill = self.spill.as_mut()?;
+            for message in spill.take(MEMORY_CAPACITY) {
+                match message {
+                    Ok(message) => self.memory.push_back(message),
+                    Err(why) => {
+                        error!("Couldn't read buffered log, dropping it: {why:?}");
+                        self.metrics.dropped += 1;
+                    }
+                }
+            }
+            if spill.pending == 0 {
+                info!("Caught up on buffered logs");
+                self.spill = None;
+            }
+        }
+    }
+}
+
+/// A queue between a log server's clients and its consumer that never drops messages.
+/// Messages are kept in memory, and once too many are waiting they're written to a temporary file and read back in order.
+#[derive(Default)]
+pub(crate) struct LogQueue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+}
+
+/// A handle used to add messages to a [LogQueue], the queue's consumer stops once every handle is dropped and the queue is empty
+pub(crate) struct LogQueueSender {
+    queue: Arc<LogQueue>,
+}
+
+impl LogQueueSender {
+    /// Add a message to the queue, this never blocks waiting for the consumer
+    pub fn send(&self, message: SocketMessage) {
+        self.queue.state.lock().unwrap().push(message);
+        self.queue.notify.notify_one();
+    }
+}
+
+impl Clone for LogQueueSender {
+    fn clone(&self) -> Self {
+        self.queue.sender()
+    }
+}
+
+impl Drop for LogQueueSender {
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().producers -= 1;
+        self.queue.notify.notify_one();
+    }
+}
+
+impl LogQueue {
+    /// Get a new handle to add messages to the queue
+    pub fn sender(self: &Arc<Self>) -> LogQueueSender {
+        self.state.lock().unwrap().producers += 1;
+        LogQueueSender {
+            queue: self.clone(),
+        }
+    }
+
+    /// Get a snapshot of the queue's metrics
+    pub fn metrics(&self) -> LogQueueMetrics {
+        self.state.lock().unwrap().metrics.clone()
+    }
+
+    /// Send messages to `tx` in order as the consumer is ready for them,
+    /// until every [LogQueueSender] is dropped and the queue is empty
+    pub async fn forward(self: Arc<Self>, tx: LogServerSender) {
+        loop {
+            let next = {
+                let mut state = self.state.lock().unwrap();
+                match state.pop() {
+                    Some(message) => Some(message),
+                    None if state.producers == 0 => break,
+                    None => None,
+                }
+            };
+            if let Some(message) = next {
+                if tx.send(message).await.is_err() {
+                    let mut state = self.state.lock().unwrap();
+                    error!("Log consumer stopped listening, dropping remaining logs");
+                    state.consumer_gone = true;
+                    state.metrics.dropped += 1;
+                    while state.pop().is_some() {
+                        state.metrics.dropped += 1;
+                    }
+                    state.update_lag();
+                } else {
+                    self.state.lock().unwrap().metrics.delivered += 1;
+                }
+            } else {
+                self.notify.notified().await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tokio::sync::mpsc;
+
+    use crate::socket::SocketMessageType;
+
+    use super::*;
+
+    fn msg(i: usize) -> SocketMessage {
+        SocketMessage::make_internal(&format!("Message {i}"), SocketMessageType::Message)
+    }
+
+    #[test]
+    fn test_queue_spills_in_order() {
+        tokio_test::block_on(async {
+            let queue = Arc::new(LogQueue::default());
+            let sender = queue.sender();
+            let total = MEMORY_CAPACITY * 3 + 5;
+            // Nothing is consuming yet, so everything past the memory capacity is spilled
+            for i in 0..total {
+                sender.send(msg(i));
+            }
+            let metrics = queue.metrics();
+            assert_eq!(metrics.lag, total as u64);
+            assert_eq!(metrics.spilled, (total - MEMORY_CAPACITY) as u64);
+            // Messages sent while catching up still come after the spilled ones
+            sender.send(msg(total));
+            drop(sender);
+
+            let (tx, mut rx) = mpsc::channel(8);
+            let (_, received) = tokio::join!(queue.clone().forward(tx), async {
+                let mut received = vec![];
+                while let Some(message) = rx.recv().await {
+                    received.push(message.message);
+                }
+                received
+            });
+            assert_eq!(received.len(), total + 1);
+            for (i, message) in received.iter().enumerate() {
+                assert_eq!(message, &format!("Message {i}"));
+            }
+            let metrics = queue.metrics();
+            assert_eq!(metrics.delivered, total as u64 + 1);
+            assert_eq!(metrics.lag, 0);
+            assert_eq!(metrics.max_lag, total as u64 + 1);
+            assert_eq!(metrics.dropped, 0);
+        });
+    }
+
+    #[test]
+    fn test_queue_consumer_gone() {
+        tokio_test::block_on(async {
+            let queue = Arc::new(LogQueue::default());
+            let sender = queue.sender();
+            for i in 0..10 {
+                sender.send(msg(i));
+            }
+            let (tx, rx) = mpsc::channel(8);
+            drop(rx);
+            drop(sender);
+            queue.clone().forward(tx).await;
+            let metrics = queue.metrics();
+            assert_eq!(metrics.delivered, 0);
+            assert_eq!(metrics.dropped, 10);
+        });
+    }
+}
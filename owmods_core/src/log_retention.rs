@@ -0,0 +1,370 @@
+use std::{
+    cmp::Reverse,
+    fs::{read_dir, remove_dir, remove_file, File},
+    io::{copy, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use flate2::{write::GzEncoder, Compression};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    constants::{
+        DEFAULT_LOG_COMPRESS_AFTER_DAYS, DEFAULT_LOG_MAX_AGE_DAYS, DEFAULT_LOG_MAX_FILES,
+        DEFAULT_LOG_MAX_TOTAL_SIZE_MB, GAME_LOGS_DIR_NAME, LOGS_DIR_NAME,
+    },
+    file::get_app_path,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Files written to recently might still be in use (a game that's still running for example), so they're never touched
+const IN_USE_GRACE: Duration = Duration::from_secs(60 * 60);
+
+/// How long to keep manager and game logs, each limit applies to the `logs` and `game_logs` folders separately
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogRetentionPolicy {
+    /// Delete log files older than this many days, `0` keeps them forever
+    pub max_age_days: u32,
+    /// Delete the oldest log files once the folder takes up more than this many megabytes, `0` for no limit
+    pub max_total_size_mb: u32,
+    /// Only keep this many of the newest log files, `0` for no limit
+    pub max_files: u32,
+    /// Compress log files (with gzip) once they're older than this many days, `0` never compresses them
+    pub compress_after_days: u32,
+}
+
+impl Default for LogRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: DEFAULT_LOG_MAX_AGE_DAYS,
+            max_total_size_mb: DEFAULT_LOG_MAX_TOTAL_SIZE_MB,
+            max_files: DEFAULT_LOG_MAX_FILES,
+            compress_after_days: DEFAULT_LOG_COMPRESS_AFTER_DAYS,
+        }
+    }
+}
+
+/// What [prune_logs] did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogPruneReport {
+    /// How many files were compressed
+    pub compressed: usize,
+    /// How many files were deleted
+    pub deleted: usize,
+    /// How many bytes were freed, by both compressing and deleting
+    pub freed_bytes: u64,
+}
+
+impl LogPruneReport {
+    /// Whether nothing was changed
+    pub fn is_empty(&self) -> bool {
+        self.compressed == 0 && self.deleted == 0
+    }
+
+    fn merge(&mut self, other: LogPruneReport) {
+        self.compressed += other.compressed;
+        self.deleted += other.deleted;
+        self.freed_bytes += other.freed_bytes;
+    }
+}
+
+struct LogFile {
+    path: PathBuf,
+    modified: SystemTime,
+    size: u64,
+}
+
+impl LogFile {
+    fn read(path: PathBuf) -> Result<Self> {
+        let meta = path.metadata()?;
+        Ok(Self {
+            path,
+            modified: meta.modified()?,
+            size: meta.len(),
+        })
+    }
+
+    fn age(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.modified).unwrap_or(Duration::ZERO)
+    }
+
+    fn is_compressed(&self) -> bool {
+        self.path.extension().is_some_and(|e| e == "gz")
+    }
+
+    // Compress to `<name>.gz` and delete the original, the new file keeps the original's modified time so its age doesn't reset
+    fn compress(&mut self) -> Result<u64> {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".gz");
+        let new_path = self.path.with_file_name(name);
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let mut encoder = GzEncoder::new(
+            BufWriter::new(File::create(&new_path)?),
+            Compression::default(),
+        );
+        copy(&mut reader, &mut encoder)?;
+        let file = encoder.finish()?.into_inner()?;
+        file.set_modified(self.modified)?;
+        let new_size = file.metadata()?.len();
+        remove_file(&self.path)?;
+        let freed = self.size.saturating_sub(new_size);
+        self.path = new_path;
+        self.size = new_size;
+        Ok(freed)
+    }
+}
+
+fn days(days: u32) -> Duration {
+    Duration::from_secs(days as u64 * SECONDS_PER_DAY)
+}
+
+/// Apply a retention policy to a folder of logs laid out as `<dir>/<date>/<file>`.
+/// Old files are compressed first, then the oldest files are deleted until the folder is within the policy's limits.
+/// Folders left empty are removed.
+///
+/// Files written to in the last hour are never compressed or deleted since they may still be in use, but they still count towards the limits.
+///
+/// ## Returns
+///
+/// What was compressed and deleted
+///
+/// ## Errors
+///
+/// If we can't read the folder, problems with single files are logged and skipped.
+///
+pub fn prune_log_dir(dir: &Path, policy: &LogRetentionPolicy) -> Result<LogPruneReport> {
+    let mut report = LogPruneReport::default();
+    if !dir.is_dir() {
+        return Ok(report);
+    }
+    let now = SystemTime::now();
+
+    let mut sub_dirs = vec![];
+    let mut files = vec![];
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        for entry in read_dir(&path)? {
+            let file_path = entry?.path();
+            if file_path.is_file() {
+                match LogFile::read(file_path) {
+                    Ok(file) => files.push(file),
+                    Err(why) => warn!("Couldn't read log file: {why:?}"),
+                }
+            }
+        }
+        sub_dirs.push(path);
+    }
+
+    let too_old =
+        |file: &LogFile| policy.max_age_days != 0 && file.age(now) > days(policy.max_age_days);
+
+    if policy.compress_after_days != 0 {
+        let compress_after = days(policy.compress_after_days).max(IN_USE_GRACE);
+        // No point compressing files we're about to delete
+        for file in files
+            .iter_mut()
+            .filter(|f| !f.is_compressed() && f.age(now) > compress_after && !too_old(f))
+        {
+            match file.compress() {
+                Ok(freed) => {
+                    debug!("Compressed {}", file.path.display());
+                    report.compressed += 1;
+                    report.freed_bytes += freed;
+                }
+                Err(why) => warn!("Couldn't compress {}: {why:?}", file.path.display()),
+            }
+        }
+    }
+
+    // Newest first, so the files we keep are the most recent ones
+    files.sort_by_key(|f| Reverse(f.modified));
+    let max_size = policy.max_total_size_mb as u64 * 1024 * 1024;
+    let (mut kept_files, mut kept_size) = (0, 0);
+    for file in files {
+        let too_many = policy.max_files != 0 && kept_files >= policy.max_files;
+        let too_big = max_size != 0 && kept_size + file.size > max_size;
+        if file.age(now) > IN_USE_GRACE && (too_old(&file) || too_many || too_big) {
+            match remove_file(&file.path) {
+                Ok(_) => {
+                    debug!("Deleted {}", file.path.display());
+                    report.deleted += 1;
+                    report.freed_bytes += file.size;
+                    continue;
+                }
+                Err(why) => warn!("Couldn't delete {}: {why:?}", file.path.display()),
+            }
+        }
+        kept_files += 1;
+        kept_size += file.size;
+    }
+
+    for sub_dir in sub_dirs {
+        if read_dir(&sub_dir)?.next().is_none() {
+            remove_dir(&sub_dir).ok();
+        }
+    }
+
+    Ok(report)
+}
+
+/// Apply a retention policy to the manager's logs (`logs`) and game logs (`game_logs`) in the app data directory, see [prune_log_dir].
+///
+/// ## Returns
+///
+/// What was compressed and deleted in both folders
+///
+/// ## Errors
+///
+/// If we can't get the app data directory or read either folder.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::config::Config;
+/// use owmods_core::log_retention::prune_logs;
+///
+/// let config = Config::get(None).unwrap();
+/// let report = prune_logs(&config.log_retention).unwrap();
+/// println!("Deleted {} old log(s)", report.deleted);
+/// ```
+///
+pub fn prune_logs(policy: &LogRetentionPolicy) -> Result<LogPruneReport> {
+    let app_path = get_app_path()?;
+    let mut report = prune_log_dir(&app_path.join(LOGS_DIR_NAME), policy)?;
+    report.merge(prune_log_dir(&app_path.join(GAME_LOGS_DIR_NAME), policy)?);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{fs, io::Read};
+
+    use flate2::read::GzDecoder;
+
+    use crate::test_utils::make_test_dir;
+
+    use super::*;
+
+    fn make_log(dir: &Path, name: &str, size: usize, age_days: u64) -> PathBuf {
+        let path = dir.join("2024-01-01").join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "a".repeat(size)).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age_days * SECONDS_PER_DAY + 60);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        path
+    }
+
+    fn no_limits() -> LogRetentionPolicy {
+        LogRetentionPolicy {
+            max_age_days: 0,
+            max_total_size_mb: 0,
+            max_files: 0,
+            compress_after_days: 0,
+        }
+    }
+
+    #[test]
+    fn test_prune_compresses_old_logs() {
+        let dir = make_test_dir();
+        let old = make_log(dir.path(), "old.jsonl", 10000, 3);
+        let new = make_log(dir.path(), "new.jsonl", 10000, 0);
+        let expired = make_log(dir.path(), "expired.jsonl", 10000, 40);
+        let policy = LogRetentionPolicy {
+            compress_after_days: 1,
+            max_age_days: 30,
+            ..no_limits()
+        };
+        let report = prune_log_dir(dir.path(), &policy).unwrap();
+        assert_eq!(report.compressed, 1);
+        assert_eq!(report.deleted, 1);
+        assert!(!expired.exists());
+        assert!(!expired.with_file_name("expired.jsonl.gz").exists());
+        assert!(report.freed_bytes > 0);
+        assert!(!old.exists());
+        assert!(new.exists());
+        let mut contents = String::new();
+        GzDecoder::new(File::open(old.with_file_name("old.jsonl.gz")).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "a".repeat(10000));
+        // Compressing again shouldn't do anything
+        let report = prune_log_dir(dir.path(), &policy).unwrap();
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_prune_limits() {
+        let dir = make_test_dir();
+        let oldest = make_log(dir.path(), "1.log", 10, 40);
+        let older = make_log(dir.path(), "2.log", 10, 20);
+        let old = make_log(dir.path(), "3.log", 10, 10);
+        let new = make_log(dir.path(), "4.log", 10, 5);
+
+        let report = prune_log_dir(
+            dir.path(),
+            &LogRetentionPolicy {
+                max_age_days: 30,
+                ..no_limits()
+            },
+        )
+        .unwrap();
+        assert_eq!(report.deleted, 1);
+        assert!(!oldest.exists());
+
+        let report = prune_log_dir(
+            dir.path(),
+            &LogRetentionPolicy {
+                max_files: 2,
+                ..no_limits()
+            },
+        )
+        .unwrap();
+        assert_eq!(report.deleted, 1);
+        assert!(!older.exists());
+        assert!(old.exists() && new.exists());
+
+        fs::write(&new, "a".repeat(2 * 1024 * 1024)).unwrap();
+        let report = prune_log_dir(
+            dir.path(),
+            &LogRetentionPolicy {
+                max_total_size_mb: 1,
+                ..no_limits()
+            },
+        )
+        .unwrap();
+        // The newest file was just written to, so it's kept even though it's over the limit alone
+        assert_eq!(report.deleted, 1);
+        assert!(!old.exists());
+        assert!(new.exists());
+    }
+
+    #[test]
+    fn test_prune_removes_empty_dirs() {
+        let dir = make_test_dir();
+        let old = make_log(dir.path(), "old.log", 10, 40);
+        let policy = LogRetentionPolicy {
+            max_age_days: 30,
+            ..no_limits()
+        };
+        let report = prune_log_dir(dir.path(), &policy).unwrap();
+        assert_eq!(report.deleted, 1);
+        assert!(!old.parent().unwrap().exists());
+        assert!(dir.path().is_dir());
+    }
+}
@@ -0,0 +1,462 @@
+use std::{
+    fs::{rename, File},
+    io::{BufWriter, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::Result;
+use log::{error, info, warn};
+use tokio::sync::mpsc;
+
+use crate::{file::create_all_parents, game_logs::GameLogWriter, socket::SocketMessage};
+
+/// Something that can handle messages from the game, see [LogDispatcher] to send messages to multiple sinks at once
+pub trait LogSink: Send {
+    /// Handle a message from the game
+    ///
+    /// ## Errors
+    ///
+    /// If the sink couldn't handle the message, the [LogDispatcher] logs the error and keeps going
+    ///
+    fn write(&mut self, message: &SocketMessage) -> Result<()>;
+
+    /// Called when no more messages are coming, sinks that buffer should write everything out here
+    ///
+    /// ## Errors
+    ///
+    /// If the sink couldn't flush
+    ///
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Format a message as human-readable text, multi-line messages are indented to line up with the header.
+/// Messages from a [crate::socket::LogServer] client start with the client's id (for example `[#1]`) so different clients can be told apart.
+///
+/// ## Examples
+///
+/// ```
+/// use owmods_core::log_sinks::format_message;
+/// use owmods_core::socket::{SocketMessage, SocketMessageType};
+///
+/// let message = SocketMessage::make_internal("Hello!", SocketMessageType::Info);
+/// assert_eq!(format_message(&message), "[Manager::LogServer][Info] Hello!");
+/// ```
+///
+pub fn format_message(message: &SocketMessage) -> String {
+    let unknown = "Unknown";
+    let client = message
+        .client_id
+        .map(|id| format!("[#{id}]"))
+        .unwrap_or_default();
+    let log_header = format!(
+        "{client}[{}::{}][{:?}] ",
+        message.sender_name.as_deref().unwrap_or(unknown),
+        message.sender_type.as_deref().unwrap_or(unknown),
+        message.message_type
+    );
+    if message.message.trim().is_empty() {
+        return log_header;
+    }
+    let spacing = " ".repeat(log_header.len());
+    message
+        .message
+        .lines()
+        .enumerate()
+        .map(|(i, l)| format!("{}{l}", if i == 0 { &log_header } else { &spacing }))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Prints messages to stdout, see [format_message]
+#[derive(Default)]
+pub struct StdoutSink;
+
+impl LogSink for StdoutSink {
+    fn write(&mut self, message: &SocketMessage) -> Result<()> {
+        println!("{}", format_message(message));
+        Ok(())
+    }
+}
+
+/// Writes messages as JSON Lines, see [crate::game_logs]
+impl LogSink for GameLogWriter {
+    fn write(&mut self, message: &SocketMessage) -> Result<()> {
+        GameLogWriter::write(self, message)
+    }
+}
+
+/// Writes messages as text (see [format_message]) to a file, once the file gets too big
+/// it's moved to `<name>.1`, the old `<name>.1` is moved to `<name>.2`, and so on.
+pub struct RotatingFileSink {
+    path: PathBuf,
+    max_size: u64,
+    max_files: u32,
+    size: u64,
+    writer: BufWriter<File>,
+}
+
+impl RotatingFileSink {
+    /// Make a new rotating file sink, appending to the file if it exists.
+    ///
+    /// - `max_size` is how big (in bytes) the file can get before it's rotated
+    /// - `max_files` is how many old files to keep, older files are deleted
+    ///
+    /// ## Errors
+    ///
+    /// If we can't create or open the file
+    ///
+    pub fn new(path: &Path, max_size: u64, max_files: u32) -> Result<Self> {
+        create_all_parents(path)?;
+        let file = File::options().append(true).create(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_size,
+            max_files,
+            size,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    fn rotated_path(&self, n: u32) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{n}"));
+        self.path.with_file_name(name)
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        for n in (1..self.max_files).rev() {
+            let from = self.rotated_path(n);
+            if from.is_file() {
+                rename(&from, self.rotated_path(n + 1))?;
+            }
+        }
+        if self.max_files == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            rename(&self.path, self.rotated_path(1))?;
+        }
+        self.writer = BufWriter::new(File::create(&self.path)?);
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl LogSink for RotatingFileSink {
+    fn write(&mut self, message: &SocketMessage) -> Result<()> {
+        let line = format!("{}\n", format_message(message));
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Re-broadcasts messages to anything that connects over TCP, one JSON-serialized [SocketMessage] per line.
+/// This lets other tools (or another person's log viewer) watch the logs at the same time.
+/// This is plain TCP, WebSocket isn't supported so browsers can't connect to it directly.
+///
+/// Each client is written to on its own thread so a slow client can't hold up the other sinks.
+/// Clients that fall more than [TcpBroadcastSink::CLIENT_BUFFER] messages behind or disconnect are dropped.
+pub struct TcpBroadcastSink {
+    /// The port the sink is listening on
+    pub port: u16,
+    clients: Arc<Mutex<Vec<SyncSender<Arc<str>>>>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl TcpBroadcastSink {
+    /// How many messages can be waiting to be sent to a client before it's dropped
+    pub const CLIENT_BUFFER: usize = 256;
+    const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Listen for clients on the given port, pass 0 to get one from the OS.
+    ///
+    /// ## Errors
+    ///
+    /// If we can't bind to the port
+    ///
+    pub fn bind(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(format!("127.0.0.1:{port}"))?;
+        let port = listener.local_addr()?.port();
+        let clients = Arc::new(Mutex::new(vec![]));
+        let closed = Arc::new(AtomicBool::new(false));
+        let (accept_clients, accept_closed) = (clients.clone(), closed.clone());
+        thread::spawn(move || Self::accept_clients(listener, accept_clients, accept_closed));
+        Ok(Self {
+            port,
+            clients,
+            closed,
+        })
+    }
+
+    fn accept_clients(
+        listener: TcpListener,
+        clients: Arc<Mutex<Vec<SyncSender<Arc<str>>>>>,
+        closed: Arc<AtomicBool>,
+    ) {
+        for stream in listener.incoming() {
+            if closed.load(Ordering::Relaxed) {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(why) => {
+                    warn!("Couldn't accept log broadcast client: {why}");
+                    continue;
+                }
+            };
+            if let Err(why) = stream.set_write_timeout(Some(Self::WRITE_TIMEOUT)) {
+                warn!("Couldn't set up log broadcast client: {why}");
+                continue;
+            }
+            let (tx, rx) = sync_channel(Self::CLIENT_BUFFER);
+            clients.lock().unwrap().push(tx);
+            thread::spawn(move || Self::write_to_client(stream, rx));
+        }
+    }
+
+    fn write_to_client(mut stream: TcpStream, rx: Receiver<Arc<str>>) {
+        let addr = stream
+            .peer_addr()
+            .map(|a| a.to_string())
+            .unwrap_or_default();
+        info!("Log broadcast client connected from {addr}");
+        for line in rx {
+            if let Err(why) = stream.write_all(line.as_bytes()) {
+                warn!("Dropping log broadcast client {addr}: {why}");
+                break;
+            }
+        }
+    }
+}
+
+impl LogSink for TcpBroadcastSink {
+    fn write(&mut self, message: &SocketMessage) -> Result<()> {
+        let line: Arc<str> = format!("{}\n", serde_json::to_string(message)?).into();
+        self.clients
+            .lock()
+            .unwrap()
+            .retain(|client| match client.try_send(line.clone()) {
+                Ok(_) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!("Dropping log broadcast client, it can't keep up");
+                    false
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+        Ok(())
+    }
+}
+
+impl Drop for TcpBroadcastSink {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+        // Wake the accept thread up so it sees we're closed
+        TcpStream::connect(("127.0.0.1", self.port)).ok();
+    }
+}
+
+/// Sends every message to a list of [LogSink]s
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::log_sinks::{LogDispatcher, StdoutSink, RotatingFileSink};
+/// use owmods_core::socket::LogServer;
+/// use std::path::Path;
+///
+/// # tokio_test::block_on(async {
+/// let mut dispatcher = LogDispatcher::default();
+/// dispatcher.add(StdoutSink);
+/// dispatcher.add(RotatingFileSink::new(Path::new("game.log"), 1024 * 1024, 3).unwrap());
+///
+/// let server = LogServer::new(0).await.unwrap();
+/// let (tx, mut rx) = tokio::sync::mpsc::channel(32);
+/// tokio::join!(server.listen(tx, true), dispatcher.consume(&mut rx));
+/// # });
+/// ```
+///
+#[derive(Default)]
+pub struct LogDispatcher {
+    sinks: Vec<Box<dyn LogSink>>,
+}
+
+impl LogDispatcher {
+    /// Add a sink to send messages to
+    pub fn add(&mut self, sink: impl LogSink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    /// Whether there are no sinks
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Send a message to every sink, sinks that fail log an error and don't stop the others
+    pub fn dispatch(&mut self, message: &SocketMessage) {
+        for sink in self.sinks.iter_mut() {
+            if let Err(why) = sink.write(message) {
+                error!("Couldn't send log to sink: {:?}", why);
+            }
+        }
+    }
+
+    /// Flush every sink
+    pub fn flush(&mut self) {
+        for sink in self.sinks.iter_mut() {
+            if let Err(why) = sink.flush() {
+                error!("Couldn't flush log sink: {:?}", why);
+            }
+        }
+    }
+
+    /// Dispatch every message from a log server channel until it's closed, then flush every sink
+    pub async fn consume(&mut self, rx: &mut mpsc::Receiver<SocketMessage>) {
+        while let Some(message) = rx.recv().await {
+            self.dispatch(&message);
+        }
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{
+        fs,
+        io::{BufRead, BufReader},
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{socket::SocketMessageType, test_utils::make_test_dir};
+
+    use super::*;
+
+    struct VecSink(Arc<Mutex<Vec<String>>>);
+
+    impl LogSink for VecSink {
+        fn write(&mut self, message: &SocketMessage) -> Result<()> {
+            self.0.lock().unwrap().push(message.message.clone());
+            Ok(())
+        }
+    }
+
+    struct FailingSink;
+
+    impl LogSink for FailingSink {
+        fn write(&mut self, _: &SocketMessage) -> Result<()> {
+            Err(anyhow::anyhow!("Nope"))
+        }
+    }
+
+    fn msg(message: &str) -> SocketMessage {
+        SocketMessage::make_internal(message, SocketMessageType::Message)
+    }
+
+    #[test]
+    fn test_format_message_multi_line() {
+        let formatted = format_message(&msg("First\nSecond"));
+        assert_eq!(
+            formatted,
+            "[Manager::LogServer][Message] First\n                              Second"
+        );
+        let formatted = format_message(&msg("Hello").with_client_id(2));
+        assert_eq!(formatted, "[#2][Manager::LogServer][Message] Hello");
+    }
+
+    #[test]
+    fn test_dispatcher() {
+        let first = Arc::new(Mutex::new(vec![]));
+        let second = Arc::new(Mutex::new(vec![]));
+        let mut dispatcher = LogDispatcher::default();
+        dispatcher.add(VecSink(first.clone()));
+        dispatcher.add(FailingSink);
+        dispatcher.add(VecSink(second.clone()));
+        dispatcher.dispatch(&msg("Hello"));
+        dispatcher.dispatch(&msg("World"));
+        assert_eq!(*first.lock().unwrap(), vec!["Hello", "World"]);
+        assert_eq!(*second.lock().unwrap(), vec!["Hello", "World"]);
+    }
+
+    #[test]
+    fn test_rotating_file_sink() {
+        let dir = make_test_dir();
+        let path = dir.path().join("game.log");
+        let line_len = format_message(&msg("0")).len() as u64 + 1;
+        let mut sink = RotatingFileSink::new(&path, line_len * 2, 2).unwrap();
+        for i in 0..7 {
+            sink.write(&msg(&i.to_string())).unwrap();
+        }
+        let read = |p: &Path| {
+            fs::read_to_string(p)
+                .unwrap()
+                .lines()
+                .map(|l| l.chars().last().unwrap())
+                .collect::<String>()
+        };
+        assert_eq!(read(&path), "6");
+        assert_eq!(read(&dir.path().join("game.log.1")), "45");
+        assert_eq!(read(&dir.path().join("game.log.2")), "23");
+        assert!(!dir.path().join("game.log.3").exists());
+    }
+
+    fn connect(sink: &TcpBroadcastSink) -> TcpStream {
+        let client = TcpStream::connect(format!("127.0.0.1:{}", sink.port)).unwrap();
+        // Clients are accepted on another thread
+        for _ in 0..100 {
+            if !sink.clients.lock().unwrap().is_empty() {
+                return client;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("Client wasn't accepted");
+    }
+
+    #[test]
+    fn test_tcp_broadcast_sink() {
+        let mut sink = TcpBroadcastSink::bind(0).unwrap();
+        let client = connect(&sink);
+        let message = msg("Hello");
+        sink.write(&message).unwrap();
+        let mut line = String::new();
+        BufReader::new(client).read_line(&mut line).unwrap();
+        let received: SocketMessage = serde_json::from_str(&line).unwrap();
+        assert_eq!(received, message);
+        assert_eq!(sink.clients.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tcp_broadcast_sink_slow_client() {
+        let mut sink = TcpBroadcastSink::bind(0).unwrap();
+        // Never reads, so the socket fills up and then the client's buffer does
+        let _client = connect(&sink);
+        let message = msg(&"a".repeat(64 * 1024));
+        for _ in 0..10_000 {
+            sink.write(&message).unwrap();
+            if sink.clients.lock().unwrap().is_empty() {
+                return;
+            }
+        }
+        panic!("Slow client wasn't dropped");
+    }
+}
@@ -0,0 +1,502 @@
+use std::{
+    fs::{self, remove_dir_all},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tempfile::TempDir;
+use typeshare::typeshare;
+
+use crate::{
+    config::Config,
+    db::{fix_version, LocalDatabase, RemoteDatabase},
+    file::{
+        check_unique_name, copy_dir_all, deserialize_from_json, fix_json_file, mod_folder_path,
+        move_dir,
+    },
+    mods::local::{LocalMod, ModManifest, ModStubConfig},
+    toggle::generate_config,
+    trash::trash_mod,
+};
+
+/// A mod found in a folder we're migrating from
+#[typeshare]
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationCandidate {
+    /// The unique name of the mod
+    pub unique_name: String,
+    /// The name of the mod
+    pub name: String,
+    /// The version of the mod in the folder we're migrating from
+    pub version: String,
+    /// The path to the mod's folder
+    pub source_path: String,
+    /// Whether the mod is enabled, this is kept when adopting the mod
+    pub enabled: bool,
+    /// The version of the mod in the database, [None] if the mod isn't in the database
+    pub remote_version: Option<String>,
+    /// The version of the mod that's already installed, if any
+    pub installed_version: Option<String>,
+}
+
+impl MigrationCandidate {
+    /// Whether the mod isn't in the database, meaning this folder is the only place to get it from
+    pub fn is_local_only(&self) -> bool {
+        self.remote_version.is_none()
+    }
+}
+
+/// The result of scanning a folder to migrate from, see [scan_migration_source]
+#[typeshare]
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationScan {
+    /// The `Mods` folder that was scanned
+    pub mods_path: String,
+    /// The mods that were found, sorted by name
+    pub mods: Vec<MigrationCandidate>,
+    /// Folders that have a manifest we couldn't read (or with an invalid unique name), or a duplicate of a mod that was already found
+    pub invalid: Vec<String>,
+}
+
+impl MigrationScan {
+    /// Get the mods that aren't in the database
+    pub fn local_only(&self) -> impl Iterator<Item = &MigrationCandidate> {
+        self.mods.iter().filter(|m| m.is_local_only())
+    }
+}
+
+/// How to adopt mods when migrating
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationMode {
+    /// Copy the mods, leaving the source folder untouched
+    Copy,
+    /// Move the mods, removing them from the source folder
+    Move,
+}
+
+/// The result of adopting mods, see [adopt_mods]
+#[typeshare]
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationResult {
+    /// The unique names of the mods that were adopted
+    pub adopted: Vec<String>,
+    /// The unique names of the mods that were skipped because they're already installed
+    pub skipped: Vec<String>,
+}
+
+fn get_mods_path(path: &Path) -> Result<PathBuf> {
+    let mods_path = path.join("Mods");
+    if mods_path.is_dir() {
+        Ok(mods_path)
+    } else if path.is_dir() {
+        Ok(path.to_path_buf())
+    } else {
+        Err(anyhow!("{} isn't a folder", path.display()))
+    }
+}
+
+// We don't use LocalDatabase::fetch here as it'd generate configs in the folder we're migrating from
+fn read_candidate(manifest_path: &Path) -> Result<(ModManifest, bool)> {
+    fix_json_file(manifest_path).ok();
+    let mut manifest: ModManifest = deserialize_from_json(manifest_path)?;
+    check_unique_name(&manifest.unique_name)?;
+    manifest.version = fix_version(&manifest.version).to_string();
+    let config_path = manifest_path.with_file_name("config.json");
+    let enabled = if config_path.is_file() {
+        fix_json_file(&config_path).ok();
+        deserialize_from_json::<ModStubConfig>(&config_path)?.enabled
+    } else {
+        true
+    };
+    Ok((manifest, enabled))
+}
+
+/// Scan an OWML folder or a `Mods` folder from another install (or another PC) for mods we can adopt.
+/// Each mod found is matched against the database and the installed mods. This doesn't change anything.
+///
+/// ## Returns
+///
+/// A [MigrationScan] with every mod found, mods not in the database are considered local-only (see [MigrationScan::local_only]).
+///
+/// ## Errors
+///
+/// If the path isn't a folder, or we can't search it for manifests.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::migrate::scan_migration_source;
+/// use owmods_core::db::{LocalDatabase, RemoteDatabase};
+/// use owmods_core::config::Config;
+/// use std::path::PathBuf;
+///
+/// # tokio_test::block_on(async {
+/// let config = Config::get(None).unwrap();
+/// let remote_db = RemoteDatabase::fetch(&config.database_url).await.unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// let scan = scan_migration_source(&PathBuf::from("/old/OWML"), &local_db, &remote_db).unwrap();
+///
+/// for local_mod in scan.local_only() {
+///     println!("{} isn't in the database", local_mod.name);
+/// }
+/// # });
+/// ```
+///
+pub fn scan_migration_source(
+    path: &Path,
+    local_db: &LocalDatabase,
+    remote_db: &RemoteDatabase,
+) -> Result<MigrationScan> {
+    let mods_path = get_mods_path(path)?;
+    debug!("Scanning {} for mods to migrate", mods_path.display());
+    let mut mods: Vec<MigrationCandidate> = vec![];
+    let mut invalid: Vec<String> = vec![];
+    let glob_matches = glob::glob(mods_path.join("**").join("manifest.json").to_str().unwrap())?;
+    for entry in glob_matches {
+        let entry = entry?;
+        let source_path = entry
+            .parent()
+            .ok_or_else(|| anyhow!("Invalid Manifest!"))?
+            .to_str()
+            .unwrap()
+            .to_string();
+        match read_candidate(&entry) {
+            Ok((manifest, enabled)) => {
+                if mods.iter().any(|m| m.unique_name == manifest.unique_name) {
+                    warn!(
+                        "Found {} twice while scanning, skipping {}",
+                        manifest.unique_name, source_path
+                    );
+                    invalid.push(source_path);
+                    continue;
+                }
+                mods.push(MigrationCandidate {
+                    remote_version: remote_db
+                        .get_mod(&manifest.unique_name)
+                        .map(|m| m.version.clone()),
+                    installed_version: local_db
+                        .get_mod(&manifest.unique_name)
+                        .map(|m| m.manifest.version.clone()),
+                    unique_name: manifest.unique_name,
+                    name: manifest.name,
+                    version: manifest.version,
+                    source_path,
+                    enabled,
+                });
+            }
+            Err(why) => {
+                warn!("Failed to read mod at {}: {:?}", source_path, why);
+                invalid.push(source_path);
+            }
+        }
+    }
+    mods.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(MigrationScan {
+        mods_path: mods_path.to_str().unwrap().to_string(),
+        mods,
+        invalid,
+    })
+}
+
+/// Adopt mods found by [scan_migration_source] into our `Mods` folder.
+/// Each mod keeps its `config.json`, so its settings and enabled state stay the same.
+/// Filter the mods in the scan first to only adopt some of them.
+///
+/// ## Returns
+///
+/// A [MigrationResult] with the mods that were adopted and the ones that were skipped because they're already installed.
+/// If `replace_existing` is set installed mods are replaced instead of skipped, the installed copy is moved to the trash (see [crate::trash]).
+///
+/// ## Errors
+///
+/// If we can't copy or move a mod, a mod has an invalid unique name, a folder that isn't the installed mod is already where a mod would go,
+/// or the scan is of our own `Mods` folder.
+/// Mods adopted before the error stay adopted.
+/// Each mod is copied or moved next to our `Mods` folder first, so an installed mod is only replaced once that worked.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::migrate::{scan_migration_source, adopt_mods, MigrationMode};
+/// use owmods_core::db::{LocalDatabase, RemoteDatabase};
+/// use owmods_core::config::Config;
+/// use std::path::PathBuf;
+///
+/// # tokio_test::block_on(async {
+/// let config = Config::get(None).unwrap();
+/// let remote_db = RemoteDatabase::fetch(&config.database_url).await.unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// let scan = scan_migration_source(&PathBuf::from("/old/OWML"), &local_db, &remote_db).unwrap();
+/// let result = adopt_mods(&config, &local_db, &scan, MigrationMode::Copy, false).unwrap();
+///
+/// println!("Adopted {} mod(s)", result.adopted.len());
+/// # });
+/// ```
+///
+pub fn adopt_mods(
+    config: &Config,
+    local_db: &LocalDatabase,
+    scan: &MigrationScan,
+    mode: MigrationMode,
+    replace_existing: bool,
+) -> Result<MigrationResult> {
+    let our_mods_path = PathBuf::from(&config.owml_path).join("Mods");
+    if our_mods_path.is_dir()
+        && PathBuf::from(&scan.mods_path).canonicalize()? == our_mods_path.canonicalize()?
+    {
+        return Err(anyhow!("Can't migrate from the current Mods folder"));
+    }
+    let target_paths = scan
+        .mods
+        .iter()
+        .map(|candidate| mod_folder_path(&our_mods_path, &candidate.unique_name))
+        .collect::<Result<Vec<_>>>()?;
+    fs::create_dir_all(&our_mods_path)?;
+    let mut result = MigrationResult::default();
+    for (candidate, target_path) in scan.mods.iter().zip(target_paths) {
+        let installed = local_db.get_mod(&candidate.unique_name);
+        if installed.is_some() && !replace_existing {
+            info!("{} is already installed, skipping", candidate.unique_name);
+            result.skipped.push(candidate.unique_name.clone());
+            continue;
+        }
+        adopt_mod(config, candidate, installed, &target_path, mode)?;
+        let config_path = target_path.join("config.json");
+        if !config_path.is_file() {
+            generate_config(&config_path)?;
+        }
+        info!("Adopted {} from {}", candidate.name, candidate.source_path);
+        result.adopted.push(candidate.unique_name.clone());
+    }
+    Ok(result)
+}
+
+fn is_same_folder(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn adopt_mod(
+    config: &Config,
+    candidate: &MigrationCandidate,
+    installed: Option<&LocalMod>,
+    target_path: &Path,
+    mode: MigrationMode,
+) -> Result<()> {
+    // Only the installed mod we're replacing can be in the way, anything else there is left alone
+    if target_path.exists()
+        && !installed.is_some_and(|m| is_same_folder(Path::new(&m.mod_path), target_path))
+    {
+        return Err(anyhow!(
+            "Can't adopt {}, {} already exists",
+            candidate.name,
+            target_path.display()
+        ));
+    }
+    let source_path = PathBuf::from(&candidate.source_path);
+    let staging_dir = TempDir::new_in(&config.owml_path)?;
+    let staged_path = staging_dir.path().join(&candidate.unique_name);
+    match mode {
+        MigrationMode::Copy => copy_dir_all(&source_path, &staged_path)?,
+        MigrationMode::Move => move_dir(&source_path, &staged_path)?,
+    }
+    let swap = || -> Result<()> {
+        let trashed = installed
+            .map(|installed| trash_mod(config, installed).map(|t| (installed, t)))
+            .transpose()?;
+        if let Err(why) = fs::rename(&staged_path, target_path) {
+            if let Some((installed, trashed)) = trashed {
+                move_dir(&trashed.files_path(config), Path::new(&installed.mod_path))?;
+                remove_dir_all(trashed.path(config))?;
+            }
+            return Err(why.into());
+        }
+        Ok(())
+    };
+    if let Err(why) = swap() {
+        if mode == MigrationMode::Move {
+            move_dir(&staged_path, &source_path)?;
+        }
+        return Err(why);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        mods::remote::RemoteMod,
+        test_utils::{get_test_file, TestContext},
+        trash::get_trashed_mods,
+    };
+
+    use super::*;
+
+    fn setup(ctx: &mut TestContext) -> MigrationScan {
+        let mut remote_mod = RemoteMod::get_test(0);
+        remote_mod.unique_name = "Bwc9876.TimeSaver".to_string();
+        ctx.remote_db
+            .mods
+            .insert(remote_mod.unique_name.clone(), remote_mod);
+        let source = ctx.temp_dir.path().join("OldOWML");
+        copy_dir_all(&get_test_file("Mods"), &source.join("Mods")).unwrap();
+        scan_migration_source(&source, &ctx.local_db, &ctx.remote_db).unwrap()
+    }
+
+    #[test]
+    fn test_scan_migration_source() {
+        let mut ctx = TestContext::new();
+        let scan = setup(&mut ctx);
+        assert_eq!(scan.mods.len(), 2);
+        assert!(scan.invalid.is_empty());
+        let local_only: Vec<&MigrationCandidate> = scan.local_only().collect();
+        assert_eq!(local_only.len(), 1);
+        assert_eq!(local_only[0].unique_name, "Bwc9876.SaveEditor");
+        assert!(!local_only[0].enabled);
+        let time_saver = scan
+            .mods
+            .iter()
+            .find(|m| m.unique_name == "Bwc9876.TimeSaver")
+            .unwrap();
+        assert_eq!(time_saver.remote_version.as_deref(), Some("0.0.0"));
+        assert!(time_saver.installed_version.is_none());
+    }
+
+    #[test]
+    fn test_scan_migration_source_invalid() {
+        let ctx = TestContext::new();
+        let scan = scan_migration_source(&get_test_file("Invalid"), &ctx.local_db, &ctx.remote_db)
+            .unwrap();
+        assert_eq!(scan.mods.len(), 1);
+        assert_eq!(scan.invalid.len(), 2);
+    }
+
+    #[test]
+    fn test_adopt_mods_copy() {
+        let mut ctx = TestContext::new();
+        let scan = setup(&mut ctx);
+        let result = adopt_mods(
+            &ctx.config,
+            &ctx.local_db,
+            &scan,
+            MigrationMode::Copy,
+            false,
+        )
+        .unwrap();
+        assert_eq!(result.adopted.len(), 2);
+        ctx.fetch_local_db();
+        assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").unwrap().enabled);
+        assert!(!ctx.local_db.get_mod("Bwc9876.SaveEditor").unwrap().enabled);
+        for candidate in scan.mods.iter() {
+            assert!(PathBuf::from(&candidate.source_path).is_dir());
+        }
+    }
+
+    #[test]
+    fn test_adopt_mods_move() {
+        let mut ctx = TestContext::new();
+        let scan = setup(&mut ctx);
+        adopt_mods(
+            &ctx.config,
+            &ctx.local_db,
+            &scan,
+            MigrationMode::Move,
+            false,
+        )
+        .unwrap();
+        ctx.fetch_local_db();
+        assert_eq!(ctx.local_db.valid().count(), 2);
+        for candidate in scan.mods.iter() {
+            assert!(!PathBuf::from(&candidate.source_path).exists());
+        }
+    }
+
+    #[test]
+    fn test_adopt_mods_skip_installed() {
+        let mut ctx = TestContext::new();
+        ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let scan = setup(&mut ctx);
+        let result = adopt_mods(
+            &ctx.config,
+            &ctx.local_db,
+            &scan,
+            MigrationMode::Copy,
+            false,
+        )
+        .unwrap();
+        assert_eq!(result.adopted, vec!["Bwc9876.SaveEditor".to_string()]);
+        assert_eq!(result.skipped, vec!["Bwc9876.TimeSaver".to_string()]);
+    }
+
+    #[test]
+    fn test_adopt_mods_own_folder() {
+        let mut ctx = TestContext::new();
+        ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let scan = scan_migration_source(&ctx.owml_dir, &ctx.local_db, &ctx.remote_db).unwrap();
+        assert!(adopt_mods(&ctx.config, &ctx.local_db, &scan, MigrationMode::Copy, true).is_err());
+    }
+
+    #[test]
+    fn test_adopt_mods_replace_installed() {
+        let mut ctx = TestContext::new();
+        ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let scan = setup(&mut ctx);
+        let result =
+            adopt_mods(&ctx.config, &ctx.local_db, &scan, MigrationMode::Copy, true).unwrap();
+        assert_eq!(result.adopted.len(), 2);
+        assert!(result.skipped.is_empty());
+        let trashed = get_trashed_mods(&ctx.config).unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].unique_name, "Bwc9876.TimeSaver");
+        ctx.fetch_local_db();
+        assert_eq!(ctx.local_db.valid().count(), 2);
+    }
+
+    #[test]
+    fn test_adopt_mods_invalid_unique_name() {
+        let mut ctx = TestContext::new();
+        ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let mut scan = setup(&mut ctx);
+        for unique_name in ["", "..", "../Evil", "Mods/Bwc9876.TimeSaver"] {
+            scan.mods[0].unique_name = unique_name.to_string();
+            assert!(
+                adopt_mods(&ctx.config, &ctx.local_db, &scan, MigrationMode::Move, true).is_err()
+            );
+        }
+        ctx.fetch_local_db();
+        assert_eq!(ctx.local_db.valid().count(), 1);
+        assert!(get_trashed_mods(&ctx.config).unwrap().is_empty());
+        for candidate in scan.mods.iter() {
+            assert!(PathBuf::from(&candidate.source_path).is_dir());
+        }
+    }
+
+    #[test]
+    fn test_adopt_mods_target_taken() {
+        let mut ctx = TestContext::new();
+        let scan = setup(&mut ctx);
+        let taken = ctx.owml_dir.join("Mods").join("Bwc9876.TimeSaver");
+        fs::create_dir_all(&taken).unwrap();
+        fs::write(taken.join("notes.txt"), "Not a mod").unwrap();
+        assert!(adopt_mods(&ctx.config, &ctx.local_db, &scan, MigrationMode::Move, true).is_err());
+        assert!(taken.join("notes.txt").is_file());
+        assert!(!taken.join("manifest.json").exists());
+        let time_saver = scan
+            .mods
+            .iter()
+            .find(|m| m.unique_name == "Bwc9876.TimeSaver")
+            .unwrap();
+        assert!(PathBuf::from(&time_saver.source_path).is_dir());
+    }
+}
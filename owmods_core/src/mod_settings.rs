@@ -0,0 +1,674 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use typeshare::typeshare;
+
+use crate::{
+    file::{deserialize_from_json, fix_json_file, serialize_to_json},
+    mods::local::{LocalMod, ModStubConfig},
+    toggle::generate_config,
+};
+
+const DEFAULT_CONFIG_NAME: &str = "default-config.json";
+const CONFIG_NAME: &str = "config.json";
+
+/// Numbers at or above this can't all be stored exactly as an `f64`, so they aren't turned into integers
+const MAX_SAFE_INTEGER: f64 = 9007199254740992.0;
+
+/// The type of a mod setting, inferred from the mod's `default-config.json`
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModSettingType {
+    /// An on/off switch
+    Toggle,
+    /// A number that must be between a minimum and a maximum
+    Slider,
+    /// One of a set of options
+    Selector,
+    /// Free-form text
+    Text,
+    /// Any number
+    Number,
+}
+
+/// The value of a mod setting
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", content = "value")]
+pub enum ModSettingValue {
+    /// The value of a [ModSettingType::Toggle]
+    Bool(bool),
+    /// The value of a [ModSettingType::Slider] or [ModSettingType::Number]
+    Number(f64),
+    /// The value of a [ModSettingType::Selector] or [ModSettingType::Text]
+    Text(String),
+}
+
+impl ModSettingValue {
+    fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(Self::Bool(*b)),
+            Value::Number(n) => n.as_f64().map(Self::Number),
+            Value::String(s) => Some(Self::Text(s.clone())),
+            _ => None,
+        }
+    }
+
+    fn to_json(&self) -> Result<Value> {
+        Ok(match self {
+            Self::Bool(b) => Value::Bool(*b),
+            // Whole numbers are saved without a decimal point so integer settings stay integers
+            Self::Number(n) if n.fract() == 0.0 && n.abs() < MAX_SAFE_INTEGER => {
+                Value::from(*n as i64)
+            }
+            Self::Number(n) => Number::from_f64(*n)
+                .map(Value::Number)
+                .ok_or_else(|| anyhow!("{} can't be saved as a setting", n))?,
+            Self::Text(s) => Value::String(s.clone()),
+        })
+    }
+}
+
+impl std::fmt::Display for ModSettingValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Number(n) => write!(f, "{}", n),
+            Self::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// A single setting of a mod, along with its current value
+#[typeshare]
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModSetting {
+    /// The key of the setting in the mod's config
+    pub key: String,
+    /// The title the mod gives this setting, if any
+    pub title: Option<String>,
+    /// The tooltip the mod gives this setting, if any
+    pub tooltip: Option<String>,
+    /// The type of the setting
+    pub setting_type: ModSettingType,
+    /// The minimum value for sliders
+    pub min: Option<f64>,
+    /// The maximum value for sliders
+    pub max: Option<f64>,
+    /// Whether this setting only accepts whole numbers, inferred from its default value
+    pub integer: bool,
+    /// The possible values for selectors
+    pub options: Option<Vec<String>>,
+    /// The value in the mod's `default-config.json`
+    pub default_value: ModSettingValue,
+    /// The current value in the mod's `config.json`
+    pub value: ModSettingValue,
+}
+
+impl ModSetting {
+    fn from_default(key: &str, raw: &Value) -> Option<Self> {
+        let (obj, raw_value) = match raw {
+            Value::Object(obj) => (Some(obj), obj.get("value")?),
+            _ => (None, raw),
+        };
+        let default_value = ModSettingValue::from_json(raw_value)?;
+        let get_str = |field: &str| {
+            obj.and_then(|o| o.get(field))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+        let get_f64 = |field: &str| obj.and_then(|o| o.get(field)).and_then(|v| v.as_f64());
+        let setting_type = match get_str("type").as_deref() {
+            Some("toggle") => ModSettingType::Toggle,
+            Some("slider") => ModSettingType::Slider,
+            Some("selector") => ModSettingType::Selector,
+            Some("text") | Some("input") => ModSettingType::Text,
+            Some("number") => ModSettingType::Number,
+            // Separators and types we don't know about can't be edited
+            Some(_) => return None,
+            None => match default_value {
+                ModSettingValue::Bool(_) => ModSettingType::Toggle,
+                ModSettingValue::Number(_) => ModSettingType::Number,
+                ModSettingValue::Text(_) => ModSettingType::Text,
+            },
+        };
+        let options = obj.and_then(|o| o.get("options")).and_then(|v| {
+            v.as_array().map(|a| {
+                a.iter()
+                    .filter_map(|o| o.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+        });
+        let setting = Self {
+            key: key.to_string(),
+            title: get_str("title"),
+            tooltip: get_str("tooltip"),
+            setting_type,
+            min: get_f64("min"),
+            max: get_f64("max"),
+            integer: raw_value.is_i64() || raw_value.is_u64(),
+            options,
+            value: default_value.clone(),
+            default_value,
+        };
+        setting.check_value(&setting.default_value).ok()?;
+        Some(setting)
+    }
+
+    /// Check if the given value is valid for this setting
+    ///
+    /// ## Errors
+    ///
+    /// If the value is the wrong type, is out of range / not one of the options,
+    /// or isn't a whole number for an integer setting
+    ///
+    pub fn check_value(&self, value: &ModSettingValue) -> Result<()> {
+        if matches!(value, ModSettingValue::Number(n) if !n.is_finite()) {
+            return Err(anyhow!("{} must be a finite number", self.key));
+        }
+        if self.integer && matches!(value, ModSettingValue::Number(n) if n.fract() != 0.0) {
+            return Err(anyhow!("{} must be a whole number", self.key));
+        }
+        match (self.setting_type, value) {
+            (ModSettingType::Toggle, ModSettingValue::Bool(_)) => Ok(()),
+            (ModSettingType::Number, ModSettingValue::Number(_)) => Ok(()),
+            (ModSettingType::Text, ModSettingValue::Text(_)) => Ok(()),
+            (ModSettingType::Slider, ModSettingValue::Number(n)) => {
+                let too_low = self.min.is_some_and(|min| *n < min);
+                let too_high = self.max.is_some_and(|max| *n > max);
+                if too_low || too_high {
+                    Err(anyhow!(
+                        "{} must be between {} and {}",
+                        self.key,
+                        self.min.map_or("-∞".to_string(), |m| m.to_string()),
+                        self.max.map_or("∞".to_string(), |m| m.to_string())
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            (ModSettingType::Selector, ModSettingValue::Text(t)) => {
+                let options = self.options.as_deref().unwrap_or_default();
+                if options.contains(t) {
+                    Ok(())
+                } else {
+                    Err(anyhow!(
+                        "{} must be one of: {}",
+                        self.key,
+                        options.join(", ")
+                    ))
+                }
+            }
+            _ => Err(anyhow!(
+                "{} is a {:?} setting, can't set it to {:?}",
+                self.key,
+                self.setting_type,
+                value
+            )),
+        }
+    }
+
+    /// Parse a value for this setting from a string (for example from the command line), and check it's valid.
+    ///
+    /// Toggles accept `true`/`false`, `on`/`off`, and `yes`/`no`.
+    ///
+    /// ## Errors
+    ///
+    /// If the text can't be parsed as the setting's type or [ModSetting::check_value] fails
+    ///
+    pub fn parse_value(&self, raw: &str) -> Result<ModSettingValue> {
+        let value = match self.setting_type {
+            ModSettingType::Toggle => match raw.trim().to_ascii_lowercase().as_str() {
+                "true" | "on" | "yes" => ModSettingValue::Bool(true),
+                "false" | "off" | "no" => ModSettingValue::Bool(false),
+                _ => return Err(anyhow!("{} must be true or false", self.key)),
+            },
+            ModSettingType::Slider | ModSettingType::Number => ModSettingValue::Number(
+                raw.trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|n| n.is_finite())
+                    .ok_or_else(|| anyhow!("{} must be a number", self.key))?,
+            ),
+            ModSettingType::Selector | ModSettingType::Text => {
+                ModSettingValue::Text(raw.to_string())
+            }
+        };
+        self.check_value(&value)?;
+        Ok(value)
+    }
+}
+
+fn read_default_settings(mod_path: &Path) -> Result<Map<String, Value>> {
+    let path = mod_path.join(DEFAULT_CONFIG_NAME);
+    if !path.is_file() {
+        return Ok(Map::new());
+    }
+    fix_json_file(&path).ok();
+    let default_config: Value = deserialize_from_json(&path)?;
+    Ok(default_config
+        .get("settings")
+        .and_then(|s| s.as_object())
+        .cloned()
+        .unwrap_or_default())
+}
+
+fn read_mod_config(mod_path: &Path) -> Result<ModStubConfig> {
+    let path = mod_path.join(CONFIG_NAME);
+    if !path.is_file() {
+        generate_config(&path)?;
+    }
+    fix_json_file(&path).ok();
+    deserialize_from_json(&path)
+}
+
+/// Get all the settings of a mod, their types are inferred from the mod's `default-config.json`.
+/// Settings that can't be edited (like separators) are skipped.
+///
+/// If a setting is missing from the mod's `config.json` or has a value of the wrong type, its value will be the default value.
+///
+/// ## Returns
+///
+/// The settings sorted by their key, empty if the mod has no `default-config.json`.
+///
+/// ## Errors
+///
+/// If we can't read the mod's `default-config.json` or `config.json`
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::mod_settings::get_mod_settings;
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+/// let local_mod = local_db.get_mod("Bwc9876.TimeSaver").unwrap();
+///
+/// for setting in get_mod_settings(local_mod).unwrap() {
+///     println!("{}: {}", setting.key, setting.value);
+/// }
+/// ```
+///
+pub fn get_mod_settings(local_mod: &LocalMod) -> Result<Vec<ModSetting>> {
+    let mod_path = PathBuf::from(&local_mod.mod_path);
+    let current = read_mod_config(&mod_path)?.settings.unwrap_or_default();
+    resolve_settings(&mod_path, &current)
+}
+
+/// Resolve the settings of the mod at `mod_path` using the given map of current values,
+/// used for both the mod's `config.json` and snapshots of it.
+pub(crate) fn resolve_settings(
+    mod_path: &Path,
+    current: &Map<String, Value>,
+) -> Result<Vec<ModSetting>> {
+    let defaults = read_default_settings(mod_path)?;
+    Ok(defaults
+        .iter()
+        .filter_map(|(key, raw)| {
+            let mut setting = ModSetting::from_default(key, raw)?;
+            let current_value = current
+                .get(key)
+                .map(|v| v.get("value").unwrap_or(v))
+                .and_then(ModSettingValue::from_json)
+                .filter(|v| setting.check_value(v).is_ok());
+            if let Some(current_value) = current_value {
+                setting.value = current_value;
+            }
+            Some(setting)
+        })
+        .collect())
+}
+
+/// Get the settings of a mod that aren't set to the value in its `default-config.json`, see [get_mod_settings].
+///
+/// ## Errors
+///
+/// If we can't read the mod's `default-config.json` or `config.json`
+///
+pub fn get_changed_settings(local_mod: &LocalMod) -> Result<Vec<ModSetting>> {
+    Ok(get_mod_settings(local_mod)?
+        .into_iter()
+        .filter(|s| s.value != s.default_value)
+        .collect())
+}
+
+/// Get a single setting of a mod by its key, see [get_mod_settings].
+///
+/// ## Errors
+///
+/// If we can't read the mod's config files or the mod doesn't have a setting with that key
+///
+pub fn get_mod_setting(local_mod: &LocalMod, key: &str) -> Result<ModSetting> {
+    get_mod_settings(local_mod)?
+        .into_iter()
+        .find(|s| s.key == key)
+        .ok_or_else(|| {
+            anyhow!(
+                "{} doesn't have a setting called {}",
+                local_mod.manifest.name,
+                key
+            )
+        })
+}
+
+fn write_values(local_mod: &LocalMod, values: Vec<(String, ModSettingValue)>) -> Result<()> {
+    let mod_path = PathBuf::from(&local_mod.mod_path);
+    let mut config = read_mod_config(&mod_path)?;
+    let mut settings = config.settings.unwrap_or_default();
+    for (key, value) in values {
+        match settings.get_mut(&key) {
+            // OWML may store the setting with its metadata, only touch the value
+            Some(Value::Object(obj)) if obj.contains_key("value") => {
+                obj.insert("value".to_string(), value.to_json()?);
+            }
+            _ => {
+                settings.insert(key, value.to_json()?);
+            }
+        }
+    }
+    config.settings = Some(settings);
+    serialize_to_json(&config, &mod_path.join(CONFIG_NAME), false)
+}
+
+/// Set a setting of a mod and save it to the mod's `config.json`.
+/// The value is checked against the setting's type first.
+///
+/// ## Errors
+///
+/// If the mod doesn't have this setting, the value is invalid for it, or we can't read/save the mod's config files.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::mod_settings::{set_mod_setting, ModSettingValue};
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+/// let local_mod = local_db.get_mod("Bwc9876.TimeSaver").unwrap();
+///
+/// set_mod_setting(local_mod, "Skip Splash", ModSettingValue::Bool(false)).unwrap();
+/// ```
+///
+pub fn set_mod_setting(local_mod: &LocalMod, key: &str, value: ModSettingValue) -> Result<()> {
+    let setting = get_mod_setting(local_mod, key)?;
+    setting.check_value(&value)?;
+    write_values(local_mod, vec![(setting.key, value)])
+}
+
+/// Reset a setting of a mod to the value in its `default-config.json`.
+/// If no key is given, all settings are reset.
+///
+/// ## Errors
+///
+/// If the mod doesn't have the given setting or we can't read/save the mod's config files.
+///
+pub fn reset_mod_settings(local_mod: &LocalMod, key: Option<&str>) -> Result<()> {
+    let keys = match key {
+        Some(key) => vec![key.to_string()],
+        None => get_mod_settings(local_mod)?
+            .into_iter()
+            .map(|s| s.key)
+            .collect(),
+    };
+    reset_selected_mod_settings(local_mod, &keys)
+}
+
+/// Reset the given settings of a mod to the values in its `default-config.json`.
+///
+/// ## Errors
+///
+/// If the mod doesn't have one of the given settings or we can't read/save the mod's config files.
+///
+pub fn reset_selected_mod_settings(local_mod: &LocalMod, keys: &[String]) -> Result<()> {
+    let values = keys
+        .iter()
+        .map(|key| get_mod_setting(local_mod, key).map(|s| (s.key, s.default_value)))
+        .collect::<Result<Vec<_>>>()?;
+    write_values(local_mod, values)
+}
+
+/// Reset the whole `config.json` of a mod, this removes all settings (including ones the manager doesn't understand)
+/// so OWML uses the values in `default-config.json`. Unlike deleting the file, whether the mod is enabled is kept.
+///
+/// ## Errors
+///
+/// If we can't read/save the mod's `config.json`
+///
+pub fn reset_mod_config(local_mod: &LocalMod) -> Result<()> {
+    let mod_path = PathBuf::from(&local_mod.mod_path);
+    let mut config = read_mod_config(&mod_path)?;
+    config.settings = None;
+    serialize_to_json(&config, &mod_path.join(CONFIG_NAME), false)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs::write;
+
+    use crate::test_utils::TestContext;
+
+    use super::*;
+
+    const TEST_DEFAULT_CONFIG: &str = r#"{
+        "enabled": true,
+        "settings": {
+            "Plain Toggle": true,
+            "Slider": { "type": "slider", "value": 0.5, "min": 0, "max": 1, "title": "A Slider" },
+            "Selector": { "type": "selector", "value": "A", "options": ["A", "B"] },
+            "Text": { "type": "text", "value": "Hello" },
+            "Separator": { "type": "separator" }
+        }
+    }"#;
+
+    fn setup(ctx: &mut TestContext) -> LocalMod {
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        write(
+            PathBuf::from(&local_mod.mod_path).join(DEFAULT_CONFIG_NAME),
+            TEST_DEFAULT_CONFIG,
+        )
+        .unwrap();
+        local_mod
+    }
+
+    #[test]
+    fn test_get_mod_settings_infer() {
+        let mut ctx = TestContext::new();
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let settings = get_mod_settings(&local_mod).unwrap();
+        assert_eq!(settings.len(), 5);
+        assert!(settings
+            .iter()
+            .all(|s| s.setting_type == ModSettingType::Toggle));
+        let skip_splash = settings.iter().find(|s| s.key == "Skip Splash").unwrap();
+        assert_eq!(skip_splash.value, ModSettingValue::Bool(true));
+    }
+
+    #[test]
+    fn test_get_mod_settings_types() {
+        let mut ctx = TestContext::new();
+        let local_mod = setup(&mut ctx);
+        let settings = get_mod_settings(&local_mod).unwrap();
+        assert_eq!(settings.len(), 4);
+        let slider = get_mod_setting(&local_mod, "Slider").unwrap();
+        assert_eq!(slider.setting_type, ModSettingType::Slider);
+        assert_eq!(slider.title.as_deref(), Some("A Slider"));
+        assert_eq!(slider.max, Some(1.0));
+        let selector = get_mod_setting(&local_mod, "Selector").unwrap();
+        assert_eq!(selector.setting_type, ModSettingType::Selector);
+        assert_eq!(selector.options.unwrap(), vec!["A", "B"]);
+        assert!(get_mod_setting(&local_mod, "Separator").is_err());
+    }
+
+    #[test]
+    fn test_set_mod_setting() {
+        let mut ctx = TestContext::new();
+        let local_mod = setup(&mut ctx);
+        set_mod_setting(&local_mod, "Slider", ModSettingValue::Number(0.25)).unwrap();
+        set_mod_setting(
+            &local_mod,
+            "Selector",
+            ModSettingValue::Text("B".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            get_mod_setting(&local_mod, "Slider").unwrap().value,
+            ModSettingValue::Number(0.25)
+        );
+        assert_eq!(
+            get_mod_setting(&local_mod, "Selector").unwrap().value,
+            ModSettingValue::Text("B".to_string())
+        );
+        // Enabled state shouldn't be touched
+        ctx.fetch_local_db();
+        assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").unwrap().enabled);
+    }
+
+    #[test]
+    fn test_set_mod_setting_invalid() {
+        let mut ctx = TestContext::new();
+        let local_mod = setup(&mut ctx);
+        assert!(set_mod_setting(&local_mod, "Slider", ModSettingValue::Number(2.0)).is_err());
+        assert!(set_mod_setting(&local_mod, "Slider", ModSettingValue::Bool(true)).is_err());
+        assert!(set_mod_setting(
+            &local_mod,
+            "Selector",
+            ModSettingValue::Text("C".to_string())
+        )
+        .is_err());
+        assert!(set_mod_setting(&local_mod, "Missing", ModSettingValue::Bool(true)).is_err());
+    }
+
+    #[test]
+    fn test_set_mod_setting_keeps_metadata() {
+        let mut ctx = TestContext::new();
+        let local_mod = setup(&mut ctx);
+        let config_path = PathBuf::from(&local_mod.mod_path).join(CONFIG_NAME);
+        write(
+            &config_path,
+            r#"{"enabled": false, "settings": {"Text": {"type": "text", "value": "Hi"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            get_mod_setting(&local_mod, "Text").unwrap().value,
+            ModSettingValue::Text("Hi".to_string())
+        );
+        set_mod_setting(&local_mod, "Text", ModSettingValue::Text("Bye".to_string())).unwrap();
+        let config: ModStubConfig = deserialize_from_json(&config_path).unwrap();
+        assert!(!config.enabled);
+        let text = config.settings.unwrap().get("Text").unwrap().clone();
+        assert_eq!(text.get("type").unwrap(), "text");
+        assert_eq!(text.get("value").unwrap(), "Bye");
+    }
+
+    #[test]
+    fn test_reset_mod_settings() {
+        let mut ctx = TestContext::new();
+        let local_mod = setup(&mut ctx);
+        set_mod_setting(&local_mod, "Plain Toggle", ModSettingValue::Bool(false)).unwrap();
+        set_mod_setting(&local_mod, "Text", ModSettingValue::Text("Bye".to_string())).unwrap();
+        assert_eq!(get_changed_settings(&local_mod).unwrap().len(), 2);
+        reset_selected_mod_settings(&local_mod, &["Text".to_string()]).unwrap();
+        assert_eq!(
+            get_mod_setting(&local_mod, "Text").unwrap().value,
+            ModSettingValue::Text("Hello".to_string())
+        );
+        let changed = get_changed_settings(&local_mod).unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].key, "Plain Toggle");
+        assert!(reset_selected_mod_settings(&local_mod, &["Missing".to_string()]).is_err());
+        reset_mod_settings(&local_mod, None).unwrap();
+        assert!(get_changed_settings(&local_mod).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reset_mod_config() {
+        let mut ctx = TestContext::new();
+        let local_mod = setup(&mut ctx);
+        let config_path = PathBuf::from(&local_mod.mod_path).join(CONFIG_NAME);
+        write(
+            &config_path,
+            r#"{"enabled": false, "settings": {"Text": "Hi", "Unknown": 5}}"#,
+        )
+        .unwrap();
+        reset_mod_config(&local_mod).unwrap();
+        let config: ModStubConfig = deserialize_from_json(&config_path).unwrap();
+        assert!(!config.enabled);
+        assert!(config.settings.is_none());
+        assert!(get_changed_settings(&local_mod).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_value() {
+        let mut ctx = TestContext::new();
+        let local_mod = setup(&mut ctx);
+        let toggle = get_mod_setting(&local_mod, "Plain Toggle").unwrap();
+        assert_eq!(
+            toggle.parse_value("off").unwrap(),
+            ModSettingValue::Bool(false)
+        );
+        assert!(toggle.parse_value("maybe").is_err());
+        let slider = get_mod_setting(&local_mod, "Slider").unwrap();
+        assert_eq!(
+            slider.parse_value("1").unwrap(),
+            ModSettingValue::Number(1.0)
+        );
+        assert!(slider.parse_value("-1").is_err());
+        assert!(slider.parse_value("abc").is_err());
+        for raw in ["NaN", "inf", "-inf"] {
+            assert!(slider.parse_value(raw).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_value_integer() {
+        let mut ctx = TestContext::new();
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        write(
+            PathBuf::from(&local_mod.mod_path).join(DEFAULT_CONFIG_NAME),
+            r#"{"settings": {"Count": 3, "Scale": 1.5}}"#,
+        )
+        .unwrap();
+        let count = get_mod_setting(&local_mod, "Count").unwrap();
+        assert!(count.integer);
+        assert_eq!(
+            count.parse_value("4").unwrap(),
+            ModSettingValue::Number(4.0)
+        );
+        assert!(count.parse_value("3.5").is_err());
+        let scale = get_mod_setting(&local_mod, "Scale").unwrap();
+        assert!(!scale.integer);
+        assert_eq!(
+            scale.parse_value("3.5").unwrap(),
+            ModSettingValue::Number(3.5)
+        );
+    }
+
+    #[test]
+    fn test_set_mod_setting_numbers() {
+        let mut ctx = TestContext::new();
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let mod_path = PathBuf::from(&local_mod.mod_path);
+        write(
+            mod_path.join(DEFAULT_CONFIG_NAME),
+            r#"{"settings": {"Count": 3, "Scale": 1.5}}"#,
+        )
+        .unwrap();
+        set_mod_setting(&local_mod, "Count", ModSettingValue::Number(5.0)).unwrap();
+        set_mod_setting(&local_mod, "Scale", ModSettingValue::Number(0.75)).unwrap();
+        assert!(set_mod_setting(&local_mod, "Count", ModSettingValue::Number(f64::NAN)).is_err());
+        assert!(set_mod_setting(&local_mod, "Count", ModSettingValue::Number(3.5)).is_err());
+        let config: ModStubConfig = deserialize_from_json(&mod_path.join(CONFIG_NAME)).unwrap();
+        let settings = config.settings.unwrap();
+        assert_eq!(settings.get("Count").unwrap(), &Value::from(5));
+        assert!(settings.get("Count").unwrap().is_i64());
+        assert_eq!(settings.get("Scale").unwrap(), &Value::from(0.75));
+    }
+}
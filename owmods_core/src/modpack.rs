@@ -0,0 +1,444 @@
+use std::{
+    fs::File,
+    io::{copy, BufReader, BufWriter, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use glob::glob;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tempfile::TempDir;
+use typeshare::typeshare;
+use zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    config::Config,
+    constants::INSTALLED_FILES_NAME,
+    db::LocalDatabase,
+    download::{install_mod_from_zip, install_owml_from_zip},
+    file::{
+        check_file_matches_paths, check_unique_name, deserialize_from_json, fix_json,
+        serialize_to_json,
+    },
+    integrity::to_record_path,
+    mods::local::{get_paths_to_preserve, LocalMod, ModStubConfig},
+    snapshots::{try_take_snapshot, SnapshotReason},
+};
+
+/// The name of the manifest file at the root of a modpack archive
+pub const MODPACK_MANIFEST_NAME: &str = "modpack.json";
+
+const MODS_FOLDER: &str = "mods";
+const OWML_ARCHIVE_NAME: &str = "OWML.zip";
+
+/// A mod included in a modpack
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModpackMod {
+    /// The unique name of the mod
+    pub unique_name: String,
+    /// The name of the mod
+    pub name: String,
+    /// The version of the mod that's in the modpack
+    pub version: String,
+    /// Whether the mod should be enabled after importing
+    pub enabled: bool,
+    /// The settings of the mod, these are written to the mod's `config.json` after importing
+    #[typeshare(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Map<String, Value>>,
+}
+
+/// The manifest of a modpack, this is stored at the root of the archive as `modpack.json`.
+/// Each mod's files are stored as `mods/{unique_name}.zip`, and OWML (if included) as `OWML.zip`.
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ModpackManifest {
+    /// The mods in the modpack
+    pub mods: Vec<ModpackMod>,
+    /// The version of OWML in the modpack, [None] if OWML isn't included
+    pub owml_version: Option<String>,
+}
+
+fn mod_archive_name(unique_name: &str) -> String {
+    format!("{}/{}.zip", MODS_FOLDER, unique_name)
+}
+
+fn zip_folder(folder: &Path, out_path: &Path, exclude_paths: &[PathBuf]) -> Result<()> {
+    let mut files: Vec<(String, PathBuf)> = vec![];
+    for glob_match in glob(folder.join("**").join("*").to_str().unwrap())? {
+        let path = glob_match?;
+        if !path.is_file() {
+            continue;
+        }
+        let rel_path = path.strip_prefix(folder)?;
+        if check_file_matches_paths(rel_path, exclude_paths) {
+            continue;
+        }
+        files.push((to_record_path(rel_path), path));
+    }
+    // The manifest needs to come first so it's found before any manifests in subfolders
+    files.sort_by_key(|(name, _)| (name != "manifest.json", name.clone()));
+
+    let mut writer = ZipWriter::new(BufWriter::new(File::create(out_path)?));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, path) in files {
+        writer.start_file(name, options)?;
+        copy(&mut BufReader::new(File::open(path)?), &mut writer)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+fn add_archive(writer: &mut ZipWriter<BufWriter<File>>, name: &str, path: &Path) -> Result<()> {
+    // The archive is already compressed, compressing it again would just waste time
+    let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+    writer.start_file(name, options)?;
+    copy(&mut BufReader::new(File::open(path)?), writer)?;
+    Ok(())
+}
+
+/// Export installed mods, their settings, and optionally OWML to a modpack archive that can be imported without an internet connection.
+///
+/// Files the mods want to preserve (like `config.json` and `save.json`) aren't included, the settings of each mod are stored in the manifest instead.
+///
+/// ## Returns
+///
+/// The manifest of the new modpack
+///
+/// ## Errors
+///
+/// If we can't read the files of any mod / OWML or write the archive
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::modpack::export_modpack;
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// let manifest = export_modpack(&config, &local_db, &"my_pack.zip".into(), false, true).unwrap();
+///
+/// println!("Exported {} mods", manifest.mods.len());
+/// ```
+///
+pub fn export_modpack(
+    config: &Config,
+    local_db: &LocalDatabase,
+    out_path: &PathBuf,
+    include_disabled: bool,
+    include_owml: bool,
+) -> Result<ModpackManifest> {
+    let temp_dir = TempDir::new()?;
+    let mut manifest = ModpackManifest::default();
+    let mut writer = ZipWriter::new(BufWriter::new(File::create(out_path)?));
+
+    let mut mods: Vec<&LocalMod> = local_db
+        .valid()
+        .filter(|m| include_disabled || m.enabled)
+        .collect();
+    mods.sort_by(|a, b| a.manifest.unique_name.cmp(&b.manifest.unique_name));
+
+    for (i, local_mod) in mods.into_iter().enumerate() {
+        let unique_name = &local_mod.manifest.unique_name;
+        info!("Packing {}", local_mod.manifest.name);
+        let mod_path = PathBuf::from(&local_mod.mod_path);
+        let archive_path = temp_dir.path().join(format!("{}.zip", i));
+        let mut exclude_paths = get_paths_to_preserve(Some(local_mod));
+        exclude_paths.push(PathBuf::from(INSTALLED_FILES_NAME));
+        zip_folder(&mod_path, &archive_path, &exclude_paths)?;
+        add_archive(&mut writer, &mod_archive_name(unique_name), &archive_path)?;
+
+        let settings = read_mod_settings(&mod_path)?;
+        manifest.mods.push(ModpackMod {
+            unique_name: unique_name.clone(),
+            name: local_mod.manifest.name.clone(),
+            version: local_mod.manifest.version.clone(),
+            enabled: local_mod.enabled,
+            settings,
+        });
+    }
+
+    if include_owml {
+        let owml = LocalDatabase::get_owml(&config.owml_path)
+            .ok_or_else(|| anyhow!("Couldn't find OWML at {}", config.owml_path))?;
+        info!("Packing OWML");
+        let archive_path = temp_dir.path().join(OWML_ARCHIVE_NAME);
+        // Mods are packed separately, and the config has paths specific to this computer
+        let exclude_paths = vec![
+            PathBuf::from("Mods"),
+            PathBuf::from("Logs"),
+            PathBuf::from("OWML.Config.json"),
+        ];
+        zip_folder(
+            &PathBuf::from(&config.owml_path),
+            &archive_path,
+            &exclude_paths,
+        )?;
+        add_archive(&mut writer, OWML_ARCHIVE_NAME, &archive_path)?;
+        manifest.owml_version = Some(owml.manifest.version);
+    }
+
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    writer.start_file(MODPACK_MANIFEST_NAME, options)?;
+    serde_json::to_writer_pretty(&mut writer, &manifest)?;
+    writer.finish()?;
+    temp_dir.close()?;
+    Ok(manifest)
+}
+
+fn read_mod_settings(mod_path: &Path) -> Result<Option<Map<String, Value>>> {
+    let config_path = mod_path.join("config.json");
+    if config_path.is_file() {
+        let config: ModStubConfig = deserialize_from_json(&config_path)?;
+        Ok(config.settings)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Read the manifest of a modpack archive without importing it
+///
+/// ## Errors
+///
+/// If the file isn't a modpack archive or the manifest is invalid
+///
+pub fn read_modpack_manifest(path: &PathBuf) -> Result<ModpackManifest> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let mut file = archive
+        .by_name(MODPACK_MANIFEST_NAME)
+        .map_err(|_| anyhow!("{} isn't a modpack", path.to_str().unwrap_or("File")))?;
+    let mut buf = String::new();
+    file.read_to_string(&mut buf)?;
+    Ok(serde_json::from_str(&fix_json(&buf))?)
+}
+
+/// Import a modpack archive made by [export_modpack].
+///
+/// This doesn't need an internet connection, each mod is installed with [install_mod_from_zip].
+/// Mods that are already installed at the same version aren't reinstalled, but still get their enabled state and settings from the modpack
+/// (a snapshot of their settings is taken first).
+/// Mods not in the modpack aren't touched.
+///
+/// ## Returns
+///
+/// The manifest of the imported modpack
+///
+/// ## Errors
+///
+/// If the archive is invalid, any of the mods has an invalid unique name, or we can't install any of the mods / OWML
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::modpack::import_modpack;
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// let manifest = import_modpack(&config, &local_db, &"my_pack.zip".into(), false).unwrap();
+///
+/// println!("Imported {} mods", manifest.mods.len());
+/// ```
+///
+pub fn import_modpack(
+    config: &Config,
+    local_db: &LocalDatabase,
+    path: &PathBuf,
+    install_owml: bool,
+) -> Result<ModpackManifest> {
+    let manifest = read_modpack_manifest(path)?;
+    for modpack_mod in manifest.mods.iter() {
+        check_unique_name(&modpack_mod.unique_name)?;
+    }
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let temp_dir = TempDir::new()?;
+
+    if install_owml && manifest.owml_version.is_some() {
+        info!("Installing OWML from modpack");
+        let owml_path = temp_dir.path().join(OWML_ARCHIVE_NAME);
+        copy(
+            &mut archive.by_name(OWML_ARCHIVE_NAME)?,
+            &mut File::create(&owml_path)?,
+        )?;
+        install_owml_from_zip(&owml_path, config)?;
+    }
+
+    for (i, modpack_mod) in manifest.mods.iter().enumerate() {
+        let unique_name = &modpack_mod.unique_name;
+        let local_mod = match local_db.get_mod(unique_name) {
+            Some(local_mod) if local_mod.manifest.version == modpack_mod.version => {
+                debug!(
+                    "{} is already at {}, skipping",
+                    unique_name, modpack_mod.version
+                );
+                try_take_snapshot(config, local_mod, SnapshotReason::Import);
+                local_mod.clone()
+            }
+            _ => {
+                let zip_path = temp_dir.path().join(format!("{}.zip", i));
+                copy(
+                    &mut archive.by_name(&mod_archive_name(unique_name))?,
+                    &mut File::create(&zip_path)?,
+                )?;
+                install_mod_from_zip(&zip_path, config, local_db)?
+            }
+        };
+        let mod_config = ModStubConfig {
+            enabled: modpack_mod.enabled,
+            settings: modpack_mod.settings.clone(),
+        };
+        serialize_to_json(
+            &mod_config,
+            &PathBuf::from(&local_mod.mod_path).join("config.json"),
+            false,
+        )?;
+    }
+
+    temp_dir.close()?;
+    Ok(manifest)
+}
+
+/// Check if a zip file is a modpack archive (has a `modpack.json` at its root) rather than a mod
+pub fn is_modpack(path: &PathBuf) -> bool {
+    File::open(path)
+        .ok()
+        .and_then(|f| ZipArchive::new(BufReader::new(f)).ok())
+        .is_some_and(|mut a| a.by_name(MODPACK_MANIFEST_NAME).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs::{copy as copy_file, create_dir_all};
+
+    use crate::{
+        constants::OWML_MANIFEST_NAME,
+        mod_settings::{get_mod_setting, set_mod_setting, ModSettingValue},
+        test_utils::{get_test_file, TestContext},
+        toggle::toggle_mod,
+    };
+
+    use super::*;
+
+    fn make_pack(ctx: &mut TestContext, include_owml: bool) -> PathBuf {
+        let time_saver = ctx.install_test_zip("Bwc9876.TimeSaver.zip", false);
+        ctx.install_test_zip("Bwc9876.SaveEditor.zip", true);
+        set_mod_setting(&time_saver, "Skip Splash", ModSettingValue::Bool(false)).unwrap();
+        toggle_mod("Bwc9876.SaveEditor", &ctx.local_db, false, false).unwrap();
+        ctx.fetch_local_db();
+        if include_owml {
+            copy_file(
+                get_test_file(OWML_MANIFEST_NAME),
+                ctx.owml_dir.join(OWML_MANIFEST_NAME),
+            )
+            .unwrap();
+        }
+        let pack_path = ctx.temp_dir.path().join("pack.zip");
+        export_modpack(&ctx.config, &ctx.local_db, &pack_path, true, include_owml).unwrap();
+        pack_path
+    }
+
+    #[test]
+    fn test_export_modpack() {
+        let mut ctx = TestContext::new();
+        let pack_path = make_pack(&mut ctx, false);
+        assert!(is_modpack(&pack_path));
+        assert!(!is_modpack(&get_test_file("Bwc9876.TimeSaver.zip")));
+        let manifest = read_modpack_manifest(&pack_path).unwrap();
+        assert_eq!(manifest.mods.len(), 2);
+        assert!(manifest.owml_version.is_none());
+        assert_eq!(manifest.mods[0].unique_name, "Bwc9876.SaveEditor");
+        assert!(!manifest.mods[0].enabled);
+        let time_saver = &manifest.mods[1];
+        assert!(time_saver.enabled);
+        assert_eq!(
+            time_saver.settings.as_ref().unwrap().get("Skip Splash"),
+            Some(&Value::Bool(false))
+        );
+    }
+
+    #[test]
+    fn test_import_modpack() {
+        let mut ctx = TestContext::new();
+        let pack_path = make_pack(&mut ctx, true);
+        let mut new_ctx = TestContext::new();
+        create_dir_all(&new_ctx.owml_dir).unwrap();
+        let manifest =
+            import_modpack(&new_ctx.config, &new_ctx.local_db, &pack_path, true).unwrap();
+        assert_eq!(manifest.owml_version.as_deref(), Some("2.9.0"));
+        assert!(new_ctx.owml_dir.join(OWML_MANIFEST_NAME).is_file());
+        assert!(!new_ctx
+            .owml_dir
+            .join("Mods")
+            .join("Bwc9876.TimeSaver.zip")
+            .exists());
+        new_ctx.fetch_local_db();
+        let time_saver = new_ctx.local_db.get_mod("Bwc9876.TimeSaver").unwrap();
+        assert!(time_saver.enabled);
+        assert_eq!(
+            get_mod_setting(time_saver, "Skip Splash").unwrap().value,
+            ModSettingValue::Bool(false)
+        );
+        assert!(
+            !new_ctx
+                .local_db
+                .get_mod("Bwc9876.SaveEditor")
+                .unwrap()
+                .enabled
+        );
+    }
+
+    #[test]
+    fn test_import_modpack_same_version() {
+        let mut ctx = TestContext::new();
+        let pack_path = make_pack(&mut ctx, false);
+        let time_saver = ctx.local_db.get_mod("Bwc9876.TimeSaver").unwrap();
+        set_mod_setting(time_saver, "Skip Splash", ModSettingValue::Bool(true)).unwrap();
+        import_modpack(&ctx.config, &ctx.local_db, &pack_path, false).unwrap();
+        assert_eq!(
+            get_mod_setting(time_saver, "Skip Splash").unwrap().value,
+            ModSettingValue::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_import_modpack_invalid_unique_name() {
+        let ctx = TestContext::new();
+        create_dir_all(&ctx.owml_dir).unwrap();
+        let manifest = ModpackManifest {
+            mods: vec![ModpackMod {
+                unique_name: "../Evil".to_string(),
+                name: "Evil".to_string(),
+                version: "1.0.0".to_string(),
+                enabled: true,
+                settings: None,
+            }],
+            ..Default::default()
+        };
+        let pack_path = ctx.temp_dir.path().join("evil.zip");
+        let mut zip = ZipWriter::new(File::create(&pack_path).unwrap());
+        zip.start_file(MODPACK_MANIFEST_NAME, FileOptions::default())
+            .unwrap();
+        serde_json::to_writer(&mut zip, &manifest).unwrap();
+        zip.start_file(mod_archive_name("../Evil"), FileOptions::default())
+            .unwrap();
+        copy(
+            &mut File::open(get_test_file("Bwc9876.TimeSaver.zip")).unwrap(),
+            &mut zip,
+        )
+        .unwrap();
+        zip.finish().unwrap();
+        assert!(import_modpack(&ctx.config, &ctx.local_db, &pack_path, false).is_err());
+        assert!(!ctx.owml_dir.join("Mods").exists());
+    }
+}
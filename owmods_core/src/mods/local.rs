@@ -204,7 +204,7 @@ pub struct ModWarning {
 pub struct ModStubConfig {
     /// Whether the mod is enabled
     pub enabled: bool,
-    /// The settings for the mod, this is kept in a Map because the manager doesn't touch it
+    /// The settings for the mod, this is kept in a Map because its shape depends on the mod, see [crate::mod_settings]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub settings: Option<Map<String, Value>>,
 }
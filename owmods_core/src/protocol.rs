@@ -16,6 +16,8 @@ pub enum ProtocolInstallType {
     InstallPreRelease,
     /// Install a mod from a zip file
     InstallZip,
+    /// Import a list of mods from a share code, see [crate::io::decode_share_code]
+    ImportList,
     /// Unknown install type, means the protocol link was invalid and therefore should be ignored
     Unknown,
 }
@@ -28,6 +30,7 @@ impl ProtocolInstallType {
             "install-url" => Self::InstallURL,
             "install-prerelease" => Self::InstallPreRelease,
             "install-zip" => Self::InstallZip,
+            "import" => Self::ImportList,
             _ => Self::Unknown,
         }
     }
@@ -46,6 +49,7 @@ impl ProtocolInstallType {
 /// - owmods://install-url/https://example.com/Mod.zip
 /// - owmods://install-zip//home/user/Downloads/Mod.zip
 /// - owmods://install-prerelease/Raicuparta.NomaiVR
+/// - owmods://import/1cypPtrQwN9MLycxNDU4sSy0CAA
 #[typeshare]
 #[derive(Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -122,6 +126,13 @@ mod tests {
             ProtocolInstallType::InstallPreRelease
         ));
         assert_eq!(payload.payload, "Raicuparta.NomaiVR");
+
+        let payload = ProtocolPayload::parse("owmods://import/1cypPtrQwN9MLycxNDU4sSy0CAA");
+        assert!(matches!(
+            payload.install_type,
+            ProtocolInstallType::ImportList
+        ));
+        assert_eq!(payload.payload, "1cypPtrQwN9MLycxNDU4sSy0CAA");
     }
 
     #[test]
@@ -145,5 +156,9 @@ mod tests {
         let payload = ProtocolPayload::parse("owmods://install-prerelease");
         assert!(matches!(payload.install_type, ProtocolInstallType::Unknown));
         assert_eq!(payload.payload, "");
+
+        let payload = ProtocolPayload::parse("owmods://import");
+        assert!(matches!(payload.install_type, ProtocolInstallType::Unknown));
+        assert_eq!(payload.payload, "");
     }
 }
@@ -5,14 +5,50 @@ use std::{
 
 use anyhow::Result;
 use glob::glob;
+use log::warn;
+use serde::Serialize;
+use typeshare::typeshare;
 
 use crate::{
+    config::Config,
     db::LocalDatabase,
     file::check_file_matches_paths,
     mods::local::{get_paths_to_preserve, FailedMod, LocalMod},
+    trash::trash_mod,
 };
 
-/// Uninstall a mod
+/// A dependency that was kept when recursively uninstalling a mod because other installed mods still need it
+#[typeshare]
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeptDependency {
+    /// The unique name of the dependency
+    pub unique_name: String,
+    /// The name of the dependency
+    pub name: String,
+    /// The names of the installed mods that still depend on it
+    pub needed_by: Vec<String>,
+}
+
+/// The outcome of uninstalling a mod with [remove_mod_to_trash]
+#[typeshare]
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveResult {
+    /// The unique names of the mods that were uninstalled, the mod that was asked to be removed comes first
+    pub removed: Vec<String>,
+    /// Dependencies that were left installed because other mods need them
+    pub kept: Vec<KeptDependency>,
+    /// The names of removed mods that use pre-patchers
+    /// and thus **should have a warning shown to the user telling them to check the mod's README for instructions**
+    pub show_warnings_for: Vec<String>,
+}
+
+/// Uninstall a mod, deleting its folder for good. Use [remove_mod_to_trash] to be able to restore it later.
+///
+/// If `recursive` is true the mod's dependencies (and their dependencies) are uninstalled as well,
+/// unless another installed mod that isn't being uninstalled still depends on them.
+/// Disabled mods still count as needing their dependencies.
 ///
 /// ## Returns
 ///
@@ -21,35 +57,117 @@ use crate::{
 ///
 /// ## Errors
 ///
-/// If we can't delete the mod's folder.
+/// If we can't delete the folder of the mod or any of its dependencies.
 ///
 pub fn remove_mod(
     local_mod: &LocalMod,
     db: &LocalDatabase,
     recursive: bool,
 ) -> Result<Vec<String>> {
-    let mut show_warnings_for: Vec<String> = vec![];
+    Ok(remove_mod_inner(local_mod, None, db, recursive)?.show_warnings_for)
+}
 
-    if PathBuf::from(&local_mod.mod_path).is_dir() {
-        // In case weird circular dep stuff happens, just don't delete it if it doesn't exist
-        remove_dir_all(&local_mod.mod_path)?;
-        if local_mod.uses_pre_patcher() {
-            show_warnings_for.push(local_mod.manifest.name.clone());
+/// Uninstall a mod, the mod's folder is moved to the trash so it can be restored later (see [crate::trash]).
+///
+/// Dependencies are handled the same way as [remove_mod].
+///
+/// ## Returns
+///
+/// A [RemoveResult] with the mods that were removed, the dependencies that were kept and why,
+/// and the mods that should have a pre-patcher warning shown.
+///
+/// ## Errors
+///
+/// If we can't move the folder of the mod or any of its dependencies to the trash.
+///
+pub fn remove_mod_to_trash(
+    local_mod: &LocalMod,
+    config: &Config,
+    db: &LocalDatabase,
+    recursive: bool,
+) -> Result<RemoveResult> {
+    remove_mod_inner(local_mod, Some(config), db, recursive)
+}
+
+fn remove_mod_inner(
+    local_mod: &LocalMod,
+    trash_config: Option<&Config>,
+    db: &LocalDatabase,
+    recursive: bool,
+) -> Result<RemoveResult> {
+    let mut result = RemoveResult::default();
+
+    let mut to_remove: Vec<&LocalMod> = vec![local_mod];
+
+    if recursive {
+        let mut to_check: Vec<String> = local_mod.manifest.dependencies.clone().unwrap_or_default();
+        while let Some(dep) = to_check.pop() {
+            if to_remove.iter().any(|m| m.manifest.unique_name == dep) {
+                continue;
+            }
+            if let Some(dep_mod) = db.get_mod(&dep) {
+                to_check.extend(dep_mod.manifest.dependencies.clone().unwrap_or_default());
+                to_remove.push(dep_mod);
+            } else {
+                warn!("Dependency {} Was Not Found, Ignoring.", dep);
+            }
+        }
+
+        // Keep dropping dependencies that something outside the removal set needs,
+        // keeping one might mean its own dependencies have to stay too
+        loop {
+            let needed = to_remove.iter().skip(1).find_map(|dep_mod| {
+                let needed_by = db
+                    .dependent(dep_mod)
+                    .filter(|m| {
+                        !to_remove
+                            .iter()
+                            .any(|r| r.manifest.unique_name == m.manifest.unique_name)
+                    })
+                    .map(|m| m.manifest.name.clone())
+                    .collect::<Vec<_>>();
+                (!needed_by.is_empty()).then(|| (dep_mod.manifest.unique_name.clone(), needed_by))
+            });
+            if let Some((unique_name, needed_by)) = needed {
+                let index = to_remove
+                    .iter()
+                    .position(|m| m.manifest.unique_name == unique_name)
+                    .unwrap();
+                let dep_mod = to_remove.remove(index);
+                warn!(
+                    "Not uninstalling {} as it's also needed by {}",
+                    dep_mod.manifest.name,
+                    needed_by.join(", ")
+                );
+                result.kept.push(KeptDependency {
+                    unique_name,
+                    name: dep_mod.manifest.name.clone(),
+                    needed_by,
+                });
+            } else {
+                break;
+            }
         }
     }
 
-    if recursive {
-        let empty: &Vec<String> = &vec![];
-        let deps = local_mod.manifest.dependencies.as_ref().unwrap_or(empty);
-        for dep in deps.iter() {
-            let dep = db.get_mod(dep);
-            if let Some(dep) = dep {
-                show_warnings_for.extend(remove_mod(dep, db, true)?);
+    for remove_mod in to_remove {
+        // In case weird circular dep stuff happens, just don't delete it if it doesn't exist
+        if PathBuf::from(&remove_mod.mod_path).is_dir() {
+            if let Some(config) = trash_config {
+                trash_mod(config, remove_mod)?;
+            } else {
+                remove_dir_all(&remove_mod.mod_path)?;
+            }
+            result.removed.push(remove_mod.manifest.unique_name.clone());
+            if remove_mod.uses_pre_patcher() {
+                result
+                    .show_warnings_for
+                    .push(remove_mod.manifest.name.clone());
             }
         }
     }
 
-    Ok(show_warnings_for)
+    Ok(result)
 }
 
 /// Removes a [FailedMod]
@@ -99,7 +217,10 @@ mod tests {
 
     use std::fs;
 
-    use crate::{file::create_all_parents, mods::local::UnsafeLocalMod, test_utils::TestContext};
+    use crate::{
+        file::create_all_parents, mods::local::UnsafeLocalMod, test_utils::TestContext,
+        trash::get_trashed_mods,
+    };
 
     use super::*;
 
@@ -111,6 +232,19 @@ mod tests {
         ctx.fetch_local_db();
         assert!(!ctx.get_test_path("Bwc9876.TimeSaver").is_dir());
         assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").is_none());
+        assert!(get_trashed_mods(&ctx.config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remove_mod_to_trash() {
+        let mut ctx = TestContext::new();
+        let new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        remove_mod_to_trash(&new_mod, &ctx.config, &ctx.local_db, false).unwrap();
+        ctx.fetch_local_db();
+        assert!(!ctx.get_test_path("Bwc9876.TimeSaver").is_dir());
+        assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").is_none());
+        let trashed = get_trashed_mods(&ctx.config).unwrap();
+        assert_eq!(trashed[0].unique_name, "Bwc9876.TimeSaver");
     }
 
     #[test]
@@ -127,6 +261,51 @@ mod tests {
         assert!(ctx.local_db.get_mod("Bwc9876.SaveEditor").is_none());
     }
 
+    #[test]
+    fn test_remove_mod_to_trash_recursive() {
+        let mut ctx = TestContext::new();
+        let mut new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", false);
+        ctx.install_test_zip("Bwc9876.SaveEditor.zip", true);
+        new_mod.manifest.dependencies = Some(vec!["Bwc9876.SaveEditor".to_string()]);
+        let result = remove_mod_to_trash(&new_mod, &ctx.config, &ctx.local_db, true).unwrap();
+        ctx.fetch_local_db();
+        assert_eq!(
+            result.removed,
+            vec!["Bwc9876.TimeSaver", "Bwc9876.SaveEditor"]
+        );
+        assert!(result.kept.is_empty());
+        assert!(!ctx.get_test_path("Bwc9876.TimeSaver").is_dir());
+        assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").is_none());
+        assert!(!ctx.get_test_path("Bwc9876.SaveEditor").is_dir());
+        assert!(ctx.local_db.get_mod("Bwc9876.SaveEditor").is_none());
+        assert_eq!(get_trashed_mods(&ctx.config).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_remove_mod_recursive_other_dependent() {
+        let mut ctx = TestContext::new();
+        let mut new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", false);
+        ctx.install_test_zip("Bwc9876.SaveEditor.zip", true);
+        new_mod.manifest.dependencies = Some(vec!["Bwc9876.SaveEditor".to_string()]);
+        let mut test_mod = LocalMod::get_test(0);
+        test_mod.enabled = false;
+        test_mod.manifest.dependencies = Some(vec![String::from("Bwc9876.SaveEditor")]);
+        ctx.insert_test_mod(&test_mod);
+        let result = remove_mod_to_trash(&new_mod, &ctx.config, &ctx.local_db, true).unwrap();
+        ctx.fetch_local_db();
+        assert_eq!(result.removed, vec!["Bwc9876.TimeSaver"]);
+        assert_eq!(
+            result.kept,
+            vec![KeptDependency {
+                unique_name: "Bwc9876.SaveEditor".to_string(),
+                name: "Save Editor".to_string(),
+                needed_by: vec![test_mod.manifest.name.clone()],
+            }]
+        );
+        assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").is_none());
+        assert!(ctx.local_db.get_mod("Bwc9876.SaveEditor").is_some());
+    }
+
     #[test]
     fn test_remove_mod_recursive_cyclical_deps() {
         let mut ctx = TestContext::new();
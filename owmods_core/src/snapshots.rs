@@ -0,0 +1,420 @@
+use std::{
+    fs::{read_dir, remove_file},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use typeshare::typeshare;
+
+use crate::{
+    config::Config,
+    constants::SETTINGS_SNAPSHOTS_DIR_NAME,
+    file::{deserialize_from_json, fix_json_file, serialize_to_json},
+    mod_settings::{resolve_settings, ModSetting},
+    mods::local::{LocalMod, ModStubConfig},
+};
+
+/// The max amount of snapshots to keep per mod that weren't taken manually, older ones are deleted first.
+pub const MAX_AUTO_SNAPSHOTS: usize = 10;
+
+/// Why a snapshot was taken
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotReason {
+    /// The user asked for it, these are never deleted automatically
+    Manual,
+    /// Before the mod was updated
+    Update,
+    /// Before the mod was enabled or disabled
+    Toggle,
+    /// Before the mod's settings were reset
+    Reset,
+    /// Before another snapshot was restored
+    Restore,
+    /// Before a modpack was imported
+    Import,
+}
+
+/// A copy of a mod's `config.json` at some point in time
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsSnapshot {
+    /// The ID of the snapshot, this is the time it was taken in milliseconds since the Unix epoch
+    pub id: String,
+    /// The unique name of the mod this snapshot is of
+    pub unique_name: String,
+    /// Why the snapshot was taken
+    pub reason: SnapshotReason,
+    /// Whether the mod was enabled
+    pub enabled: bool,
+    /// The settings of the mod, kept as-is
+    #[typeshare(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Map<String, Value>>,
+}
+
+impl SettingsSnapshot {
+    /// Get the folder the snapshots of a mod are stored in, this is next to the config's `settings.json`
+    /// so snapshots aren't touched when the mod's folder is updated or removed.
+    pub fn dir(config: &Config, unique_name: &str) -> PathBuf {
+        config
+            .path
+            .with_file_name(SETTINGS_SNAPSHOTS_DIR_NAME)
+            .join(unique_name)
+    }
+
+    fn path(&self, config: &Config) -> PathBuf {
+        Self::dir(config, &self.unique_name).join(format!("{}.json", self.id))
+    }
+
+    fn sort_key(&self) -> u128 {
+        self.id.parse().unwrap_or(0)
+    }
+
+    fn same_contents(&self, other: &ModStubConfig) -> bool {
+        self.enabled == other.enabled && self.settings == other.settings
+    }
+
+    /// Get the settings in this snapshot that aren't set to the value in the mod's `default-config.json`.
+    /// See [crate::mod_settings::get_changed_settings].
+    ///
+    /// ## Errors
+    ///
+    /// If we can't read the mod's `default-config.json`
+    ///
+    pub fn changed_settings(&self, local_mod: &LocalMod) -> Result<Vec<ModSetting>> {
+        let settings = self.settings.clone().unwrap_or_default();
+        Ok(
+            resolve_settings(&PathBuf::from(&local_mod.mod_path), &settings)?
+                .into_iter()
+                .filter(|s| s.value != s.default_value)
+                .collect(),
+        )
+    }
+}
+
+fn read_current_config(local_mod: &LocalMod) -> Result<Option<ModStubConfig>> {
+    let path = Path::new(&local_mod.mod_path).join("config.json");
+    if path.is_file() {
+        fix_json_file(&path).ok();
+        Ok(Some(deserialize_from_json(&path)?))
+    } else {
+        Ok(None)
+    }
+}
+
+fn new_id(dir: &Path) -> String {
+    let mut millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    while dir.join(format!("{}.json", millis)).exists() {
+        millis += 1;
+    }
+    millis.to_string()
+}
+
+fn prune_auto_snapshots(config: &Config, unique_name: &str) -> Result<()> {
+    let auto_snapshots = get_snapshots(config, unique_name)?
+        .into_iter()
+        .filter(|s| s.reason != SnapshotReason::Manual)
+        .skip(MAX_AUTO_SNAPSHOTS);
+    for snapshot in auto_snapshots {
+        debug!(
+            "Deleting old settings snapshot {} of {}",
+            snapshot.id, unique_name
+        );
+        remove_file(snapshot.path(config))?;
+    }
+    Ok(())
+}
+
+/// Take a snapshot of a mod's current `config.json`.
+///
+/// Snapshots that aren't [SnapshotReason::Manual] are skipped if nothing changed since the last snapshot,
+/// and only the latest [MAX_AUTO_SNAPSHOTS] of them are kept.
+///
+/// ## Returns
+///
+/// The new snapshot, or [None] if the mod doesn't have a `config.json` or nothing changed.
+///
+/// ## Errors
+///
+/// If we can't read the mod's `config.json` or save the snapshot
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::snapshots::{take_snapshot, SnapshotReason};
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+/// let local_mod = local_db.get_mod("Bwc9876.TimeSaver").unwrap();
+///
+/// if let Some(snapshot) = take_snapshot(&config, local_mod, SnapshotReason::Manual).unwrap() {
+///     println!("Took snapshot {}", snapshot.id);
+/// }
+/// ```
+///
+pub fn take_snapshot(
+    config: &Config,
+    local_mod: &LocalMod,
+    reason: SnapshotReason,
+) -> Result<Option<SettingsSnapshot>> {
+    let unique_name = &local_mod.manifest.unique_name;
+    let current = match read_current_config(local_mod)? {
+        Some(current) => current,
+        None => return Ok(None),
+    };
+    if reason != SnapshotReason::Manual {
+        let latest = get_snapshots(config, unique_name)?.into_iter().next();
+        if latest.is_some_and(|l| l.same_contents(&current)) {
+            return Ok(None);
+        }
+    }
+    let dir = SettingsSnapshot::dir(config, unique_name);
+    let snapshot = SettingsSnapshot {
+        id: new_id(&dir),
+        unique_name: unique_name.clone(),
+        reason,
+        enabled: current.enabled,
+        settings: current.settings,
+    };
+    debug!(
+        "Taking settings snapshot {} of {} ({:?})",
+        snapshot.id, unique_name, reason
+    );
+    serialize_to_json(&snapshot, &snapshot.path(config), true)?;
+    prune_auto_snapshots(config, unique_name)?;
+    Ok(Some(snapshot))
+}
+
+/// Same as [take_snapshot], but only logs a warning if it fails.
+/// Used before actions that shouldn't be stopped by a snapshot failing.
+pub(crate) fn try_take_snapshot(config: &Config, local_mod: &LocalMod, reason: SnapshotReason) {
+    if let Err(why) = take_snapshot(config, local_mod, reason) {
+        warn!(
+            "Couldn't take a snapshot of the settings of {}: {:?}",
+            local_mod.manifest.name, why
+        );
+    }
+}
+
+/// Get all snapshots of a mod
+///
+/// ## Returns
+///
+/// The snapshots, newest first
+///
+/// ## Errors
+///
+/// If we can't read the snapshots folder or any snapshot in it
+///
+pub fn get_snapshots(config: &Config, unique_name: &str) -> Result<Vec<SettingsSnapshot>> {
+    let dir = SettingsSnapshot::dir(config, unique_name);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut snapshots = vec![];
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "json") {
+            snapshots.push(deserialize_from_json::<SettingsSnapshot>(&path)?);
+        }
+    }
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.sort_key()));
+    Ok(snapshots)
+}
+
+/// Get a single snapshot of a mod by its ID
+///
+/// ## Errors
+///
+/// If the snapshot doesn't exist or we can't read it
+///
+pub fn get_snapshot(config: &Config, unique_name: &str, id: &str) -> Result<SettingsSnapshot> {
+    get_snapshots(config, unique_name)?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| anyhow!("Snapshot {} of {} not found", id, unique_name))
+}
+
+/// Delete a snapshot of a mod
+///
+/// ## Errors
+///
+/// If the snapshot doesn't exist or we can't delete it
+///
+pub fn delete_snapshot(config: &Config, unique_name: &str, id: &str) -> Result<()> {
+    let snapshot = get_snapshot(config, unique_name, id)?;
+    remove_file(snapshot.path(config))?;
+    Ok(())
+}
+
+/// Restore a snapshot, this overwrites the mod's `config.json` with the contents of the snapshot.
+/// A [SnapshotReason::Restore] snapshot of the current config is taken first so this can be undone.
+///
+/// ## Errors
+///
+/// If the snapshot doesn't exist or we can't write to the mod's `config.json`
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::snapshots::{get_snapshots, restore_snapshot};
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+/// let local_mod = local_db.get_mod("Bwc9876.TimeSaver").unwrap();
+///
+/// let latest = get_snapshots(&config, "Bwc9876.TimeSaver").unwrap().remove(0);
+/// restore_snapshot(&config, local_mod, &latest.id).unwrap();
+/// ```
+///
+pub fn restore_snapshot(config: &Config, local_mod: &LocalMod, id: &str) -> Result<()> {
+    let snapshot = get_snapshot(config, &local_mod.manifest.unique_name, id)?;
+    take_snapshot(config, local_mod, SnapshotReason::Restore)?;
+    let restored = ModStubConfig {
+        enabled: snapshot.enabled,
+        settings: snapshot.settings,
+    };
+    serialize_to_json(
+        &restored,
+        &Path::new(&local_mod.mod_path).join("config.json"),
+        false,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs::write;
+
+    use crate::{
+        mod_settings::{get_mod_setting, set_mod_setting, ModSettingValue},
+        test_utils::TestContext,
+        toggle::{toggle_mod, toggle_mod_with_snapshot},
+    };
+
+    use super::*;
+
+    fn config_path(local_mod: &LocalMod) -> PathBuf {
+        PathBuf::from(&local_mod.mod_path).join("config.json")
+    }
+
+    #[test]
+    fn test_take_snapshot() {
+        let mut ctx = TestContext::new();
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let snapshot = take_snapshot(&ctx.config, &local_mod, SnapshotReason::Manual)
+            .unwrap()
+            .unwrap();
+        assert!(snapshot.enabled);
+        assert!(ctx
+            .temp_dir
+            .path()
+            .join(SETTINGS_SNAPSHOTS_DIR_NAME)
+            .is_dir());
+        // Nothing changed, so auto snapshots are skipped but manual ones aren't
+        assert!(
+            take_snapshot(&ctx.config, &local_mod, SnapshotReason::Toggle)
+                .unwrap()
+                .is_none()
+        );
+        take_snapshot(&ctx.config, &local_mod, SnapshotReason::Manual).unwrap();
+        assert_eq!(
+            get_snapshots(&ctx.config, "Bwc9876.TimeSaver")
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_toggle_takes_snapshot() {
+        let mut ctx = TestContext::new();
+        ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        toggle_mod_with_snapshot(
+            "Bwc9876.TimeSaver",
+            &ctx.config,
+            &ctx.local_db,
+            false,
+            false,
+        )
+        .unwrap();
+        let snapshots = get_snapshots(&ctx.config, "Bwc9876.TimeSaver").unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].reason, SnapshotReason::Toggle);
+        assert!(snapshots[0].enabled);
+    }
+
+    #[test]
+    fn test_update_takes_snapshot() {
+        let mut ctx = TestContext::new();
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        write(
+            config_path(&local_mod),
+            r#"{"enabled": true, "settings": {"Skip Splash": false}}"#,
+        )
+        .unwrap();
+        ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let snapshots = get_snapshots(&ctx.config, "Bwc9876.TimeSaver").unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].reason, SnapshotReason::Update);
+        assert_eq!(snapshots[0].changed_settings(&local_mod).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_restore_snapshot() {
+        let mut ctx = TestContext::new();
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let snapshot = take_snapshot(&ctx.config, &local_mod, SnapshotReason::Manual)
+            .unwrap()
+            .unwrap();
+        set_mod_setting(&local_mod, "Skip Splash", ModSettingValue::Bool(false)).unwrap();
+        toggle_mod("Bwc9876.TimeSaver", &ctx.local_db, false, false).unwrap();
+        restore_snapshot(&ctx.config, &local_mod, &snapshot.id).unwrap();
+        assert_eq!(
+            get_mod_setting(&local_mod, "Skip Splash").unwrap().value,
+            ModSettingValue::Bool(true)
+        );
+        ctx.fetch_local_db();
+        assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").unwrap().enabled);
+        let snapshots = get_snapshots(&ctx.config, "Bwc9876.TimeSaver").unwrap();
+        // Manual, then the restore (a plain toggle doesn't take one)
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].reason, SnapshotReason::Restore);
+        assert!(!snapshots[0].enabled);
+    }
+
+    #[test]
+    fn test_prune_auto_snapshots() {
+        let mut ctx = TestContext::new();
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let manual = take_snapshot(&ctx.config, &local_mod, SnapshotReason::Manual)
+            .unwrap()
+            .unwrap();
+        for i in 0..(MAX_AUTO_SNAPSHOTS + 3) {
+            write(
+                config_path(&local_mod),
+                format!(r#"{{"enabled": true, "settings": {{"Count": {}}}}}"#, i),
+            )
+            .unwrap();
+            take_snapshot(&ctx.config, &local_mod, SnapshotReason::Toggle).unwrap();
+        }
+        let snapshots = get_snapshots(&ctx.config, "Bwc9876.TimeSaver").unwrap();
+        assert_eq!(snapshots.len(), MAX_AUTO_SNAPSHOTS + 1);
+        assert!(snapshots.iter().any(|s| s.id == manual.id));
+        delete_snapshot(&ctx.config, "Bwc9876.TimeSaver", &manual.id).unwrap();
+        assert!(get_snapshot(&ctx.config, "Bwc9876.TimeSaver", &manual.id).is_err());
+    }
+}
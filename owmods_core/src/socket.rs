@@ -1,15 +1,29 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
 use anyhow::{anyhow, Result};
 use log::{error, info, warn};
-use serde::{Deserialize, Serialize};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use tokio::{
-    io::{AsyncBufReadExt, BufReader},
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     net::{TcpListener, TcpStream},
     sync::mpsc,
+    time::timeout,
 };
 use typeshare::typeshare;
 
-use crate::search::Searchable;
+use crate::{
+    log_queue::{LogQueue, LogQueueMetrics, LogQueueSender},
+    search::Searchable,
+};
 
 /// A channel to send logs to the server
 pub type LogServerSender = mpsc::Sender<SocketMessage>;
@@ -49,6 +63,163 @@ impl SocketMessageType {
             _ => Err(anyhow!("Invalid Variant!")),
         }
     }
+
+    /// How severe this type of message is, higher is more severe.
+    /// Debug messages are the least severe, then regular messages, warnings, errors, and finally fatal errors.
+    pub fn severity(&self) -> u8 {
+        match self {
+            Self::Debug => 0,
+            Self::Message | Self::Info | Self::Success | Self::Quit => 1,
+            Self::Warning => 2,
+            Self::Error => 3,
+            Self::Fatal => 4,
+        }
+    }
+}
+
+/// A set of conditions a [SocketMessage] can match, used to build a [LogFilter].
+/// A message matches the rule only if it matches **every** condition that's set, conditions that aren't set match anything.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilterRule {
+    /// The message has to be one of these types
+    pub message_types: Vec<SocketMessageType>,
+    /// The message has to be at least this severe, see [SocketMessageType::severity]
+    pub min_level: Option<SocketMessageType>,
+    /// The sender name has to match one of these (glob patterns, compiled to regexes)
+    pub sender_names: Vec<Regex>,
+    /// The sender type has to be one of these (case-insensitive)
+    pub sender_types: Vec<String>,
+    /// The message text has to match this regex
+    pub message_regex: Option<Regex>,
+}
+
+impl LogFilterRule {
+    /// Only match messages of the given types
+    pub fn with_types(mut self, message_types: &[SocketMessageType]) -> Self {
+        self.message_types.extend_from_slice(message_types);
+        self
+    }
+
+    /// Only match messages at least as severe as the given type
+    pub fn with_min_level(mut self, level: SocketMessageType) -> Self {
+        self.min_level = Some(level);
+        self
+    }
+
+    /// Only match messages from senders whose name matches the given glob pattern (`*` and `?`, case-insensitive).
+    /// Calling this multiple times matches any of the patterns.
+    ///
+    /// ## Errors
+    ///
+    /// If the pattern can't be turned into a regex
+    ///
+    pub fn with_sender_name(mut self, pattern: &str) -> Result<Self> {
+        let pattern = regex::escape(pattern)
+            .replace("\\*", ".*")
+            .replace("\\?", ".");
+        self.sender_names
+            .push(Regex::new(&format!("(?i)^{pattern}$"))?);
+        Ok(self)
+    }
+
+    /// Only match messages with the given sender type (case-insensitive).
+    /// Calling this multiple times matches any of the types.
+    pub fn with_sender_type(mut self, sender_type: &str) -> Self {
+        self.sender_types.push(sender_type.to_string());
+        self
+    }
+
+    /// Only match messages whose text matches the given regex
+    ///
+    /// ## Errors
+    ///
+    /// If the regex is invalid
+    ///
+    pub fn with_message_regex(mut self, regex: &str) -> Result<Self> {
+        self.message_regex = Some(Regex::new(regex)?);
+        Ok(self)
+    }
+
+    /// Check if a message matches every condition of this rule
+    pub fn matches(&self, message: &SocketMessage) -> bool {
+        let sender_name = message.sender_name.as_deref().unwrap_or("");
+        let sender_type = message.sender_type.as_deref().unwrap_or("");
+        (self.message_types.is_empty() || self.message_types.contains(&message.message_type))
+            && self
+                .min_level
+                .as_ref()
+                .is_none_or(|l| message.message_type.severity() >= l.severity())
+            && (self.sender_names.is_empty()
+                || self.sender_names.iter().any(|r| r.is_match(sender_name)))
+            && (self.sender_types.is_empty()
+                || self
+                    .sender_types
+                    .iter()
+                    .any(|t| t.eq_ignore_ascii_case(sender_type)))
+            && self
+                .message_regex
+                .as_ref()
+                .is_none_or(|r| r.is_match(&message.message))
+    }
+}
+
+/// A filter for messages coming from the game, made up of include and exclude rules.
+/// A message passes the filter if it matches **any** include rule (or there are none) and **no** exclude rules.
+///
+/// ## Examples
+///
+/// ```
+/// use owmods_core::socket::{LogFilter, LogFilterRule, SocketMessage, SocketMessageType};
+///
+/// // Warnings and errors from New Horizons, but not ones about missing textures
+/// let filter = LogFilter::default()
+///     .include(
+///         LogFilterRule::default()
+///             .with_min_level(SocketMessageType::Warning)
+///             .with_sender_name("NewHorizons")
+///             .unwrap(),
+///     )
+///     .exclude(LogFilterRule::default().with_message_regex("(?i)texture").unwrap());
+///
+/// let mut message = SocketMessage::make_internal("Couldn't load planet", SocketMessageType::Error);
+/// message.sender_name = Some("NewHorizons".to_string());
+/// assert!(filter.matches(&message));
+///
+/// message.message = "Couldn't load texture".to_string();
+/// assert!(!filter.matches(&message));
+/// ```
+///
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Messages have to match at least one of these rules, if there are none every message is included
+    pub include: Vec<LogFilterRule>,
+    /// Messages that match any of these rules are dropped
+    pub exclude: Vec<LogFilterRule>,
+}
+
+impl LogFilter {
+    /// Add an include rule
+    pub fn include(mut self, rule: LogFilterRule) -> Self {
+        self.include.push(rule);
+        self
+    }
+
+    /// Add an exclude rule
+    pub fn exclude(mut self, rule: LogFilterRule) -> Self {
+        self.exclude.push(rule);
+        self
+    }
+
+    /// Whether this filter lets every message through
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Check if a message passes this filter
+    pub fn matches(&self, message: &SocketMessage) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|r| r.matches(message)))
+            && !self.exclude.iter().any(|r| r.matches(message))
+    }
 }
 
 /// Represents a message sent from the game
@@ -66,6 +237,50 @@ pub struct SocketMessage {
     /// Note that the message sent calls this `type` so we have to alias it because type is a reserved keyword
     #[serde(alias = "type")]
     pub message_type: SocketMessageType,
+    /// The id of the connection this message came from, set by the [LogServer] so messages from different
+    /// game instances (or a game that was relaunched) can be told apart. Ids start at 1 and are unique per server,
+    /// clients get one when they send their first log so clients that only send commands don't have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<u32>,
+    /// When the sender says it sent this message, in milliseconds since the Unix epoch.
+    /// Senders don't have to set this, and values that aren't a number of milliseconds are ignored.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_lenient_millis",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[typeshare(serialized_as = "Option<f64>")]
+    pub timestamp: Option<u64>,
+    /// When the [LogServer] received this message, in milliseconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[typeshare(serialized_as = "Option<f64>")]
+    pub received_at: Option<u64>,
+    /// The order the [LogServer] received this message in, starting at 1. Unlike [SocketMessage::received_at]
+    /// this is unique per server, so it can order messages that were received in the same millisecond.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u32>,
+}
+
+/// Get the current time in milliseconds since the Unix epoch
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+// Senders aren't ours, so a timestamp we don't understand shouldn't make the whole message invalid
+fn deserialize_lenient_millis<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u64>, D::Error> {
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|value| match value {
+        serde_json::Value::Number(n) => n
+            .as_u64()
+            .or_else(|| n.as_f64().filter(|f| *f >= 0.0).map(|f| f as u64)),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }))
 }
 
 impl SocketMessage {
@@ -81,8 +296,28 @@ impl SocketMessage {
             message_type,
             sender_name: Some("Manager".to_string()),
             sender_type: Some("LogServer".to_string()),
+            client_id: None,
+            timestamp: None,
+            received_at: None,
+            sequence: None,
         }
     }
+
+    /// Set the [SocketMessage::client_id] of this message
+    pub fn with_client_id(mut self, client_id: u32) -> Self {
+        self.client_id = Some(client_id);
+        self
+    }
+
+    /// Check if this message says the same thing as another, ignoring when each was sent and received.
+    /// Used to group messages that are sent many times in a row.
+    pub fn is_repeat_of(&self, other: &SocketMessage) -> bool {
+        self.message == other.message
+            && self.message_type == other.message_type
+            && self.sender_name == other.sender_name
+            && self.sender_type == other.sender_type
+            && self.client_id == other.client_id
+    }
 }
 
 impl Searchable for SocketMessage {
@@ -95,16 +330,155 @@ impl Searchable for SocketMessage {
     }
 }
 
+/// A command sent to the game through a [LogServer], each command is sent as a single line of JSON.
+///
+/// The JSON has a `command` field with the name of the command (camelCase), and a `payload` field for commands that take one.
+/// For example `{"command":"ping"}` or `{"command":"console","payload":"help"}`.
+///
+/// A log server sends these to the games connected to it, and also accepts them from other clients,
+/// in which case it passes them on to every connected game, see [send_command].
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "command", content = "payload", rename_all = "camelCase")]
+pub enum SocketCommand {
+    /// Check that the game is still responding, the game should reply with a log message
+    Ping,
+    /// Ask OWML to reload the configs of every mod
+    ReloadConfigs,
+    /// Run a console command, what this does is up to OWML and the mods running
+    Console(String),
+}
+
+impl SocketCommand {
+    /// Parse a command from its name and arguments, names are the same as the `command` field in JSON
+    /// but kebab-case is also accepted (`reload-configs` and `reloadConfigs` both work).
+    ///
+    /// ## Errors
+    ///
+    /// If the command doesn't exist, or a command that needs arguments wasn't given any
+    ///
+    pub fn parse(name: &str, args: &[String]) -> Result<Self> {
+        match name {
+            "ping" => Ok(Self::Ping),
+            "reload-configs" | "reloadConfigs" => Ok(Self::ReloadConfigs),
+            "console" if !args.is_empty() => Ok(Self::Console(args.join(" "))),
+            "console" => Err(anyhow!("The console command needs something to run")),
+            _ => Err(anyhow!(
+                "Unknown command {name}, use ping, reload-configs, or console"
+            )),
+        }
+    }
+}
+
+/// What a [LogServer] replies with when a client asks it to send a [SocketCommand]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommandReceipt {
+    sent_to: usize,
+}
+
+/// Sends [SocketCommand]s to every game connected to a [LogServer], get one with [LogServer::command_sender].
+/// This can be cloned and kept around for as long as needed, commands sent when no game is connected go nowhere.
+#[derive(Debug, Clone, Default)]
+pub struct CommandSender {
+    clients: Arc<Mutex<HashMap<u32, mpsc::UnboundedSender<String>>>>,
+}
+
+impl CommandSender {
+    fn register(&self, id: u32, client: mpsc::UnboundedSender<String>) {
+        self.clients.lock().unwrap().insert(id, client);
+    }
+
+    fn unregister(&self, id: u32) {
+        self.clients.lock().unwrap().remove(&id);
+    }
+
+    /// Send a command to every connected game
+    ///
+    /// ## Returns
+    ///
+    /// How many games the command was sent to
+    ///
+    pub fn send(&self, command: &SocketCommand) -> usize {
+        let line = match serde_json::to_string(command) {
+            Ok(line) => line,
+            Err(why) => {
+                error!("Couldn't serialize command: {why:?}");
+                return 0;
+            }
+        };
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|_, client| client.send(line.clone()).is_ok());
+        clients.len()
+    }
+}
+
+/// Connect to a log server and ask it to send a command to every game connected to it.
+/// Use this when the log server is running in another process, otherwise use [LogServer::command_sender].
+///
+/// ## Returns
+///
+/// How many games the command was sent to
+///
+/// ## Errors
+///
+/// If we can't connect to the server or it doesn't reply in time
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::socket::{send_command, SocketCommand};
+///
+/// # tokio_test::block_on(async {
+/// let sent_to = send_command(12345, &SocketCommand::Ping).await.unwrap();
+/// println!("Pinged {sent_to} game(s)");
+/// # });
+/// ```
+///
+pub async fn send_command(port: u16, command: &SocketCommand) -> Result<usize> {
+    let mut stream = TcpStream::connect(format!("127.0.0.1:{port}")).await?;
+    let line = format!("{}\n", serde_json::to_string(command)?);
+    stream.write_all(line.as_bytes()).await?;
+    let mut reply = String::new();
+    timeout(
+        Duration::from_secs(5),
+        BufReader::new(stream).read_line(&mut reply),
+    )
+    .await
+    .map_err(|_| anyhow!("Log server on port {port} didn't reply"))??;
+    let receipt: CommandReceipt = serde_json::from_str(reply.trim())
+        .map_err(|e| anyhow!("Invalid reply from log server on port {port}: {e:?}"))?;
+    Ok(receipt.sent_to)
+}
+
 /// A server used to listen to logs from the game
 pub struct LogServer {
     /// The port the server is bound to
     pub port: u16,
     listener: TcpListener,
+    filter: Arc<LogFilter>,
+    commands: CommandSender,
+    next_connection_id: AtomicU32,
+    next_client_id: Arc<AtomicU32>,
+    queue: Arc<LogQueue>,
 }
 
 impl LogServer {
-    const CLIENT_CONNECTED: &'static str = "====== Client Connected To Console ======";
-    const CLIENT_DISCONNECTED: &'static str = "====== Client Disconnected From Console ======";
+    fn client_connected(id: u32) -> SocketMessage {
+        SocketMessage::make_internal(
+            &format!("====== Client {id} Connected To Console ======"),
+            SocketMessageType::Info,
+        )
+        .with_client_id(id)
+    }
+
+    fn client_disconnected(id: u32) -> SocketMessage {
+        SocketMessage::make_internal(
+            &format!("====== Client {id} Disconnected From Console ======"),
+            SocketMessageType::Info,
+        )
+        .with_client_id(id)
+    }
 
     /// Create and bind a log server to the given port, pass port 0 to auto-assign.
     /// **IMPORTANT:** If you pass port 0 make sure to get the port after binding. Otherwise the port you have and the port the server is bound to won't match.
@@ -122,26 +496,68 @@ impl LogServer {
         let listener = TcpListener::bind(&address).await?;
         // Get the actual port we bound to in case the user passed port 0
         let port = listener.local_addr()?.port();
-        Ok(Self { port, listener })
+        Ok(Self {
+            port,
+            listener,
+            filter: Arc::new(LogFilter::default()),
+            commands: CommandSender::default(),
+            next_connection_id: AtomicU32::new(1),
+            next_client_id: Arc::new(AtomicU32::new(1)),
+            queue: Arc::new(LogQueue::default()),
+        })
     }
 
-    // Give a log to the tx channel
-    async fn yield_log(tx: &LogServerSender, message: SocketMessage) {
-        if tx.capacity() == 0 {
-            warn!("Logs incoming too fast! Logs may be dropped!");
-        } else {
-            let res = tx.send(message).await;
-            if let Err(why) = res {
-                error!("Couldn't Yield Log: {why:?}")
-            }
-        }
+    /// Only send messages from the game that pass the given filter,
+    /// messages from the server itself (like a client connecting) are always sent.
+    pub fn with_filter(mut self, filter: LogFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self
+    }
+
+    /// Get a [CommandSender] to send commands to the games connected to this server
+    pub fn command_sender(&self) -> CommandSender {
+        self.commands.clone()
+    }
+
+    /// Get stats on how well whatever is receiving logs from [LogServer::listen] is keeping up.
+    /// If it falls behind, logs are buffered to a temporary file instead of being dropped.
+    pub fn metrics(&self) -> LogQueueMetrics {
+        self.queue.metrics()
     }
 
     // Loop that runs when a client connects to the server
     // Handles all messages from the client
+    // Clients only get an id (and a connected message) once they send a log, so clients that only send commands aren't counted
     // Returns true if the client sent a quit message
-    async fn client_loop(mut stream: TcpStream, tx: &LogServerSender) -> bool {
-        let mut reader = BufReader::with_capacity(16000, &mut stream);
+    async fn client_loop(
+        connection: u32,
+        stream: TcpStream,
+        tx: &LogQueueSender,
+        filter: &LogFilter,
+        commands: &CommandSender,
+        next_client_id: &AtomicU32,
+    ) -> bool {
+        let (read, mut write) = stream.into_split();
+        // Lines to send back to the client, either commands for the game or replies to a client sending commands
+        let (line_tx, mut line_rx) = mpsc::unbounded_channel::<String>();
+        let writer = tokio::spawn(async move {
+            while let Some(line) = line_rx.recv().await {
+                if let Err(why) = write.write_all(format!("{line}\n").as_bytes()).await {
+                    warn!("Couldn't send line to log client: {why:?}");
+                    break;
+                }
+            }
+        });
+        commands.register(connection, line_tx.clone());
+        let mut client_id: Option<u32> = None;
+        let mut get_client_id = || {
+            *client_id.get_or_insert_with(|| {
+                let id = next_client_id.fetch_add(1, Ordering::Relaxed);
+                tx.send(Self::client_connected(id));
+                id
+            })
+        };
+        let mut reader = BufReader::with_capacity(16000, read);
         let mut body = String::new();
         let mut flag = false;
         while let Ok(bytes_read) = reader.read_line(&mut body).await {
@@ -151,7 +567,22 @@ impl LogServer {
             if body.trim() == "" {
                 continue;
             }
+            if let Ok(command) = serde_json::from_str::<SocketCommand>(body.trim()) {
+                // Clients that send commands aren't games, so they shouldn't get commands themselves
+                commands.unregister(connection);
+                let sent_to = commands.send(&command);
+                tx.send(SocketMessage::make_internal(
+                    &format!("Sent command {command:?} to {sent_to} client(s)"),
+                    SocketMessageType::Info,
+                ));
+                if let Ok(reply) = serde_json::to_string(&CommandReceipt { sent_to }) {
+                    line_tx.send(reply).ok();
+                }
+                body.clear();
+                continue;
+            }
             let message: Result<SocketMessage, _> = serde_json::from_str(body.trim());
+            let id = get_client_id();
             match message {
                 Ok(message) => {
                     match message.message_type {
@@ -160,23 +591,30 @@ impl LogServer {
                             break;
                         }
                         _ => {
-                            Self::yield_log(tx, message).await;
+                            if filter.matches(&message) {
+                                tx.send(message.with_client_id(id));
+                            }
                         }
                     };
                 }
                 Err(why) => {
-                    Self::yield_log(
-                        tx,
+                    tx.send(
                         SocketMessage::make_internal(
                             &format!("Invalid Log From Game Received: {:?}", why),
                             SocketMessageType::Error,
-                        ),
-                    )
-                    .await;
+                        )
+                        .with_client_id(id),
+                    );
                 }
             }
             body.clear();
         }
+        commands.unregister(connection);
+        drop(line_tx);
+        writer.await.ok();
+        if let Some(id) = client_id {
+            tx.send(Self::client_disconnected(id));
+        }
         flag
     }
 
@@ -184,7 +622,7 @@ impl LogServer {
     // Makes a new client_loop for each client
     async fn server_loop(
         &self,
-        tx: &LogServerSender,
+        tx: &LogQueueSender,
         shutdown_sender: mpsc::Sender<()>,
         disconnect_on_quit: bool,
     ) {
@@ -194,41 +632,31 @@ impl LogServer {
                 Ok((stream, _)) => {
                     let tx2 = tx.clone();
                     let shutdown_sender2 = shutdown_sender.clone();
+                    let filter = self.filter.clone();
+                    let commands = self.commands.clone();
+                    let next_client_id = self.next_client_id.clone();
+                    let connection = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
                     tokio::spawn(async move {
-                        Self::yield_log(
+                        let quit_received = Self::client_loop(
+                            connection,
+                            stream,
                             &tx2,
-                            SocketMessage::make_internal(
-                                Self::CLIENT_CONNECTED,
-                                SocketMessageType::Info,
-                            ),
+                            &filter,
+                            &commands,
+                            &next_client_id,
                         )
                         .await;
 
-                        let quit_received = Self::client_loop(stream, &tx2).await;
-
                         if quit_received && disconnect_on_quit {
                             shutdown_sender2.send(()).await.ok();
                         }
-
-                        Self::yield_log(
-                            &tx2,
-                            SocketMessage::make_internal(
-                                Self::CLIENT_DISCONNECTED,
-                                SocketMessageType::Info,
-                            ),
-                        )
-                        .await;
                     });
                 }
                 Err(why) => {
-                    Self::yield_log(
-                        tx,
-                        SocketMessage::make_internal(
-                            &format!("Client Connection Failure! {why:?}"),
-                            SocketMessageType::Error,
-                        ),
-                    )
-                    .await;
+                    tx.send(SocketMessage::make_internal(
+                        &format!("Client Connection Failure! {why:?}"),
+                        SocketMessageType::Error,
+                    ));
                 }
             }
         }
@@ -239,15 +667,20 @@ impl LogServer {
     /// - tx will send [SocketMessage]s from the game
     /// - disconnect_on_quit will make the server stop listening if the game sends a [SocketMessageType::Quit] message
     ///
+    /// Logs are never dropped if tx's receiver falls behind, they're buffered (to a temporary file if there's a lot of them)
+    /// and sent in order once it catches up, see [LogServer::metrics].
+    /// tx is closed once the server stops listening, every client has disconnected, and every log has been sent.
+    ///
     pub async fn listen(&self, tx: LogServerSender, disconnect_on_quit: bool) -> Result<()> {
-        Self::yield_log(
-            &tx,
-            SocketMessage::make_internal(
-                &format!("Ready to receive game logs on port {}!", self.port),
-                SocketMessageType::Info,
-            ),
-        )
-        .await;
+        // Make our sender first, so the queue doesn't think everyone's done before we've started
+        let queue_tx = self.queue.sender();
+        tokio::spawn(self.queue.clone().forward(tx));
+        let tx = queue_tx;
+
+        tx.send(SocketMessage::make_internal(
+            &format!("Ready to receive game logs on port {}!", self.port),
+            SocketMessageType::Info,
+        ));
 
         // Make a channel to listen for a shutdown message
         let (shutdown_sender, mut shutdown_receiver) = mpsc::channel::<()>(2);
@@ -306,7 +739,17 @@ mod tests {
 
         pub fn assert_logs(&self, expected: Vec<SocketMessage>) {
             assert_eq!(self.logs.len(), expected.len());
+            let mut last_received_at = 0;
             for (i, log) in self.logs.iter().enumerate() {
+                assert_eq!(log.sequence, Some(i as u32 + 1));
+                let received_at = log.received_at.unwrap();
+                assert!(received_at >= last_received_at);
+                last_received_at = received_at;
+                let log = &SocketMessage {
+                    received_at: None,
+                    sequence: None,
+                    ..log.clone()
+                };
                 if log != &expected[i] {
                     panic!(
                         "Log {} doesn't match expected!\nExpected: {:?}\nActual: {:?}",
@@ -361,8 +804,11 @@ mod tests {
                 let mut game = MockGame::new(port).await;
                 game.send_test_msg("Test Message", SocketMessageType::Info)
                     .await;
-                game.send_test_msg("Success!", SocketMessageType::Success)
-                    .await;
+                game.send(SocketMessage {
+                    timestamp: Some(1700000000000),
+                    ..MockGame::make_test_msg("Success!", SocketMessageType::Success)
+                })
+                .await;
                 game.send_test_msg("", SocketMessageType::Quit).await;
                 // Wait for the server to finish appending logs
                 tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -373,13 +819,14 @@ mod tests {
                     &format!("Ready to receive game logs on port {}!", server.port),
                     SocketMessageType::Info,
                 ),
-                SocketMessage::make_internal(LogServer::CLIENT_CONNECTED, SocketMessageType::Info),
-                MockGame::make_test_msg("Test Message", SocketMessageType::Info),
-                MockGame::make_test_msg("Success!", SocketMessageType::Success),
-                SocketMessage::make_internal(
-                    LogServer::CLIENT_DISCONNECTED,
-                    SocketMessageType::Info,
-                ),
+                LogServer::client_connected(1),
+                MockGame::make_test_msg("Test Message", SocketMessageType::Info).with_client_id(1),
+                SocketMessage {
+                    timestamp: Some(1700000000000),
+                    ..MockGame::make_test_msg("Success!", SocketMessageType::Success)
+                        .with_client_id(1)
+                },
+                LogServer::client_disconnected(1),
             ];
 
             server.assert_logs(expected);
@@ -415,20 +862,14 @@ mod tests {
                     &format!("Ready to receive game logs on port {}!", server.port),
                     SocketMessageType::Info,
                 ),
-                SocketMessage::make_internal(LogServer::CLIENT_CONNECTED, SocketMessageType::Info),
-                MockGame::make_test_msg("Test Message", SocketMessageType::Info),
-                MockGame::make_test_msg("Success!", SocketMessageType::Success),
-                SocketMessage::make_internal(
-                    LogServer::CLIENT_DISCONNECTED,
-                    SocketMessageType::Info,
-                ),
-                SocketMessage::make_internal(LogServer::CLIENT_CONNECTED, SocketMessageType::Info),
-                MockGame::make_test_msg("Test Message", SocketMessageType::Info),
-                MockGame::make_test_msg("Warning!", SocketMessageType::Warning),
-                SocketMessage::make_internal(
-                    LogServer::CLIENT_DISCONNECTED,
-                    SocketMessageType::Info,
-                ),
+                LogServer::client_connected(1),
+                MockGame::make_test_msg("Test Message", SocketMessageType::Info).with_client_id(1),
+                MockGame::make_test_msg("Success!", SocketMessageType::Success).with_client_id(1),
+                LogServer::client_disconnected(1),
+                LogServer::client_connected(2),
+                MockGame::make_test_msg("Test Message", SocketMessageType::Info).with_client_id(2),
+                MockGame::make_test_msg("Warning!", SocketMessageType::Warning).with_client_id(2),
+                LogServer::client_disconnected(2),
             ];
 
             server.assert_logs(expected);
@@ -469,25 +910,274 @@ mod tests {
                     &format!("Ready to receive game logs on port {}!", server.port),
                     SocketMessageType::Info,
                 ),
-                SocketMessage::make_internal(LogServer::CLIENT_CONNECTED, SocketMessageType::Info),
-                MockGame::make_test_msg("Test Message", SocketMessageType::Info),
-                MockGame::make_test_msg("Success!", SocketMessageType::Success),
-                SocketMessage::make_internal(LogServer::CLIENT_CONNECTED, SocketMessageType::Info),
-                MockGame::make_test_msg("Test Message", SocketMessageType::Info),
-                MockGame::make_test_msg("Warning!", SocketMessageType::Warning),
-                MockGame::make_test_msg("Other Info", SocketMessageType::Info),
-                MockGame::make_test_msg("Other Warning", SocketMessageType::Warning),
-                SocketMessage::make_internal(
-                    LogServer::CLIENT_DISCONNECTED,
-                    SocketMessageType::Info,
-                ),
+                LogServer::client_connected(1),
+                MockGame::make_test_msg("Test Message", SocketMessageType::Info).with_client_id(1),
+                MockGame::make_test_msg("Success!", SocketMessageType::Success).with_client_id(1),
+                LogServer::client_connected(2),
+                MockGame::make_test_msg("Test Message", SocketMessageType::Info).with_client_id(2),
+                MockGame::make_test_msg("Warning!", SocketMessageType::Warning).with_client_id(2),
+                MockGame::make_test_msg("Other Info", SocketMessageType::Info).with_client_id(1),
+                MockGame::make_test_msg("Other Warning", SocketMessageType::Warning)
+                    .with_client_id(2),
+                LogServer::client_disconnected(2),
+                LogServer::client_disconnected(1),
+            ];
+
+            server.assert_logs(expected);
+        });
+    }
+
+    fn make_sender_msg(
+        sender_name: &str,
+        message: &str,
+        message_type: SocketMessageType,
+    ) -> SocketMessage {
+        let mut msg = MockGame::make_test_msg(message, message_type);
+        msg.sender_name = Some(sender_name.to_string());
+        msg
+    }
+
+    #[test]
+    fn test_log_filter_rule() {
+        let rule = LogFilterRule::default()
+            .with_min_level(SocketMessageType::Warning)
+            .with_sender_name("New*")
+            .unwrap()
+            .with_sender_type("testclient");
+        assert!(rule.matches(&make_sender_msg(
+            "NewHorizons",
+            "Oh no",
+            SocketMessageType::Error
+        )));
+        assert!(rule.matches(&make_sender_msg(
+            "newhorizons",
+            "Careful",
+            SocketMessageType::Warning
+        )));
+        assert!(!rule.matches(&make_sender_msg(
+            "NewHorizons",
+            "Hello",
+            SocketMessageType::Info
+        )));
+        assert!(!rule.matches(&make_sender_msg("QSB", "Oh no", SocketMessageType::Error)));
+        assert!(!rule.matches(&make_sender_msg(
+            "A.NewHorizons",
+            "Oh no",
+            SocketMessageType::Error
+        )));
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let filter = LogFilter::default()
+            .include(LogFilterRule::default().with_types(&[SocketMessageType::Error]))
+            .include(LogFilterRule::default().with_sender_name("QSB").unwrap())
+            .exclude(
+                LogFilterRule::default()
+                    .with_message_regex("^Ignore")
+                    .unwrap(),
+            );
+        assert!(filter.matches(&make_sender_msg(
+            "NewHorizons",
+            "Oh no",
+            SocketMessageType::Error
+        )));
+        assert!(filter.matches(&make_sender_msg("QSB", "Hello", SocketMessageType::Info)));
+        assert!(!filter.matches(&make_sender_msg(
+            "NewHorizons",
+            "Hello",
+            SocketMessageType::Info
+        )));
+        assert!(!filter.matches(&make_sender_msg(
+            "QSB",
+            "Ignore me",
+            SocketMessageType::Error
+        )));
+        assert!(LogFilter::default().matches(&make_sender_msg(
+            "QSB",
+            "Hello",
+            SocketMessageType::Info
+        )));
+    }
+
+    #[test]
+    fn test_log_server_filter() {
+        tokio_test::block_on(async {
+            let mut server = TestLogServer::new().await;
+            server.server = server.server.with_filter(
+                LogFilter::default()
+                    .include(LogFilterRule::default().with_min_level(SocketMessageType::Warning)),
+            );
+
+            let port = server.port;
+
+            tokio::join!(server.listen(true), async move {
+                let mut game = MockGame::new(port).await;
+                game.send_test_msg("Test Message", SocketMessageType::Info)
+                    .await;
+                game.send_test_msg("Warning!", SocketMessageType::Warning)
+                    .await;
+                game.send_test_msg("Debug", SocketMessageType::Debug).await;
+                game.send_test_msg("", SocketMessageType::Quit).await;
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            });
+
+            let expected = vec![
                 SocketMessage::make_internal(
-                    LogServer::CLIENT_DISCONNECTED,
+                    &format!("Ready to receive game logs on port {}!", server.port),
                     SocketMessageType::Info,
                 ),
+                LogServer::client_connected(1),
+                MockGame::make_test_msg("Warning!", SocketMessageType::Warning).with_client_id(1),
+                LogServer::client_disconnected(1),
             ];
 
             server.assert_logs(expected);
         });
     }
+
+    #[test]
+    fn test_socket_command_format() {
+        assert_eq!(
+            serde_json::to_string(&SocketCommand::Ping).unwrap(),
+            "{\"command\":\"ping\"}"
+        );
+        assert_eq!(
+            serde_json::to_string(&SocketCommand::Console("help".to_string())).unwrap(),
+            "{\"command\":\"console\",\"payload\":\"help\"}"
+        );
+        assert_eq!(
+            SocketCommand::parse("reload-configs", &[]).unwrap(),
+            SocketCommand::ReloadConfigs
+        );
+        assert_eq!(
+            SocketCommand::parse("console", &["say".to_string(), "hi".to_string()]).unwrap(),
+            SocketCommand::Console("say hi".to_string())
+        );
+        assert!(SocketCommand::parse("console", &[]).is_err());
+        assert!(SocketCommand::parse("explode", &[]).is_err());
+        // A command isn't a valid log message, and a log message isn't a valid command
+        assert!(serde_json::from_str::<SocketMessage>("{\"command\":\"ping\"}").is_err());
+        assert!(serde_json::from_str::<SocketCommand>(
+            &serde_json::to_string(&MockGame::make_test_msg("Hi", SocketMessageType::Info))
+                .unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_socket_message_timestamp() {
+        let parse = |timestamp: &str| {
+            serde_json::from_str::<SocketMessage>(&format!(
+                "{{\"type\": 0, \"message\": \"Hi\", \"timestamp\": {timestamp}}}"
+            ))
+            .unwrap()
+            .timestamp
+        };
+        assert_eq!(parse("1700000000123"), Some(1700000000123));
+        assert_eq!(parse("1700000000123.6"), Some(1700000000123));
+        assert_eq!(parse("\"1700000000123\""), Some(1700000000123));
+        // Anything else is ignored instead of making the message invalid
+        assert_eq!(parse("\"2023-11-14T22:13:20Z\""), None);
+        assert_eq!(parse("-5"), None);
+        assert_eq!(parse("null"), None);
+        let msg =
+            serde_json::from_str::<SocketMessage>("{\"type\": 0, \"message\": \"Hi\"}").unwrap();
+        assert_eq!(msg.timestamp, None);
+        assert_eq!(
+            serde_json::to_string(&msg).unwrap(),
+            "{\"senderName\":null,\"senderType\":null,\"message\":\"Hi\",\"messageType\":0}"
+        );
+    }
+
+    #[test]
+    fn test_log_server_commands() {
+        tokio_test::block_on(async {
+            let mut server = TestLogServer::new().await;
+            let port = server.port;
+            let commands = server.server.command_sender();
+
+            tokio::join!(server.listen(true), async move {
+                let mut game = MockGame::new(port).await;
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+                let sent_to = send_command(port, &SocketCommand::Ping).await.unwrap();
+                assert_eq!(sent_to, 1);
+                assert_eq!(commands.send(&SocketCommand::ReloadConfigs), 1);
+
+                let mut reader = BufReader::new(&mut game.stream);
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                assert_eq!(line, "{\"command\":\"ping\"}\n");
+                line.clear();
+                reader.read_line(&mut line).await.unwrap();
+                assert_eq!(line, "{\"command\":\"reloadConfigs\"}\n");
+
+                game.send_test_msg("", SocketMessageType::Quit).await;
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                assert_eq!(commands.send(&SocketCommand::Ping), 0);
+            });
+
+            let expected = SocketMessage::make_internal(
+                "Sent command Ping to 1 client(s)",
+                SocketMessageType::Info,
+            );
+            assert!(server.logs.iter().any(|log| log.is_repeat_of(&expected)));
+            // The client that only sent a command isn't counted as a game
+            assert!(server
+                .logs
+                .iter()
+                .any(|log| log.is_repeat_of(&LogServer::client_connected(1))));
+            assert!(server.logs.iter().all(|log| log.client_id != Some(2)));
+        });
+    }
+
+    #[test]
+    fn test_log_server_slow_consumer() {
+        tokio_test::block_on(async {
+            let server = LogServer::new(0).await.unwrap();
+            let port = server.port;
+            let (tx, mut rx) = mpsc::channel(1);
+            let total = 5000;
+
+            let (res, received, _) = tokio::join!(
+                server.listen(tx, true),
+                async {
+                    // Don't start reading until the game is done, so the server has to buffer everything
+                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                    let mut received = vec![];
+                    while let Some(msg) = rx.recv().await {
+                        received.push(msg);
+                    }
+                    received
+                },
+                async move {
+                    let mut game = MockGame::new(port).await;
+                    let mut lines = String::new();
+                    for i in 0..total {
+                        let msg = MockGame::make_test_msg(&i.to_string(), SocketMessageType::Info);
+                        lines.push_str(&format!("{}\n", serde_json::to_string(&msg).unwrap()));
+                    }
+                    game.stream.write_all(lines.as_bytes()).await.unwrap();
+                    game.send_test_msg("", SocketMessageType::Quit).await;
+                }
+            );
+            res.unwrap();
+
+            // Ready + connected + every message + disconnected
+            assert_eq!(received.len(), total + 3);
+            for (i, msg) in received[2..total + 2].iter().enumerate() {
+                assert_eq!(msg.message, i.to_string());
+            }
+            // Sequence numbers stay in order even for messages that were buffered to disk
+            for (i, msg) in received.iter().enumerate() {
+                assert_eq!(msg.sequence, Some(i as u32 + 1));
+            }
+            let metrics = server.metrics();
+            assert!(metrics.spilled > 0);
+            assert_eq!(metrics.dropped, 0);
+            assert_eq!(metrics.delivered, total as u64 + 3);
+            assert_eq!(metrics.lag, 0);
+        });
+    }
 }
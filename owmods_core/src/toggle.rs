@@ -1,12 +1,16 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Result};
-use log::warn;
+use log::{info, warn};
+use serde::Serialize;
+use typeshare::typeshare;
 
 use crate::{
+    config::Config,
     db::LocalDatabase,
     file::{deserialize_from_json, fix_json_file, serialize_to_json},
     mods::local::{LocalMod, ModStubConfig},
+    snapshots::{try_take_snapshot, SnapshotReason},
 };
 
 fn read_config(config_path: &Path) -> Result<ModStubConfig> {
@@ -57,16 +61,24 @@ pub fn get_mod_enabled(mod_path: &Path) -> Result<bool> {
     }
 }
 
-fn _toggle_mod(local_mod: &LocalMod, enabled: bool) -> Result<bool> {
+// If `snapshot_config` is set, a snapshot of the mod's settings is taken before its enabled state changes
+fn _toggle_mod(
+    snapshot_config: Option<&Config>,
+    local_mod: &LocalMod,
+    enabled: bool,
+) -> Result<bool> {
     let config_path = PathBuf::from(&local_mod.mod_path).join("config.json");
 
     if config_path.is_file() {
-        let mut config = read_config(&config_path)?;
-        config.enabled = enabled;
-        write_config(&config, &config_path)?;
+        let mut mod_config = read_config(&config_path)?;
+        if let Some(config) = snapshot_config.filter(|_| mod_config.enabled != enabled) {
+            try_take_snapshot(config, local_mod, SnapshotReason::Toggle);
+        }
+        mod_config.enabled = enabled;
+        write_config(&mod_config, &config_path)?;
     } else {
         generate_config(&config_path)?;
-        return _toggle_mod(local_mod, enabled);
+        return _toggle_mod(snapshot_config, local_mod, enabled);
     }
 
     Ok(!enabled && local_mod.uses_pre_patcher())
@@ -89,13 +101,44 @@ pub fn toggle_mod(
     local_db: &LocalDatabase,
     enabled: bool,
     recursive: bool,
+) -> Result<Vec<String>> {
+    toggle_mod_inner(unique_name, None, local_db, enabled, recursive)
+}
+
+/// Same as [toggle_mod], but takes a snapshot of the settings of each mod whose enabled state changes first
+/// (see [crate::snapshots]), so the change can be undone. Failing to take a snapshot doesn't stop the toggle.
+///
+/// ## Returns
+///
+/// See [toggle_mod]
+///
+/// ## Errors
+///
+/// See [toggle_mod]
+///
+pub fn toggle_mod_with_snapshot(
+    unique_name: &str,
+    config: &Config,
+    local_db: &LocalDatabase,
+    enabled: bool,
+    recursive: bool,
+) -> Result<Vec<String>> {
+    toggle_mod_inner(unique_name, Some(config), local_db, enabled, recursive)
+}
+
+fn toggle_mod_inner(
+    unique_name: &str,
+    snapshot_config: Option<&Config>,
+    local_db: &LocalDatabase,
+    enabled: bool,
+    recursive: bool,
 ) -> Result<Vec<String>> {
     let mut show_warnings_for: Vec<String> = vec![];
 
     let local_mod = local_db
         .get_mod(unique_name)
         .ok_or_else(|| anyhow!("Mod {} not found in local database.", unique_name))?;
-    let show_warning = _toggle_mod(local_mod, enabled)?;
+    let show_warning = _toggle_mod(snapshot_config, local_mod, enabled)?;
 
     if show_warning {
         show_warnings_for.push(unique_name.to_string());
@@ -113,7 +156,7 @@ pub fn toggle_mod(
                 let dep_mod = local_db.get_mod(&dep);
                 if let Some(dep_mod) = dep_mod {
                     let show_warning = if enabled {
-                        _toggle_mod(dep_mod, enabled)
+                        _toggle_mod(snapshot_config, dep_mod, enabled)
                     } else {
                         let mut flag = true;
                         for dependent_mod in local_db.dependent(dep_mod).filter(|m| m.enabled) {
@@ -127,7 +170,7 @@ pub fn toggle_mod(
                             }
                         }
                         if flag {
-                            _toggle_mod(dep_mod, enabled)
+                            _toggle_mod(snapshot_config, dep_mod, enabled)
                         } else {
                             Ok(false)
                         }
@@ -146,6 +189,209 @@ pub fn toggle_mod(
     Ok(show_warnings_for)
 }
 
+/// A conflict between a mod we want to enable and a mod that's already enabled
+#[typeshare]
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModConflict {
+    /// The unique name of the mod being enabled (this can be a dependency of the mod the user asked to enable)
+    pub unique_name: String,
+    /// The unique name of the enabled mod it conflicts with
+    pub conflicting_mod: String,
+    /// Whether the conflict is declared by the already enabled mod instead of the mod being enabled
+    pub reverse: bool,
+}
+
+/// The result of trying to enable a mod with [enable_mod]
+#[typeshare]
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EnableModResult {
+    /// Whether the mod was enabled, this is false if there were conflicts and we weren't allowed to disable the conflicting mods
+    pub enabled: bool,
+    /// The conflicts that were found
+    pub conflicts: Vec<ModConflict>,
+    /// The unique names of mods that were disabled to resolve the conflicts
+    pub disabled: Vec<String>,
+    /// The unique names of disabled mods that use pre patchers,
+    /// and therefore **should alert the user to check the mod's README for instructions on how to fully disable it**.
+    pub show_warnings_for: Vec<String>,
+}
+
+/// Find the conflicts that enabling a mod would cause with the mods that are already enabled.
+/// This checks conflicts declared in either direction, if recursive is true the dependencies that would be enabled are checked as well.
+///
+/// ## Returns
+///
+/// A list of conflicts, empty if the mod can be enabled safely
+///
+/// ## Errors
+///
+/// If the mod isn't installed
+///
+pub fn get_enable_conflicts(
+    unique_name: &str,
+    local_db: &LocalDatabase,
+    recursive: bool,
+) -> Result<Vec<ModConflict>> {
+    let local_mod = local_db
+        .get_mod(unique_name)
+        .ok_or_else(|| anyhow!("Mod {} not found in local database.", unique_name))?;
+
+    let mut to_enable: Vec<&LocalMod> = vec![local_mod];
+    if recursive {
+        let mut to_check: Vec<String> = local_mod.manifest.dependencies.clone().unwrap_or_default();
+        while let Some(dep) = to_check.pop() {
+            if let Some(dep_mod) = local_db.get_mod(&dep) {
+                if !to_enable
+                    .iter()
+                    .any(|m| m.manifest.unique_name == dep_mod.manifest.unique_name)
+                {
+                    to_enable.push(dep_mod);
+                    to_check.extend(dep_mod.manifest.dependencies.clone().unwrap_or_default());
+                }
+            }
+        }
+    }
+
+    let mut conflicts: Vec<ModConflict> = vec![];
+    for enabling in to_enable.iter() {
+        let declared = enabling.manifest.conflicts.clone().unwrap_or_default();
+        for active in local_db.active().filter(|m| {
+            !to_enable
+                .iter()
+                .any(|e| e.manifest.unique_name == m.manifest.unique_name)
+        }) {
+            let reverse_declared = active
+                .manifest
+                .conflicts
+                .as_ref()
+                .is_some_and(|c| c.contains(&enabling.manifest.unique_name));
+            if declared.contains(&active.manifest.unique_name) || reverse_declared {
+                conflicts.push(ModConflict {
+                    unique_name: enabling.manifest.unique_name.clone(),
+                    conflicting_mod: active.manifest.unique_name.clone(),
+                    reverse: !declared.contains(&active.manifest.unique_name),
+                });
+            }
+        }
+    }
+    Ok(conflicts)
+}
+
+/// Enable a mod, checking for conflicts with mods that are already enabled first (see [get_enable_conflicts]).
+///
+/// If there are conflicts and `disable_conflicting` is false, nothing is changed and the conflicts are returned
+/// so the caller can ask the user what to do. If `disable_conflicting` is true, the conflicting mods are disabled first.
+///
+/// ## Returns
+///
+/// An [EnableModResult] describing what happened
+///
+/// ## Errors
+///
+/// If the mod isn't installed or we can't read/save the config files of the mods.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::toggle::enable_mod;
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// let result = enable_mod("Bwc9876.TimeSaver", &local_db, true, false).unwrap();
+///
+/// for conflict in result.conflicts.iter() {
+///     println!("{} conflicts with {}", conflict.unique_name, conflict.conflicting_mod);
+/// }
+/// ```
+///
+pub fn enable_mod(
+    unique_name: &str,
+    local_db: &LocalDatabase,
+    recursive: bool,
+    disable_conflicting: bool,
+) -> Result<EnableModResult> {
+    enable_mod_inner(unique_name, None, local_db, recursive, disable_conflicting)
+}
+
+/// Same as [enable_mod], but takes a snapshot of the settings of each mod that's enabled or disabled first,
+/// see [toggle_mod_with_snapshot].
+///
+/// ## Returns
+///
+/// See [enable_mod]
+///
+/// ## Errors
+///
+/// See [enable_mod]
+///
+pub fn enable_mod_with_snapshot(
+    unique_name: &str,
+    config: &Config,
+    local_db: &LocalDatabase,
+    recursive: bool,
+    disable_conflicting: bool,
+) -> Result<EnableModResult> {
+    enable_mod_inner(
+        unique_name,
+        Some(config),
+        local_db,
+        recursive,
+        disable_conflicting,
+    )
+}
+
+fn enable_mod_inner(
+    unique_name: &str,
+    snapshot_config: Option<&Config>,
+    local_db: &LocalDatabase,
+    recursive: bool,
+    disable_conflicting: bool,
+) -> Result<EnableModResult> {
+    let conflicts = get_enable_conflicts(unique_name, local_db, recursive)?;
+    if !conflicts.is_empty() && !disable_conflicting {
+        return Ok(EnableModResult {
+            enabled: false,
+            conflicts,
+            ..Default::default()
+        });
+    }
+    let mut result = EnableModResult {
+        enabled: true,
+        ..Default::default()
+    };
+    for conflict in conflicts.iter() {
+        if result.disabled.contains(&conflict.conflicting_mod) {
+            continue;
+        }
+        info!(
+            "Disabling {} as it conflicts with {}",
+            conflict.conflicting_mod, conflict.unique_name
+        );
+        result.show_warnings_for.extend(toggle_mod_inner(
+            &conflict.conflicting_mod,
+            snapshot_config,
+            local_db,
+            false,
+            false,
+        )?);
+        result.disabled.push(conflict.conflicting_mod.clone());
+    }
+    result.show_warnings_for.extend(toggle_mod_inner(
+        unique_name,
+        snapshot_config,
+        local_db,
+        true,
+        recursive,
+    )?);
+    result.conflicts = conflicts;
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -297,4 +543,63 @@ mod tests {
         assert!(show_warnings.is_empty());
         ctx.fetch_local_db();
     }
+
+    fn setup_conflict(ctx: &mut TestContext, reverse: bool) {
+        let mut time_saver = ctx.install_test_zip("Bwc9876.TimeSaver.zip", false);
+        let mut save_editor = ctx.install_test_zip("Bwc9876.SaveEditor.zip", true);
+        if reverse {
+            save_editor.manifest.conflicts = Some(vec!["Bwc9876.TimeSaver".to_string()]);
+            ctx.insert_test_mod(&save_editor);
+        } else {
+            time_saver.manifest.conflicts = Some(vec!["Bwc9876.SaveEditor".to_string()]);
+        }
+        time_saver.enabled = false;
+        ctx.insert_test_mod(&time_saver);
+    }
+
+    #[test]
+    fn test_enable_mod_conflict() {
+        let mut ctx = TestContext::new();
+        setup_conflict(&mut ctx, false);
+        let result = enable_mod("Bwc9876.TimeSaver", &ctx.local_db, false, false).unwrap();
+        assert!(!result.enabled);
+        assert_eq!(
+            result.conflicts,
+            vec![ModConflict {
+                unique_name: "Bwc9876.TimeSaver".to_string(),
+                conflicting_mod: "Bwc9876.SaveEditor".to_string(),
+                reverse: false,
+            }]
+        );
+        ctx.fetch_local_db();
+        assert!(ctx.local_db.get_mod("Bwc9876.SaveEditor").unwrap().enabled);
+    }
+
+    #[test]
+    fn test_enable_mod_reverse_conflict_disable() {
+        let mut ctx = TestContext::new();
+        setup_conflict(&mut ctx, true);
+        let result = enable_mod("Bwc9876.TimeSaver", &ctx.local_db, false, true).unwrap();
+        assert!(result.enabled);
+        assert!(result.conflicts[0].reverse);
+        assert_eq!(result.disabled, vec!["Bwc9876.SaveEditor".to_string()]);
+        ctx.fetch_local_db();
+        assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").unwrap().enabled);
+        assert!(!ctx.local_db.get_mod("Bwc9876.SaveEditor").unwrap().enabled);
+    }
+
+    #[test]
+    fn test_enable_mod_conflict_recursive() {
+        let mut ctx = TestContext::new();
+        setup_conflict(&mut ctx, false);
+        let mut test_mod = LocalMod::get_test(0);
+        test_mod.enabled = false;
+        test_mod.manifest.dependencies = Some(vec!["Bwc9876.TimeSaver".to_string()]);
+        ctx.insert_test_mod(&test_mod);
+        let conflicts = get_enable_conflicts("Example.TestMod0", &ctx.local_db, false).unwrap();
+        assert!(conflicts.is_empty());
+        let conflicts = get_enable_conflicts("Example.TestMod0", &ctx.local_db, true).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].unique_name, "Bwc9876.TimeSaver");
+    }
 }
@@ -0,0 +1,537 @@
+use std::{
+    cmp::Reverse,
+    fs::{self, create_dir_all, read_dir, remove_dir_all, remove_file},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+    config::Config,
+    constants::{TRASH_DIR_NAME, TRASH_INFO_FILE_NAME},
+    db::LocalDatabase,
+    file::{deserialize_from_json, move_dir, serialize_to_json},
+    mods::local::LocalMod,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A mod that was uninstalled and moved to the trash, it can be restored until it's purged.
+/// Leftovers removed by [crate::clean::clean_issue] are kept here too, see [TrashedMod::leftover].
+#[typeshare]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedMod {
+    /// The ID of this entry, this is the time the mod was removed in milliseconds since the Unix epoch
+    pub id: String,
+    /// The unique name of the mod
+    pub unique_name: String,
+    /// The name of the mod
+    pub name: String,
+    /// The version of the mod that was removed
+    pub version: String,
+    /// The path the mod was in before it was removed
+    pub original_path: String,
+    /// Whether this is a leftover file or folder rather than a mod.
+    /// For leftovers the unique name and name are the path relative to the `Mods` folder and there's no version.
+    #[serde(default)]
+    pub leftover: bool,
+}
+
+impl TrashedMod {
+    /// Get the folder the trash is kept in, this is next to the settings file
+    pub fn trash_dir(config: &Config) -> PathBuf {
+        config.path.with_file_name(TRASH_DIR_NAME)
+    }
+
+    /// Get the folder this entry is kept in
+    pub fn path(&self, config: &Config) -> PathBuf {
+        Self::trash_dir(config).join(&self.id)
+    }
+
+    /// Get the folder the mod's files are kept in, for leftover files this is the file itself
+    pub fn files_path(&self, config: &Config) -> PathBuf {
+        self.path(config).join("mod")
+    }
+
+    /// Get when the mod was removed
+    pub fn removed_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.id.parse().unwrap_or(0))
+    }
+
+    /// Get how long ago the mod was removed
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.removed_at())
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+fn new_id(dir: &Path) -> String {
+    let mut millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    while dir.join(millis.to_string()).exists() {
+        millis += 1;
+    }
+    millis.to_string()
+}
+
+/// Move a file or folder, falling back to copying it if it can't be renamed
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if from.is_dir() {
+        move_dir(from, to)
+    } else {
+        if fs::rename(from, to).is_err() {
+            fs::copy(from, to)?;
+            remove_file(from)?;
+        }
+        Ok(())
+    }
+}
+
+fn move_to_trash(config: &Config, path: &Path, trashed: TrashedMod) -> Result<TrashedMod> {
+    debug!(
+        "Moving {} to the trash as {}",
+        trashed.unique_name, trashed.id
+    );
+    let entry_path = trashed.path(config);
+    create_dir_all(&entry_path)?;
+    move_path(path, &trashed.files_path(config))?;
+    serialize_to_json(&trashed, &entry_path.join(TRASH_INFO_FILE_NAME), false)?;
+    if let Err(why) = purge_old_trash(config) {
+        warn!("Couldn't purge old mods from the trash: {:?}", why);
+    }
+    Ok(trashed)
+}
+
+/// Move a mod's folder to the trash instead of deleting it, the mod's `config.json` and `save.json` go with it.
+/// Entries older than [Config::trash_max_age_days] are purged afterwards.
+///
+/// ## Returns
+///
+/// The new entry in the trash.
+///
+/// ## Errors
+///
+/// If we can't move the mod's folder or save the info about it.
+///
+pub fn trash_mod(config: &Config, local_mod: &LocalMod) -> Result<TrashedMod> {
+    let trashed = TrashedMod {
+        id: new_id(&TrashedMod::trash_dir(config)),
+        unique_name: local_mod.manifest.unique_name.clone(),
+        name: local_mod.manifest.name.clone(),
+        version: local_mod.manifest.version.clone(),
+        original_path: local_mod.mod_path.clone(),
+        leftover: false,
+    };
+    move_to_trash(config, &PathBuf::from(&local_mod.mod_path), trashed)
+}
+
+/// Move a file or folder that isn't a loaded mod to the trash instead of deleting it, used by [crate::clean::clean_issue].
+/// Entries older than [Config::trash_max_age_days] are purged afterwards.
+///
+/// ## Returns
+///
+/// The new entry in the trash, `display_path` is used as its unique name and name.
+///
+/// ## Errors
+///
+/// If we can't move the file or folder or save the info about it.
+///
+pub fn trash_leftover(config: &Config, path: &Path, display_path: &str) -> Result<TrashedMod> {
+    let trashed = TrashedMod {
+        id: new_id(&TrashedMod::trash_dir(config)),
+        unique_name: display_path.to_string(),
+        name: display_path.to_string(),
+        version: String::new(),
+        original_path: path.to_string_lossy().to_string(),
+        leftover: true,
+    };
+    move_to_trash(config, path, trashed)
+}
+
+/// Get every mod in the trash
+///
+/// ## Returns
+///
+/// The mods in the trash, newest first. Entries we can't read are skipped.
+///
+/// ## Errors
+///
+/// If we can't read the trash folder.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::trash::get_trashed_mods;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+///
+/// for trashed in get_trashed_mods(&config).unwrap() {
+///     println!("{} v{} ({})", trashed.name, trashed.version, trashed.id);
+/// }
+/// ```
+///
+pub fn get_trashed_mods(config: &Config) -> Result<Vec<TrashedMod>> {
+    let trash_dir = TrashedMod::trash_dir(config);
+    if !trash_dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut trashed: Vec<TrashedMod> = vec![];
+    for entry in read_dir(&trash_dir)? {
+        let info_path = entry?.path().join(TRASH_INFO_FILE_NAME);
+        match deserialize_from_json::<TrashedMod>(&info_path) {
+            Ok(entry) => trashed.push(entry),
+            Err(why) => warn!("Couldn't read {}, skipping: {:?}", info_path.display(), why),
+        }
+    }
+    trashed.sort_by_key(|t| Reverse(t.removed_at()));
+    Ok(trashed)
+}
+
+/// Get a mod in the trash by its ID
+///
+/// ## Errors
+///
+/// If the ID isn't valid, there's no entry in the trash with that ID, or we can't read it.
+///
+pub fn get_trashed_mod(config: &Config, id: &str) -> Result<TrashedMod> {
+    // IDs are always a number of milliseconds, anything else could point outside the trash
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("Invalid trash ID: \"{}\"", id));
+    }
+    let info_path = TrashedMod::trash_dir(config)
+        .join(id)
+        .join(TRASH_INFO_FILE_NAME);
+    if !info_path.is_file() {
+        return Err(anyhow!("{} isn't in the trash", id));
+    }
+    let trashed: TrashedMod = deserialize_from_json(&info_path)?;
+    if trashed.id != id {
+        return Err(anyhow!("The trash entry at {} is invalid", id));
+    }
+    Ok(trashed)
+}
+
+/// Restore a mod from the trash, putting it back where it was if we can.
+/// If the original folder can't be used the mod is put in the `Mods` folder.
+///
+/// ## Returns
+///
+/// The restored mod.
+///
+/// ## Errors
+///
+/// If the entry doesn't exist, is a leftover (see [restore_trashed_leftover]), the mod is installed again already,
+/// or we can't move its files back.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use owmods_core::trash::{get_trashed_mods, restore_trashed_mod};
+/// use owmods_core::db::LocalDatabase;
+/// use owmods_core::config::Config;
+///
+/// let config = Config::get(None).unwrap();
+/// let local_db = LocalDatabase::fetch(&config.owml_path).unwrap();
+///
+/// let latest = get_trashed_mods(&config).unwrap().into_iter().next().unwrap();
+/// let restored = restore_trashed_mod(&config, &local_db, &latest.id).unwrap();
+///
+/// println!("Restored {}", restored.manifest.name);
+/// ```
+///
+pub fn restore_trashed_mod(
+    config: &Config,
+    local_db: &LocalDatabase,
+    id: &str,
+) -> Result<LocalMod> {
+    let trashed = get_trashed_mod(config, id)?;
+    if trashed.leftover {
+        return Err(anyhow!("{} isn't a mod", trashed.name));
+    }
+    if local_db.get_mod(&trashed.unique_name).is_some() {
+        return Err(anyhow!(
+            "{} is already installed, uninstall it before restoring",
+            trashed.name
+        ));
+    }
+    let original_path = PathBuf::from(&trashed.original_path);
+    let target_path =
+        if !original_path.exists() && original_path.parent().map(|p| p.is_dir()).unwrap_or(false) {
+            original_path
+        } else {
+            PathBuf::from(&config.owml_path)
+                .join("Mods")
+                .join(&trashed.unique_name)
+        };
+    if target_path.exists() {
+        return Err(anyhow!(
+            "Can't restore {}, {} already exists",
+            trashed.name,
+            target_path.display()
+        ));
+    }
+    if let Some(parent) = target_path.parent() {
+        create_dir_all(parent)?;
+    }
+    move_dir(&trashed.files_path(config), &target_path)?;
+    remove_dir_all(trashed.path(config))?;
+    info!("Restored {} from the trash", trashed.name);
+    LocalDatabase::read_local_mod(&target_path.join("manifest.json"))
+}
+
+/// Restore a leftover removed by [crate::clean::clean_issue] from the trash, putting it back where it was.
+///
+/// ## Returns
+///
+/// The path the leftover was restored to.
+///
+/// ## Errors
+///
+/// If the entry doesn't exist, isn't a leftover, something is in its original place already, or we can't move it back.
+///
+pub fn restore_trashed_leftover(config: &Config, id: &str) -> Result<PathBuf> {
+    let trashed = get_trashed_mod(config, id)?;
+    if !trashed.leftover {
+        return Err(anyhow!("{} is a mod, restore it as one", trashed.name));
+    }
+    let target_path = PathBuf::from(&trashed.original_path);
+    if target_path.exists() {
+        return Err(anyhow!(
+            "Can't restore {}, {} already exists",
+            trashed.name,
+            target_path.display()
+        ));
+    }
+    if let Some(parent) = target_path.parent() {
+        create_dir_all(parent)?;
+    }
+    move_path(&trashed.files_path(config), &target_path)?;
+    remove_dir_all(trashed.path(config))?;
+    info!("Restored {} from the trash", trashed.name);
+    Ok(target_path)
+}
+
+/// Permanently delete a mod in the trash
+///
+/// ## Errors
+///
+/// If the entry doesn't exist or we can't delete it.
+///
+pub fn purge_trashed_mod(config: &Config, id: &str) -> Result<()> {
+    let trashed = get_trashed_mod(config, id)?;
+    remove_dir_all(trashed.path(config))?;
+    Ok(())
+}
+
+/// Permanently delete every mod in the trash
+///
+/// ## Returns
+///
+/// The number of mods that were deleted.
+///
+/// ## Errors
+///
+/// If we can't delete the trash folder.
+///
+pub fn empty_trash(config: &Config) -> Result<usize> {
+    let count = get_trashed_mods(config)?.len();
+    let trash_dir = TrashedMod::trash_dir(config);
+    if trash_dir.is_dir() {
+        remove_dir_all(trash_dir)?;
+    }
+    Ok(count)
+}
+
+/// Permanently delete mods that have been in the trash for longer than [Config::trash_max_age_days].
+/// If that's set to `0` nothing is deleted.
+///
+/// ## Returns
+///
+/// The entries that were deleted.
+///
+/// ## Errors
+///
+/// If we can't read the trash or delete an entry.
+///
+pub fn purge_old_trash(config: &Config) -> Result<Vec<TrashedMod>> {
+    if config.trash_max_age_days == 0 {
+        return Ok(vec![]);
+    }
+    let max_age = Duration::from_secs(config.trash_max_age_days as u64 * SECONDS_PER_DAY);
+    let mut purged: Vec<TrashedMod> = vec![];
+    for trashed in get_trashed_mods(config)? {
+        if trashed.age() > max_age {
+            debug!("Purging {} ({}) from the trash", trashed.name, trashed.id);
+            remove_dir_all(trashed.path(config))?;
+            purged.push(trashed);
+        }
+    }
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{file::copy_dir_all, test_utils::TestContext};
+
+    use super::*;
+
+    fn trash_time_saver(ctx: &mut TestContext) -> TrashedMod {
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        let trashed = trash_mod(&ctx.config, &local_mod).unwrap();
+        ctx.fetch_local_db();
+        trashed
+    }
+
+    #[test]
+    fn test_trash_mod() {
+        let mut ctx = TestContext::new();
+        let trashed = trash_time_saver(&mut ctx);
+        assert!(ctx.local_db.get_mod("Bwc9876.TimeSaver").is_none());
+        assert!(trashed
+            .files_path(&ctx.config)
+            .join("config.json")
+            .is_file());
+        let all = get_trashed_mods(&ctx.config).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].unique_name, "Bwc9876.TimeSaver");
+        assert_eq!(all[0].original_path, trashed.original_path);
+    }
+
+    #[test]
+    fn test_get_trashed_mods_newest_first() {
+        let mut ctx = TestContext::new();
+        let trashed = trash_time_saver(&mut ctx);
+        // "9000" sorts after the real ID as a string but was removed long before it
+        let mut old = trashed.clone();
+        old.id = "9000".to_string();
+        create_dir_all(old.path(&ctx.config)).unwrap();
+        serialize_to_json(
+            &old,
+            &old.path(&ctx.config).join(TRASH_INFO_FILE_NAME),
+            false,
+        )
+        .unwrap();
+        let all = get_trashed_mods(&ctx.config).unwrap();
+        assert_eq!(all[0].id, trashed.id);
+        assert_eq!(all[1].id, "9000");
+    }
+
+    #[test]
+    fn test_trash_leftover() {
+        let ctx = TestContext::new();
+        let folder = ctx.join_mods_folder("Orphan");
+        create_dir_all(&folder).unwrap();
+        fs::write(folder.join("Orphan.dll"), "").unwrap();
+        let file = ctx.join_mods_folder("Leftover.zip");
+        fs::write(&file, "zip").unwrap();
+        let trashed_folder = trash_leftover(&ctx.config, &folder, "Orphan").unwrap();
+        let trashed_file = trash_leftover(&ctx.config, &file, "Leftover.zip").unwrap();
+        assert!(!folder.exists());
+        assert!(!file.exists());
+        assert!(trashed_folder.leftover);
+        assert_eq!(trashed_file.unique_name, "Leftover.zip");
+        assert!(restore_trashed_mod(&ctx.config, &ctx.local_db, &trashed_file.id).is_err());
+        assert_eq!(
+            restore_trashed_leftover(&ctx.config, &trashed_folder.id).unwrap(),
+            folder
+        );
+        assert_eq!(
+            restore_trashed_leftover(&ctx.config, &trashed_file.id).unwrap(),
+            file
+        );
+        assert!(folder.join("Orphan.dll").is_file());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "zip");
+        assert!(get_trashed_mods(&ctx.config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_trashed_leftover_mod() {
+        let mut ctx = TestContext::new();
+        let trashed = trash_time_saver(&mut ctx);
+        assert!(restore_trashed_leftover(&ctx.config, &trashed.id).is_err());
+        assert!(trashed.files_path(&ctx.config).is_dir());
+    }
+
+    #[test]
+    fn test_get_trashed_mod_invalid_id() {
+        let mut ctx = TestContext::new();
+        let trashed = trash_time_saver(&mut ctx);
+        for id in ["", "..", "../trash", "/tmp", "12/34"] {
+            assert!(get_trashed_mod(&ctx.config, id).is_err(), "{id}");
+            assert!(purge_trashed_mod(&ctx.config, id).is_err(), "{id}");
+        }
+        assert!(trashed.path(&ctx.config).is_dir());
+        assert_eq!(
+            get_trashed_mod(&ctx.config, &trashed.id)
+                .unwrap()
+                .unique_name,
+            "Bwc9876.TimeSaver"
+        );
+    }
+
+    #[test]
+    fn test_restore_trashed_mod() {
+        let mut ctx = TestContext::new();
+        let local_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        crate::toggle::toggle_mod("Bwc9876.TimeSaver", &ctx.local_db, false, false).unwrap();
+        let trashed = trash_mod(&ctx.config, &local_mod).unwrap();
+        ctx.fetch_local_db();
+        let restored = restore_trashed_mod(&ctx.config, &ctx.local_db, &trashed.id).unwrap();
+        assert_eq!(restored.mod_path, local_mod.mod_path);
+        assert!(!restored.enabled);
+        assert!(get_trashed_mods(&ctx.config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_trashed_mod_installed() {
+        let mut ctx = TestContext::new();
+        let trashed = trash_time_saver(&mut ctx);
+        ctx.install_test_zip("Bwc9876.TimeSaver.zip", true);
+        assert!(restore_trashed_mod(&ctx.config, &ctx.local_db, &trashed.id).is_err());
+        assert!(trashed.files_path(&ctx.config).is_dir());
+    }
+
+    #[test]
+    fn test_purge_and_empty_trash() {
+        let mut ctx = TestContext::new();
+        let trashed = trash_time_saver(&mut ctx);
+        purge_trashed_mod(&ctx.config, &trashed.id).unwrap();
+        assert!(get_trashed_mods(&ctx.config).unwrap().is_empty());
+        trash_time_saver(&mut ctx);
+        trash_time_saver(&mut ctx);
+        assert_eq!(empty_trash(&ctx.config).unwrap(), 2);
+        assert!(get_trashed_mods(&ctx.config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_purge_old_trash() {
+        let mut ctx = TestContext::new();
+        let trashed = trash_time_saver(&mut ctx);
+        let mut old = trashed.clone();
+        old.id = "1000".to_string();
+        copy_dir_all(&trashed.path(&ctx.config), &old.path(&ctx.config)).unwrap();
+        serialize_to_json(
+            &old,
+            &old.path(&ctx.config).join(TRASH_INFO_FILE_NAME),
+            false,
+        )
+        .unwrap();
+        ctx.config.trash_max_age_days = 0;
+        assert!(purge_old_trash(&ctx.config).unwrap().is_empty());
+        ctx.config.trash_max_age_days = 30;
+        let purged = purge_old_trash(&ctx.config).unwrap();
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].id, "1000");
+        assert!(trashed.path(&ctx.config).is_dir());
+    }
+}
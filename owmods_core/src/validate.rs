@@ -1,4 +1,8 @@
-use std::path::PathBuf;
+use std::{
+    fmt::Display,
+    fs::read_to_string,
+    path::{Component, Path, PathBuf},
+};
 
 use anyhow::Result;
 use log::info;
@@ -7,11 +11,12 @@ use typeshare::typeshare;
 
 use crate::{
     analytics::{send_analytics_event, AnalyticsEventName},
+    autoremove::DependencyMarks,
     config::Config,
     db::{LocalDatabase, RemoteDatabase},
     download::install_mods_parallel,
     mods::local::LocalMod,
-    toggle::toggle_mod,
+    toggle::toggle_mod_with_snapshot,
 };
 
 /// Represents an error with a [LocalMod]
@@ -19,8 +24,8 @@ use crate::{
 #[derive(Serialize, Clone)]
 #[serde(tag = "errorType", content = "payload")]
 pub enum ModValidationError {
-    /// The mod's manifest was invalid, contains the error encountered when loading it
-    InvalidManifest(String),
+    /// The mod's manifest was invalid, contains details on the error encountered when loading it
+    InvalidManifest(ManifestError),
     /// The mod is missing a dependency that needs to be installed, contains the unique name of the missing dep
     MissingDep(String),
     /// A dependency of the mod is disabled, contains the unique name of the disabled dep
@@ -33,6 +38,151 @@ pub enum ModValidationError {
     DuplicateMod(String),
     /// The mod is outdated, contains the newest version
     Outdated(String),
+    /// The prepatcher the mod specifies in its `manifest.json` doesn't exist, contains the path to the patcher specified by the mod
+    MissingPatcher(String),
+    /// A path in the mod's `pathsToPreserve` is absolute or leads outside the mod's folder, contains the offending path
+    InvalidPreservePath(String),
+}
+
+/// Details on why a mod's `manifest.json` couldn't be loaded
+#[typeshare]
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestError {
+    /// The path to the manifest that failed to load
+    pub file: String,
+    /// What went wrong, without any location info
+    pub message: String,
+    /// The line the error occurred on (starting at 1), if the error came from parsing the JSON
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// The column the error occurred on (starting at 1), if the error came from parsing the JSON
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    /// The contents of the line the error occurred on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// A suggestion on how to fix the error, if we can guess what's wrong
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+const MAX_SNIPPET_LEN: usize = 120;
+
+impl ManifestError {
+    /// Create a manifest error from an error encountered when reading a manifest.
+    /// If the error was caused by the JSON failing to parse we'll
+    /// grab the location, the offending line, and try to come up with a hint.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use owmods_core::validate::ManifestError;
+    /// use owmods_core::db::LocalDatabase;
+    /// use std::path::PathBuf;
+    ///
+    /// let manifest_path = PathBuf::from("/path/to/manifest.json");
+    /// if let Err(why) = LocalDatabase::read_local_mod(&manifest_path) {
+    ///     let err = ManifestError::from_error(&manifest_path, &why);
+    ///     println!("{}", err);
+    /// }
+    /// ```
+    ///
+    pub fn from_error(manifest_path: &Path, err: &anyhow::Error) -> Self {
+        let file = manifest_path.to_string_lossy().to_string();
+        if let Some(json_err) = err.downcast_ref::<serde_json::Error>() {
+            let contents = read_to_string(manifest_path).unwrap_or_default();
+            Self::from_json_error(file, json_err, &contents)
+        } else {
+            Self {
+                file,
+                message: format!("{:#}", err),
+                line: None,
+                column: None,
+                snippet: None,
+                hint: None,
+            }
+        }
+    }
+
+    fn from_json_error(file: String, err: &serde_json::Error, contents: &str) -> Self {
+        let full = err.to_string();
+        // serde_json adds " at line X column Y" to the message, we store those separately
+        let message = match full.rfind(" at line ") {
+            Some(idx) if err.line() != 0 => full[..idx].to_string(),
+            _ => full,
+        };
+        let (line, column, snippet) = if err.line() == 0 {
+            (None, None, None)
+        } else {
+            let snippet = contents.lines().nth(err.line() - 1).map(|l| {
+                l.trim_end()
+                    .chars()
+                    .take(MAX_SNIPPET_LEN)
+                    .collect::<String>()
+            });
+            (Some(err.line() as u32), Some(err.column() as u32), snippet)
+        };
+        let hint = Self::get_hint(err, &message, snippet.as_deref());
+        Self {
+            file,
+            message,
+            line,
+            column,
+            snippet,
+            hint,
+        }
+    }
+
+    fn get_hint(err: &serde_json::Error, message: &str, snippet: Option<&str>) -> Option<String> {
+        if let Some(field) = message
+            .strip_prefix("missing field `")
+            .and_then(|f| f.strip_suffix('`'))
+        {
+            return Some(format!("missing {field}, add \"{field}\" to the manifest"));
+        }
+        let bad_char = snippet
+            .zip(err.column().checked_sub(1))
+            .and_then(|(s, c)| s.chars().nth(c));
+        let hint = match err.classify() {
+            serde_json::error::Category::Eof => {
+                "unexpected end of file, check for unclosed braces, brackets, or quotes"
+            }
+            _ if message == "trailing comma" => {
+                "trailing comma, remove the comma after the last item in the object or array"
+            }
+            _ if matches!(bad_char, Some('/')) => "JSON doesn't support comments, remove them",
+            _ if matches!(bad_char, Some('\'')) => {
+                "strings must use double quotes, not single quotes"
+            }
+            _ if message == "key must be a string" => "keys must be wrapped in double quotes",
+            _ if message.starts_with("expected `,` or") => {
+                "missing comma, items in objects and arrays must be separated by commas"
+            }
+            _ if message.starts_with("control character") => {
+                "strings can't contain raw newlines or tabs, use \\n or \\t instead"
+            }
+            _ if message.ends_with("expected struct ModManifest") => {
+                "the manifest must be a JSON object (wrapped in {})"
+            }
+            _ => return None,
+        };
+        Some(hint.to_string())
+    }
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, ":{}:{}", line, column)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(hint) = self.hint.as_ref() {
+            write!(f, " (hint: {})", hint)?;
+        }
+        Ok(())
+    }
 }
 
 fn check_mod_dll(local_mod: &LocalMod) -> Option<ModValidationError> {
@@ -48,6 +198,48 @@ fn check_mod_dll(local_mod: &LocalMod) -> Option<ModValidationError> {
     }
 }
 
+fn check_mod_patcher(local_mod: &LocalMod) -> Option<ModValidationError> {
+    let patcher_name = local_mod.manifest.patcher.as_ref()?;
+    let patcher_path = PathBuf::from(&local_mod.mod_path).join(patcher_name);
+    if patcher_path.is_file() {
+        None
+    } else {
+        Some(ModValidationError::MissingPatcher(patcher_name.to_string()))
+    }
+}
+
+// A preserve path is only valid if it's relative and never climbs above the mod's folder.
+// Paths that resolve to the mod folder itself are also rejected, as they'd preserve every file.
+fn is_path_inside_mod(path: &Path) -> bool {
+    let mut depth: usize = 0;
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => return false,
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if depth == 0 {
+                    return false;
+                }
+                depth -= 1;
+            }
+            Component::Normal(_) => depth += 1,
+        }
+    }
+    depth > 0
+}
+
+fn check_mod_paths_to_preserve(local_mod: &LocalMod) -> Vec<ModValidationError> {
+    let mut errors: Vec<ModValidationError> = vec![];
+    if let Some(paths) = &local_mod.manifest.paths_to_preserve {
+        for path in paths.iter() {
+            if !is_path_inside_mod(Path::new(path)) {
+                errors.push(ModValidationError::InvalidPreservePath(path.clone()));
+            }
+        }
+    }
+    errors
+}
+
 fn check_mod_deps(local_mod: &LocalMod, db: &LocalDatabase) -> Vec<ModValidationError> {
     let mut errors: Vec<ModValidationError> = vec![];
     if let Some(deps) = &local_mod.manifest.dependencies {
@@ -91,6 +283,10 @@ pub fn check_mod(local_mod: &LocalMod, db: &LocalDatabase) -> Vec<ModValidationE
     if let Some(dll_error) = check_mod_dll(local_mod) {
         errors.push(dll_error);
     }
+    if let Some(patcher_error) = check_mod_patcher(local_mod) {
+        errors.push(patcher_error);
+    }
+    errors.extend(check_mod_paths_to_preserve(local_mod));
     errors
 }
 
@@ -112,7 +308,7 @@ pub async fn fix_deps(
         match error {
             ModValidationError::DisabledDep(unique_name) => {
                 info!("Enabling {}", unique_name);
-                toggle_mod(unique_name, db, true, true)?;
+                toggle_mod_with_snapshot(unique_name, config, db, true, true)?;
             }
             ModValidationError::MissingDep(unique_name) => {
                 info!("Marking {} For Install", unique_name);
@@ -125,6 +321,7 @@ pub async fn fix_deps(
         info!("Installing {} Missing Dependencies", missing.len());
     }
     install_mods_parallel(missing.clone(), config, remote_db, db).await?;
+    DependencyMarks::mark_dependencies(config, &missing)?;
     for missing_mod in missing {
         send_analytics_event(AnalyticsEventName::ModRequiredInstall, &missing_mod).await;
     }
@@ -134,7 +331,10 @@ pub async fn fix_deps(
 #[cfg(test)]
 mod tests {
 
-    use crate::mods::local::UnsafeLocalMod;
+    use crate::{
+        mods::local::{ModManifest, UnsafeLocalMod},
+        test_utils::TestContext,
+    };
 
     use super::*;
 
@@ -300,4 +500,109 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_check_mod_patcher_not_specified() {
+        let mod_a = LocalMod::get_test(0);
+        let error = check_mod_patcher(&mod_a);
+        assert!(error.is_none());
+    }
+
+    #[test]
+    fn test_check_mod_patcher_not_found() {
+        let mut mod_a = LocalMod::get_test(0);
+        mod_a.mod_path = "/not/real/".to_string();
+        mod_a.manifest.patcher = Some("Patcher.exe".to_string());
+        let error = check_mod_patcher(&mod_a);
+        assert!(error.is_some());
+        match error.unwrap() {
+            ModValidationError::MissingPatcher(path) => {
+                assert_eq!(path, "Patcher.exe");
+            }
+            _ => {
+                panic!("Wrong Error Thrown!");
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_mod_patcher_found() {
+        let mut ctx = TestContext::new();
+        let mut new_mod = ctx.install_test_zip("Bwc9876.TimeSaver.zip", false);
+        new_mod.manifest.patcher = Some("manifest.json".to_string());
+        let error = check_mod_patcher(&new_mod);
+        assert!(error.is_none());
+    }
+
+    #[test]
+    fn test_check_paths_to_preserve_valid() {
+        let mut mod_a = LocalMod::get_test(0);
+        mod_a.manifest.paths_to_preserve = Some(vec![
+            "save.json".to_string(),
+            "SaveFolder/".to_string(),
+            "./Folder/../other.json".to_string(),
+        ]);
+        let errors = check_mod_paths_to_preserve(&mod_a);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_check_paths_to_preserve_invalid() {
+        let mut mod_a = LocalMod::get_test(0);
+        mod_a.manifest.paths_to_preserve = Some(vec![
+            "/etc/passwd".to_string(),
+            "../OtherMod/config.json".to_string(),
+            "Folder/../../escape.json".to_string(),
+            ".".to_string(),
+            "".to_string(),
+        ]);
+        let errors = check_mod_paths_to_preserve(&mod_a);
+        assert_eq!(errors.len(), 5);
+        assert!(errors
+            .iter()
+            .all(|e| matches!(e, ModValidationError::InvalidPreservePath(_))));
+    }
+
+    fn get_manifest_error(contents: &str) -> ManifestError {
+        let err = serde_json::from_str::<ModManifest>(contents).err().unwrap();
+        ManifestError::from_json_error("manifest.json".to_string(), &err, contents)
+    }
+
+    #[test]
+    fn test_manifest_error_trailing_comma() {
+        let err = get_manifest_error("{\n  \"name\": \"Test\",\n}");
+        assert_eq!(err.message, "trailing comma");
+        assert_eq!(err.line, Some(3));
+        assert_eq!(err.snippet.as_deref(), Some("}"));
+        assert!(err.hint.unwrap().starts_with("trailing comma"));
+    }
+
+    #[test]
+    fn test_manifest_error_missing_field() {
+        let err = get_manifest_error(
+            "{\"name\": \"Test\", \"author\": \"Test\", \"version\": \"1.0.0\", \"owmlVersion\": \"2.0.0\"}",
+        );
+        assert_eq!(err.message, "missing field `uniqueName`");
+        assert!(err.hint.unwrap().starts_with("missing uniqueName"));
+    }
+
+    #[test]
+    fn test_manifest_error_comment() {
+        let err = get_manifest_error("{\n  // Comment\n  \"name\": \"Test\"\n}");
+        assert_eq!(err.line, Some(2));
+        assert_eq!(err.snippet.as_deref(), Some("  // Comment"));
+        assert!(err.hint.unwrap().contains("comments"));
+    }
+
+    #[test]
+    fn test_manifest_error_display() {
+        let err = get_manifest_error("{\n  \"name\": \"Test\",\n}");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "manifest.json:3:1: trailing comma (hint: {})",
+                err.hint.unwrap()
+            )
+        );
+    }
 }
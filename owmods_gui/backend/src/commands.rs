@@ -10,6 +10,7 @@ use log::error;
 use owmods_core::{
     alerts::{fetch_alert, Alert},
     analytics::{send_analytics_event, AnalyticsEventName},
+    clean::CleanIssue,
     config::Config,
     constants::OWML_UNIQUE_NAME,
     db::{LocalDatabase, RemoteDatabase},
@@ -18,6 +19,17 @@ use owmods_core::{
         install_mods_parallel,
     },
     game::launch_game,
+    game_logs::{read_game_log, GameLogWriter},
+    io::{
+        decode_share_code, import_mod_list, preview_import, read_import_list, ExportFormat,
+        ImportPreview,
+    },
+    log_analysis::LogAnalysis,
+    log_export::{write_log_export, LogExportFormat},
+    log_sinks::{LogDispatcher, LogSink},
+    migrate::{adopt_mods, scan_migration_source, MigrationMode, MigrationResult, MigrationScan},
+    mod_settings::{reset_mod_config, ModSetting, ModSettingValue},
+    modpack::ModpackManifest,
     mods::{
         local::{LocalMod, UnsafeLocalMod},
         remote::RemoteMod,
@@ -26,8 +38,14 @@ use owmods_core::{
     owml::OWMLConfig,
     progress::bars::{ProgressBar, ProgressBars},
     protocol::{ProtocolInstallType, ProtocolPayload},
-    remove::{remove_failed_mod, remove_mod},
-    socket::{LogServer, SocketMessageType},
+    remove::{remove_failed_mod, remove_mod_to_trash, RemoveResult},
+    snapshots::{
+        delete_snapshot, get_snapshot, get_snapshots, restore_snapshot, take_snapshot,
+        SettingsSnapshot, SnapshotReason,
+    },
+    socket::{LogServer, SocketCommand, SocketMessageType},
+    toggle::EnableModResult,
+    trash::TrashedMod,
     updates::check_mod_needs_update,
     validate::fix_deps,
 };
@@ -269,18 +287,46 @@ pub async fn toggle_mod(
     recursive: bool,
     state: tauri::State<'_, State>,
 ) -> Result<Vec<String>> {
+    let config = state.config.read().await;
     let db = state.local_db.read().await;
-    let show_warnings_for = owmods_core::toggle::toggle_mod(unique_name, &db, enabled, recursive)?;
+    let show_warnings_for = owmods_core::toggle::toggle_mod_with_snapshot(
+        unique_name,
+        &config,
+        &db,
+        enabled,
+        recursive,
+    )?;
     Ok(show_warnings_for)
 }
 
+#[tauri::command]
+pub async fn enable_mod(
+    unique_name: &str,
+    recursive: bool,
+    disable_conflicting: bool,
+    state: tauri::State<'_, State>,
+) -> Result<EnableModResult> {
+    let config = state.config.read().await;
+    let db = state.local_db.read().await;
+    let result = owmods_core::toggle::enable_mod_with_snapshot(
+        unique_name,
+        &config,
+        &db,
+        recursive,
+        disable_conflicting,
+    )?;
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn toggle_all(enabled: bool, state: tauri::State<'_, State>) -> Result<Vec<String>> {
+    let config = state.config.read().await;
     let local_db = state.local_db.read().await;
     let mut show_warnings_for: Vec<String> = vec![];
     for local_mod in local_db.valid() {
-        show_warnings_for.extend(owmods_core::toggle::toggle_mod(
+        show_warnings_for.extend(owmods_core::toggle::toggle_mod_with_snapshot(
             &local_mod.manifest.unique_name,
+            &config,
             &local_db,
             enabled,
             false,
@@ -360,16 +406,80 @@ pub async fn install_zip(
 #[tauri::command]
 pub async fn uninstall_mod(
     unique_name: &str,
+    recursive: bool,
     state: tauri::State<'_, State>,
     _handle: tauri::AppHandle,
-) -> Result<Vec<String>> {
+) -> Result<RemoveResult> {
+    let config = state.config.read().await;
     let db = state.local_db.read().await;
     let local_mod = db
         .get_mod(unique_name)
         .ok_or_else(|| anyhow!("Mod {} not found", unique_name))?;
-    let warnings = remove_mod(local_mod, &db, false)?;
+    let result = remove_mod_to_trash(local_mod, &config, &db, recursive)?;
 
-    Ok(warnings)
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn scan_mods_folder(state: tauri::State<'_, State>) -> Result<Vec<CleanIssue>> {
+    let config = state.config.read().await;
+    let local_db = state.local_db.read().await;
+    let issues = owmods_core::clean::scan_mods_folder(&config, &local_db)?;
+    Ok(issues)
+}
+
+#[tauri::command]
+pub async fn clean_mods_folder(
+    state: tauri::State<'_, State>,
+    handle: tauri::AppHandle,
+) -> Result<Vec<CleanIssue>> {
+    let config = state.config.read().await;
+    let local_db = state.local_db.read().await;
+    let (cleaned, _) = owmods_core::clean::clean_mods_folder(&config, &local_db)?;
+    handle
+        .typed_emit_all(&Event::RequestReload("LOCAL".to_string()))
+        .ok();
+    Ok(cleaned)
+}
+
+#[tauri::command]
+pub async fn get_trashed_mods(state: tauri::State<'_, State>) -> Result<Vec<TrashedMod>> {
+    let config = state.config.read().await;
+    let trashed = owmods_core::trash::get_trashed_mods(&config)?;
+    Ok(trashed)
+}
+
+#[tauri::command]
+pub async fn restore_trashed_mod(
+    id: String,
+    state: tauri::State<'_, State>,
+    handle: tauri::AppHandle,
+) -> Result {
+    let config = state.config.read().await;
+    let local_db = state.local_db.read().await;
+    if owmods_core::trash::get_trashed_mod(&config, &id)?.leftover {
+        owmods_core::trash::restore_trashed_leftover(&config, &id)?;
+    } else {
+        owmods_core::trash::restore_trashed_mod(&config, &local_db, &id)?;
+    }
+    handle
+        .typed_emit_all(&Event::RequestReload("LOCAL".to_string()))
+        .ok();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn purge_trashed_mod(id: String, state: tauri::State<'_, State>) -> Result {
+    let config = state.config.read().await;
+    owmods_core::trash::purge_trashed_mod(&config, &id)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn empty_trash(state: tauri::State<'_, State>) -> Result {
+    let config = state.config.read().await;
+    owmods_core::trash::empty_trash(&config)?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -571,15 +681,18 @@ pub async fn update_mod(
         )
         .await
     } else {
-        install_mod_from_db(
-            &unique_name.to_string(),
+        // Not install_mod_from_db, updating a dependency shouldn't make it explicitly installed
+        let res = install_mods_parallel(
+            vec![unique_name.to_string()],
             &config,
             &remote_db,
             &local_db,
-            false,
-            false,
         )
-        .await
+        .await;
+        if res.is_ok() {
+            send_analytics_event(AnalyticsEventName::ModUpdate, unique_name).await;
+        }
+        res.map(|_| ())
     };
     mark_mod_busy(unique_name, false, true, &state, &handle).await;
     res?;
@@ -638,20 +751,21 @@ pub async fn start_logs(state: tauri::State<'_, State>, handle: tauri::AppHandle
     let game_logs = state.game_log.read().await;
     let gui_config = state.gui_config.read().await;
     let config = state.config.read().await.clone();
+    // Replays don't have a log server to send logs to
+    let live_port = game_logs.keys().find_map(|&k| u16::try_from(k).ok());
     if gui_config.no_log_server {
         drop(gui_config);
         launch_game(&config, true, None).await?;
         return Ok(());
-    } else if gui_config.log_multi_window || game_logs.keys().count() == 0 {
-        drop(game_logs);
-        drop(gui_config);
-        make_log_window(&handle).await?;
-    } else {
+    } else if let Some(port) = live_port.filter(|_| !gui_config.log_multi_window) {
         drop(gui_config);
         let config = state.config.read().await.clone();
-        let port = *game_logs.keys().next().unwrap_or(&0);
         drop(game_logs);
         launch_game(&config, false, Some(&port)).await?;
+    } else {
+        drop(game_logs);
+        drop(gui_config);
+        make_log_window(&handle, None).await?;
     }
     Ok(())
 }
@@ -682,12 +796,22 @@ pub async fn run_game(
 
     let log_server = LogServer::new(0).await?;
     let port = log_server.port;
+    let key = LogPort::from(port);
 
     {
         let mut game_log = state.game_log.write().await;
-        game_log.insert(port, LogData::new(port, &handle)?);
+        game_log.insert(
+            key,
+            LogData::new(key, &handle).with_commands(log_server.command_sender()),
+        );
     }
 
+    let mut sinks = LogDispatcher::default();
+    sinks.add(
+        GameLogWriter::for_session(port)
+            .map_err(|e| anyhow!("Couldn't create log file: {:?}", e))?,
+    );
+
     let close_handle = handle.clone();
 
     window.on_window_event(move |e| {
@@ -696,22 +820,24 @@ pub async fn run_game(
             async_runtime::spawn(async move {
                 let state = handle.state::<State>();
                 let mut logs = state.game_log.write().await;
-                logs.remove(&port);
+                logs.remove(&key);
             });
         }
     });
 
-    window.typed_emit(&Event::GameStart(port)).ok();
+    window.typed_emit(&Event::GameStart(key)).ok();
 
     let (tx, mut rx) = mpsc::channel(32);
 
     let log_handler = async {
         while let Some(msg) = rx.recv().await {
+            sinks.dispatch(&msg);
             let mut game_log = state.game_log.write().await;
-            if let Some(log_data) = game_log.get_mut(&port) {
-                log_data.take_message(msg);
+            if let Some(log_data) = game_log.get_mut(&key) {
+                log_data.write(&msg)?;
             }
         }
+        sinks.flush();
         Ok(())
     };
 
@@ -724,6 +850,92 @@ pub async fn run_game(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn send_game_command(
+    port: LogPort,
+    command: SocketCommand,
+    state: tauri::State<'_, State>,
+) -> Result<usize> {
+    let game_log = state.game_log.read().await;
+    let log_data = game_log
+        .get(&port)
+        .ok_or_else(|| anyhow!("Log Server Not Running"))?;
+    Ok(log_data.send_command(&command)?)
+}
+
+#[tauri::command]
+pub async fn analyze_game_log(
+    port: LogPort,
+    state: tauri::State<'_, State>,
+) -> Result<LogAnalysis> {
+    let local_db = state.local_db.read().await;
+    let game_log = state.game_log.read().await;
+    let log_data = game_log
+        .get(&port)
+        .ok_or_else(|| anyhow!("Log Server Not Running"))?;
+    Ok(log_data.analyze(&local_db))
+}
+
+#[tauri::command]
+pub async fn export_game_log(
+    port: LogPort,
+    path: String,
+    filtered: bool,
+    state: tauri::State<'_, State>,
+) -> Result {
+    let game_log = state.game_log.read().await;
+    let log_data = game_log
+        .get(&port)
+        .ok_or_else(|| anyhow!("Log Server Not Running"))?;
+    let path = PathBuf::from(path);
+    write_log_export(
+        &path,
+        &log_data.export_messages(filtered),
+        LogExportFormat::from_path(&path),
+        "Outer Wilds Game Logs",
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn open_game_log(
+    path: String,
+    state: tauri::State<'_, State>,
+    handle: tauri::AppHandle,
+) -> Result {
+    let messages = read_game_log(&PathBuf::from(path))?;
+    let port = {
+        let mut game_log = state.game_log.write().await;
+        // Live logs are keyed by their port, so keys past any port are free for replays
+        let port = (LogPort::from(u16::MAX) + 1..)
+            .find(|p| !game_log.contains_key(p))
+            .ok_or_else(|| anyhow!("Too many logs open"))?;
+        let mut log_data = LogData::new(port, &handle);
+        for logged in messages {
+            log_data.take_message(logged.message);
+        }
+        log_data.process_emit_queue();
+        game_log.insert(port, log_data);
+        port
+    };
+
+    let window = make_log_window(&handle, Some(port)).await?;
+
+    let close_handle = handle.clone();
+    window.on_window_event(move |e| {
+        if let WindowEvent::CloseRequested { .. } = e {
+            let handle = close_handle.clone();
+            async_runtime::spawn(async move {
+                let state = handle.state::<State>();
+                let mut logs = state.game_log.write().await;
+                logs.remove(&port);
+            });
+        }
+    });
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn clear_logs(port: LogPort, state: tauri::State<'_, State>) -> Result {
     let mut data = state.game_log.write().await;
@@ -759,7 +971,7 @@ pub async fn get_game_message(
     let logs = state.game_log.read().await;
     if let Some(log_data) = logs.get(&port) {
         if let Some(msg) = log_data.get_message(line) {
-            Ok(msg.clone())
+            Ok(msg)
         } else {
             Err(Error(anyhow!("Log Line {line} Not Found")))
         }
@@ -805,6 +1017,131 @@ pub async fn import_mods(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn preview_import_mods(
+    path: String,
+    disable_missing: bool,
+    state: tauri::State<'_, State>,
+) -> Result<ImportPreview> {
+    let local_db = state.local_db.read().await;
+    let remote_db = state.remote_db.read().await;
+    let list = read_import_list(&PathBuf::from(path))?;
+    Ok(preview_import(
+        &list,
+        &local_db,
+        &remote_db,
+        disable_missing,
+    ))
+}
+
+#[tauri::command]
+pub async fn export_share_code(
+    include_versions: bool,
+    state: tauri::State<'_, State>,
+) -> Result<String> {
+    let local_db = state.local_db.read().await;
+    let code =
+        owmods_core::io::export_mods_as(&local_db, ExportFormat::ShareCode, include_versions)?;
+    Ok(code)
+}
+
+#[tauri::command]
+pub async fn preview_import_code(
+    code: String,
+    disable_missing: bool,
+    state: tauri::State<'_, State>,
+) -> Result<ImportPreview> {
+    let local_db = state.local_db.read().await;
+    let remote_db = state.remote_db.read().await;
+    let list = decode_share_code(&code)?;
+    Ok(preview_import(
+        &list,
+        &local_db,
+        &remote_db,
+        disable_missing,
+    ))
+}
+
+#[tauri::command]
+pub async fn import_mods_from_code(
+    code: String,
+    disable_missing: bool,
+    state: tauri::State<'_, State>,
+) -> Result {
+    let local_db = state.local_db.read().await;
+    let remote_db = state.remote_db.read().await;
+    let config = state.config.read().await;
+    let list = decode_share_code(&code)?;
+    import_mod_list(&config, &local_db, &remote_db, &list, disable_missing).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn export_modpack(
+    path: String,
+    include_disabled: bool,
+    include_owml: bool,
+    state: tauri::State<'_, State>,
+) -> Result<ModpackManifest> {
+    let config = state.config.read().await.clone();
+    let local_db = state.local_db.read().await.clone();
+    let path = PathBuf::from(path);
+    let manifest = owmods_core::modpack::export_modpack(
+        &config,
+        &local_db,
+        &path,
+        include_disabled,
+        include_owml,
+    )?;
+    Ok(manifest)
+}
+
+#[tauri::command]
+pub async fn import_modpack(
+    path: String,
+    install_owml: bool,
+    state: tauri::State<'_, State>,
+    handle: tauri::AppHandle,
+) -> Result<ModpackManifest> {
+    let config = state.config.read().await.clone();
+    let local_db = state.local_db.read().await.clone();
+    let path = PathBuf::from(path);
+    let manifest = owmods_core::modpack::import_modpack(&config, &local_db, &path, install_owml)?;
+    handle
+        .typed_emit_all(&Event::RequestReload("LOCAL".to_string()))
+        .ok();
+    Ok(manifest)
+}
+
+#[tauri::command]
+pub async fn scan_migration(path: String, state: tauri::State<'_, State>) -> Result<MigrationScan> {
+    let local_db = state.local_db.read().await;
+    let remote_db = state.remote_db.read().await;
+    let scan = scan_migration_source(&PathBuf::from(path), &local_db, &remote_db)?;
+    Ok(scan)
+}
+
+#[tauri::command]
+pub async fn migrate_mods(
+    path: String,
+    unique_names: Vec<String>,
+    mode: MigrationMode,
+    replace_existing: bool,
+    state: tauri::State<'_, State>,
+    handle: tauri::AppHandle,
+) -> Result<MigrationResult> {
+    let config = state.config.read().await.clone();
+    let local_db = state.local_db.read().await.clone();
+    let remote_db = state.remote_db.read().await.clone();
+    let mut scan = scan_migration_source(&PathBuf::from(path), &local_db, &remote_db)?;
+    scan.mods.retain(|m| unique_names.contains(&m.unique_name));
+    let result = adopt_mods(&config, &local_db, &scan, mode, replace_existing)?;
+    handle
+        .typed_emit_all(&Event::RequestReload("LOCAL".to_string()))
+        .ok();
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn fix_mod_deps(
     unique_name: &str,
@@ -1004,3 +1341,122 @@ pub async fn open_mod_github(unique_name: &str, state: tauri::State<'_, State>)
     open_github(unique_name, &db)?;
     Ok(())
 }
+
+#[tauri::command]
+pub async fn get_mod_settings(
+    unique_name: &str,
+    state: tauri::State<'_, State>,
+) -> Result<Vec<ModSetting>> {
+    let db = state.local_db.read().await;
+    let local_mod = db
+        .get_mod(unique_name)
+        .ok_or_else(|| anyhow!("Mod {} not found", unique_name))?;
+    Ok(owmods_core::mod_settings::get_mod_settings(local_mod)?)
+}
+
+#[tauri::command]
+pub async fn set_mod_setting(
+    unique_name: &str,
+    key: &str,
+    value: ModSettingValue,
+    state: tauri::State<'_, State>,
+) -> Result {
+    let db = state.local_db.read().await;
+    let local_mod = db
+        .get_mod(unique_name)
+        .ok_or_else(|| anyhow!("Mod {} not found", unique_name))?;
+    owmods_core::mod_settings::set_mod_setting(local_mod, key, value)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn reset_mod_settings(
+    unique_name: &str,
+    keys: Vec<String>,
+    state: tauri::State<'_, State>,
+) -> Result {
+    let config = state.config.read().await;
+    let db = state.local_db.read().await;
+    let local_mod = db
+        .get_mod(unique_name)
+        .ok_or_else(|| anyhow!("Mod {} not found", unique_name))?;
+    take_snapshot(&config, local_mod, SnapshotReason::Reset)?;
+    if keys.is_empty() {
+        reset_mod_config(local_mod)?;
+    } else {
+        owmods_core::mod_settings::reset_selected_mod_settings(local_mod, &keys)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_changed_settings(
+    unique_name: &str,
+    snapshot_id: Option<String>,
+    state: tauri::State<'_, State>,
+) -> Result<Vec<ModSetting>> {
+    let config = state.config.read().await;
+    let db = state.local_db.read().await;
+    let local_mod = db
+        .get_mod(unique_name)
+        .ok_or_else(|| anyhow!("Mod {} not found", unique_name))?;
+    let changed = if let Some(snapshot_id) = snapshot_id {
+        get_snapshot(&config, unique_name, &snapshot_id)?.changed_settings(local_mod)?
+    } else {
+        owmods_core::mod_settings::get_changed_settings(local_mod)?
+    };
+    Ok(changed)
+}
+
+#[tauri::command]
+pub async fn get_settings_snapshots(
+    unique_name: &str,
+    state: tauri::State<'_, State>,
+) -> Result<Vec<SettingsSnapshot>> {
+    let config = state.config.read().await;
+    Ok(get_snapshots(&config, unique_name)?)
+}
+
+#[tauri::command]
+pub async fn take_settings_snapshot(unique_name: &str, state: tauri::State<'_, State>) -> Result {
+    let config = state.config.read().await;
+    let db = state.local_db.read().await;
+    let local_mod = db
+        .get_mod(unique_name)
+        .ok_or_else(|| anyhow!("Mod {} not found", unique_name))?;
+    take_snapshot(&config, local_mod, SnapshotReason::Manual)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn restore_settings_snapshot(
+    unique_name: &str,
+    snapshot_id: &str,
+    state: tauri::State<'_, State>,
+    handle: tauri::AppHandle,
+) -> Result {
+    let config = state.config.read().await;
+    {
+        let db = state.local_db.read().await;
+        let local_mod = db
+            .get_mod(unique_name)
+            .ok_or_else(|| anyhow!("Mod {} not found", unique_name))?;
+        restore_snapshot(&config, local_mod, snapshot_id)?;
+    }
+    // The snapshot may have a different enabled state
+    handle
+        .typed_emit_all(&Event::RequestReload("LOCAL".to_string()))
+        .ok();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_settings_snapshot(
+    unique_name: &str,
+    snapshot_id: &str,
+    state: tauri::State<'_, State>,
+) -> Result {
+    let config = state.config.read().await;
+    delete_snapshot(&config, unique_name, snapshot_id)?;
+    Ok(())
+}
@@ -1,8 +1,4 @@
-use std::{
-    fs::File,
-    io::{BufWriter, Write},
-    time::{Instant, SystemTime, UNIX_EPOCH},
-};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, Result};
 use log::error;
@@ -10,13 +6,15 @@ use owmods_core::{
     alerts::get_warnings,
     config::Config,
     db::LocalDatabase,
-    file::{create_all_parents, get_app_path},
+    log_analysis::{LogAnalysis, LogAnalyzer},
+    log_export::ExportedMessage,
+    log_sinks::LogSink,
     search::matches_query,
-    socket::{SocketMessage, SocketMessageType},
+    socket::{CommandSender, SocketCommand, SocketMessage, SocketMessageType},
 };
 use serde::{Deserialize, Serialize};
 use tauri::{api::dialog, AppHandle, Window, WindowBuilder};
-use time::{macros::format_description, OffsetDateTime};
+use time::{macros::format_description, OffsetDateTime, UtcOffset};
 use typeshare::typeshare;
 
 use crate::{
@@ -31,11 +29,21 @@ pub struct GameMessage {
     pub message: SocketMessage,
     pub amount: u32,
     pub timestamp: String,
+    /// The client this message came from, only set once more than one client has connected to this log
+    /// so the UI can tell sessions apart (for example when the game restarts after a crash)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<u32>,
 }
 
 impl GameMessage {
-    fn get_timestamp() -> String {
-        let now = OffsetDateTime::now_local().unwrap_or(OffsetDateTime::now_utc());
+    // Messages from a log server say when they were received (replays keep the original time), otherwise it's now
+    fn get_timestamp(received_at: Option<u64>) -> String {
+        let now = received_at
+            .and_then(|ms| OffsetDateTime::from_unix_timestamp_nanos(ms as i128 * 1_000_000).ok())
+            .unwrap_or(OffsetDateTime::now_utc());
+        let now = UtcOffset::current_local_offset()
+            .map(|offset| now.to_offset(offset))
+            .unwrap_or(now);
         now.format(format_description!(
             "[hour repr:12]:[minute]:[second] [period] (UTC[offset_hour sign:mandatory])"
         ))
@@ -45,9 +53,10 @@ impl GameMessage {
     pub fn new(port: LogPort, message: SocketMessage) -> Self {
         Self {
             port,
+            timestamp: Self::get_timestamp(message.received_at),
             message,
             amount: 1,
-            timestamp: Self::get_timestamp(),
+            session: None,
         }
     }
 }
@@ -56,7 +65,8 @@ pub struct LogData {
     port: LogPort,
     messages: Vec<GameMessage>,
     indices: Vec<usize>,
-    writer: BufWriter<File>,
+    sessions: Vec<u32>,
+    commands: Option<CommandSender>,
     active_filter: Option<SocketMessageType>,
     active_search: String,
     app_handle: AppHandle,
@@ -71,39 +81,33 @@ impl LogData {
     const LOG_LIMIT_PER_SECOND: u32 = 100;
     const LOG_TIME_UNTIL_FORCED_EMIT_SEC: u64 = 1;
 
-    pub fn new(port: LogPort, handle: &AppHandle) -> Result<Self> {
-        let now = OffsetDateTime::now_utc();
-        let logs_path = get_app_path()?
-            .join("game_logs")
-            .join(
-                now.format(format_description!("[year]-[month]-[day]"))
-                    .unwrap(),
-            )
-            .join(format!(
-                "{}_{port}.log",
-                now.format(format_description!("[hour]-[minute]-[second]"))
-                    .unwrap()
-            ));
-        create_all_parents(&logs_path)?;
-        let file = File::options()
-            .read(true)
-            .append(true)
-            .create(true)
-            .open(&logs_path)
-            .map_err(|e| anyhow!("Couldn't create log file: {:?}", e))?;
-        let writer = BufWriter::new(file);
-
-        Ok(Self {
+    pub fn new(port: LogPort, handle: &AppHandle) -> Self {
+        Self {
             port,
             messages: vec![],
             indices: vec![],
-            writer,
+            sessions: vec![],
+            commands: None,
             active_filter: None,
             active_search: String::new(),
             app_handle: handle.clone(),
             message_tracker: (0, Instant::now()),
             queued_emits: vec![],
-        })
+        }
+    }
+
+    /// Let this log send commands to the game, replayed logs don't have a game to send commands to
+    pub fn with_commands(mut self, commands: CommandSender) -> Self {
+        self.commands = Some(commands);
+        self
+    }
+
+    pub fn send_command(&self, command: &SocketCommand) -> Result<usize> {
+        let commands = self
+            .commands
+            .as_ref()
+            .ok_or_else(|| anyhow!("Can't send commands to a replayed log"))?;
+        Ok(commands.send(command))
     }
 
     fn emit_update(&self) {
@@ -180,8 +184,12 @@ impl LogData {
         self.indices.clone()
     }
 
-    pub fn get_message(&self, index: usize) -> Option<&GameMessage> {
-        self.messages.get(index)
+    pub fn get_message(&self, index: usize) -> Option<GameMessage> {
+        let mut msg = self.messages.get(index)?.clone();
+        if self.sessions.len() > 1 {
+            msg.session = msg.message.client_id;
+        }
+        Some(msg)
     }
 
     pub fn get_count(&self) -> u32 {
@@ -213,8 +221,10 @@ impl LogData {
 
     pub fn take_message(&mut self, msg: SocketMessage) {
         let msg = GameMessage::new(self.port, msg);
-        if let Err(why) = write_log(&mut self.writer, &msg.message) {
-            error!("Couldn't write to log file: {}", why);
+        if let Some(client_id) = msg.message.client_id {
+            if !self.sessions.contains(&client_id) {
+                self.sessions.push(client_id);
+            }
         }
         // Reset the message tracker every second
         if self.message_tracker.1.elapsed().as_secs_f32() > 1.0 {
@@ -222,7 +232,7 @@ impl LogData {
         }
         self.message_tracker.0 = self.message_tracker.0.saturating_add(1);
         if let Some(last) = self.messages.last_mut() {
-            if last.message == msg.message {
+            if last.message.is_repeat_of(&msg.message) {
                 last.amount = last.amount.saturating_add(1);
                 // If we're getting logs too fast, queue up an emit so the UI isn't sent a bazillion updates
                 if self.message_tracker.0 >= Self::LOG_LIMIT_PER_SECOND {
@@ -265,6 +275,34 @@ impl LogData {
         }
     }
 
+    pub fn analyze(&self, local_db: &LocalDatabase) -> LogAnalysis {
+        let mut analyzer = LogAnalyzer::new(Some(local_db));
+        for msg in self.messages.iter() {
+            analyzer.add_repeated(&msg.message, msg.amount);
+        }
+        analyzer.finish()
+    }
+
+    /// Get the messages to export, either what's currently shown (with the active filter and search) or the whole log
+    pub fn export_messages(&self, filtered: bool) -> Vec<ExportedMessage> {
+        let messages: Vec<&GameMessage> = if filtered {
+            self.indices
+                .iter()
+                .filter_map(|i| self.messages.get(*i))
+                .collect()
+        } else {
+            self.messages.iter().collect()
+        };
+        messages
+            .into_iter()
+            .map(|msg| ExportedMessage {
+                display_time: msg.timestamp.clone(),
+                amount: msg.amount,
+                message: msg.message.clone(),
+            })
+            .collect()
+    }
+
     pub fn clear(&mut self) {
         // First make the UI not render any rows to avoid errors
         self.indices.clear();
@@ -275,23 +313,29 @@ impl LogData {
     }
 }
 
-impl Drop for LogData {
-    fn drop(&mut self) {
-        if let Err(why) = self.writer.flush() {
-            error!("Couldn't flush log file: {}", why);
-        }
+impl LogSink for LogData {
+    fn write(&mut self, message: &SocketMessage) -> Result<()> {
+        self.take_message(message.clone());
+        Ok(())
     }
 }
 
-pub async fn make_log_window(handle: &AppHandle) -> Result<Window> {
+/// Make a new log window, if `replay` is set the window shows the (already loaded) logs on that port
+/// instead of starting the game
+pub async fn make_log_window(handle: &AppHandle, replay: Option<LogPort>) -> Result<Window> {
     let epoch = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_millis();
+    let url = if let Some(port) = replay {
+        format!("/logs/index.html?replay={port}")
+    } else {
+        "/logs/index.html".to_string()
+    };
     let log_window = WindowBuilder::new(
         handle,
         format!("game-{epoch}"),
-        tauri::WindowUrl::App("/logs/index.html".parse()?),
+        tauri::WindowUrl::App(url.parse()?),
     );
     let window = log_window
         .center()
@@ -314,16 +358,3 @@ pub fn show_warnings(window: &Window, local_db: &LocalDatabase, config: &Config)
     }
     Ok(config)
 }
-
-pub fn write_log(writer: &mut BufWriter<File>, msg: &SocketMessage) -> Result<()> {
-    writeln!(
-        writer,
-        "[{}][{}][{:?}] {}",
-        msg.sender_name.as_ref().unwrap_or(&"Unknown".to_string()),
-        msg.sender_type.as_ref().unwrap_or(&"Unknown".to_string()),
-        msg.message_type,
-        msg.message
-    )?;
-    writer.flush()?;
-    Ok(())
-}
@@ -6,7 +6,7 @@ use std::{
 
 use anyhow::Result;
 use log::{Level, STATIC_MAX_LEVEL};
-use owmods_core::file::get_app_path;
+use owmods_core::{constants::LOGS_DIR_NAME, file::get_app_path};
 use serde::Serialize;
 use std::fs::create_dir_all;
 use tauri::{async_runtime, AppHandle, Manager};
@@ -38,7 +38,7 @@ impl Logger {
         let now = OffsetDateTime::now_utc();
         let logs_path = get_app_path()
             .expect("Couldn't Make Log File")
-            .join("logs")
+            .join(LOGS_DIR_NAME)
             .join(
                 now.format(format_description!("[year]-[month]-[day]"))
                     .unwrap(),
@@ -9,12 +9,13 @@ use commands::*;
 use fs_watch::setup_fs_watch;
 use game::LogData;
 use gui_config::GuiConfig;
-use log::{debug, error, set_boxed_logger, set_max_level, warn};
+use log::{debug, error, info, set_boxed_logger, set_max_level, warn};
 use logging::Logger;
 use owmods_core::{
     config::Config,
     db::{LocalDatabase, RemoteDatabase},
     file::get_app_path,
+    log_retention::prune_logs,
     progress::bars::ProgressBars,
     protocol::{ProtocolInstallType, ProtocolPayload},
 };
@@ -32,7 +33,8 @@ mod gui_config;
 mod logging;
 
 type StatePart<T> = Arc<TokioLock<T>>;
-type LogPort = u16;
+/// The key of a game log, live logs use the port of their log server and replays use keys above [u16::MAX]
+type LogPort = u32;
 type LogMessages = HashMap<LogPort, LogData>;
 
 fn manage<T>(obj: T) -> StatePart<T> {
@@ -48,7 +50,7 @@ pub struct State {
     config: StatePart<Config>,
     /// The current GUI configuration
     gui_config: StatePart<GuiConfig>,
-    /// A map of ports to the log messages sent to that port
+    /// A map of ports to the log messages sent to that port, replays of saved logs are in here too (see [LogPort])
     game_log: StatePart<LogMessages>,
     /// The protocol url used to invoke the program, if any. This is should only be gotten once and removed after
     protocol_url: StatePart<Option<ProtocolPayload>>,
@@ -63,6 +65,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let gui_config = GuiConfig::get().unwrap_or_default();
     let local_db = LocalDatabase::fetch(&config.owml_path).unwrap_or_default();
     let remote_db = RemoteDatabase::default();
+    let log_retention = config.log_retention.clone();
 
     tauri_plugin_deep_link::prepare("com.bwc9876.owmods-gui");
 
@@ -94,6 +97,17 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .ok();
             set_boxed_logger(Box::new(logger)).map(|_| set_max_level(log::LevelFilter::Debug))?;
 
+            // Log Cleanup
+
+            std::thread::spawn(move || match prune_logs(&log_retention) {
+                Ok(report) if !report.is_empty() => info!(
+                    "Compressed {} and deleted {} old log file(s)",
+                    report.compressed, report.deleted
+                ),
+                Ok(_) => {}
+                Err(why) => warn!("Failed to clean up old logs: {:?}", why),
+            });
+
             // Protocol Listener Setup
 
             let handle = app.handle();
@@ -140,6 +154,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             get_remote_mod,
             open_mod_folder,
             toggle_mod,
+            enable_mod,
             toggle_all,
             uninstall_mod,
             uninstall_broken_mod,
@@ -167,6 +182,10 @@ fn main() -> Result<(), Box<dyn Error>> {
             get_game_message,
             export_mods,
             import_mods,
+            preview_import_mods,
+            export_share_code,
+            preview_import_code,
+            import_mods_from_code,
             fix_mod_deps,
             db_has_issues,
             get_alert,
@@ -183,7 +202,29 @@ fn main() -> Result<(), Box<dyn Error>> {
             register_drop_handler,
             get_db_tags,
             open_mod_github,
-            force_log_update
+            force_log_update,
+            get_mod_settings,
+            set_mod_setting,
+            reset_mod_settings,
+            get_changed_settings,
+            get_settings_snapshots,
+            take_settings_snapshot,
+            restore_settings_snapshot,
+            delete_settings_snapshot,
+            export_modpack,
+            import_modpack,
+            scan_migration,
+            migrate_mods,
+            get_trashed_mods,
+            restore_trashed_mod,
+            purge_trashed_mod,
+            empty_trash,
+            scan_mods_folder,
+            clean_mods_folder,
+            open_game_log,
+            send_game_command,
+            analyze_game_log,
+            export_game_log
         ])
         .plugin(tauri_plugin_window_state::Builder::default().build())
         .run(tauri::generate_context!());
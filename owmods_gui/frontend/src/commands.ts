@@ -10,7 +10,21 @@ import {
     Alert,
     ProgressBars,
     ProgressBar,
-    Event
+    Event,
+    ModSetting,
+    ModSettingValue,
+    SettingsSnapshot,
+    ModpackManifest,
+    ImportPreview,
+    MigrationScan,
+    MigrationMode,
+    MigrationResult,
+    TrashedMod,
+    RemoveResult,
+    CleanIssue,
+    SocketCommand,
+    LogAnalysis,
+    EnableModResult
 } from "@types";
 
 type CommandInfo<P, R> = [P, R];
@@ -43,12 +57,19 @@ const commandInfo = {
         $<CommandInfo<{ uniqueName: string; enabled: boolean; recursive: boolean }, string[]>>(
             "toggle_mod"
         ),
+    enableMod: $<
+        CommandInfo<
+            { uniqueName: string; recursive: boolean; disableConflicting: boolean },
+            EnableModResult
+        >
+    >("enable_mod"),
     toggleAll: $<CommandInfo<{ enabled: boolean }, string[]>>("toggle_all"),
     openModFolder: $<ModAction>("open_mod_folder"),
     openModGithub: $<ModAction>("open_mod_github"),
     openModReadme: $<ModAction>("open_mod_readme"),
     openOwml: $<EmptyCommand>("open_owml"),
-    uninstallMod: $<ModCommand<string[]>>("uninstall_mod"),
+    uninstallMod:
+        $<CommandInfo<{ uniqueName: string; recursive: boolean }, RemoveResult>>("uninstall_mod"),
     uninstallBrokenMod: $<ActionCommand<{ modPath: string }>>("uninstall_broken_mod"),
     installMod: $<CommandInfo<{ uniqueName: string; prerelease?: boolean }, void>>("install_mod"),
     installUrl: $<ActionCommand<{ url: string }>>("install_url"),
@@ -63,6 +84,14 @@ const commandInfo = {
     logsAreActive: $<CommandInfo<{ port: number }, boolean>>("active_log"),
     startLogs: $<EmptyCommand>("start_logs"),
     runGame: $<EmptyCommand>("run_game"),
+    openGameLog: $<ActionCommand<{ path: string }>>("open_game_log"),
+    sendGameCommand: $<CommandInfo<{ port: number; command: SocketCommand }, number>>(
+        "send_game_command"
+    ),
+    analyzeGameLog: $<CommandInfo<{ port: number }, LogAnalysis>>("analyze_game_log"),
+    exportGameLog: $<ActionCommand<{ port: number; path: string; filtered: boolean }>>(
+        "export_game_log"
+    ),
     clearLogs: $<ActionCommand<{ port: number }>>("clear_logs"),
     getLogLines: $<
         CommandInfo<
@@ -76,6 +105,43 @@ const commandInfo = {
     >("get_log_lines"),
     exportMods: $<ActionCommand<{ path: string }>>("export_mods"),
     importMods: $<ActionCommand<{ path: string; disableMissing: boolean }>>("import_mods"),
+    previewImportMods: $<CommandInfo<{ path: string; disableMissing: boolean }, ImportPreview>>(
+        "preview_import_mods"
+    ),
+    exportShareCode: $<CommandInfo<{ includeVersions: boolean }, string>>("export_share_code"),
+    previewImportCode: $<CommandInfo<{ code: string; disableMissing: boolean }, ImportPreview>>(
+        "preview_import_code"
+    ),
+    importModsFromCode: $<ActionCommand<{ code: string; disableMissing: boolean }>>(
+        "import_mods_from_code"
+    ),
+    exportModpack: $<
+        CommandInfo<
+            { path: string; includeDisabled: boolean; includeOwml: boolean },
+            ModpackManifest
+        >
+    >("export_modpack"),
+    importModpack: $<CommandInfo<{ path: string; installOwml: boolean }, ModpackManifest>>(
+        "import_modpack"
+    ),
+    scanMigration: $<CommandInfo<{ path: string }, MigrationScan>>("scan_migration"),
+    migrateMods: $<
+        CommandInfo<
+            {
+                path: string;
+                uniqueNames: string[];
+                mode: MigrationMode;
+                replaceExisting: boolean;
+            },
+            MigrationResult
+        >
+    >("migrate_mods"),
+    getTrashedMods: $<GetCommand<TrashedMod[]>>("get_trashed_mods"),
+    restoreTrashedMod: $<ActionCommand<{ id: string }>>("restore_trashed_mod"),
+    purgeTrashedMod: $<ActionCommand<{ id: string }>>("purge_trashed_mod"),
+    emptyTrash: $<EmptyCommand>("empty_trash"),
+    scanModsFolder: $<GetCommand<CleanIssue[]>>("scan_mods_folder"),
+    cleanModsFolder: $<GetCommand<CleanIssue[]>>("clean_mods_folder"),
     fixDeps: $<ActionCommand<{ uniqueName: string }>>("fix_mod_deps"),
     checkDBForIssues: $<GetCommand<boolean>>("db_has_issues"),
     getAlert: $<GetCommand<Alert>>("get_alert"),
@@ -90,7 +156,27 @@ const commandInfo = {
     registerDropHandler: $<EmptyCommand>("register_drop_handler"),
     getDbTags: $<GetCommand<string[]>>("get_db_tags"),
     logError: $<ActionCommand<{ err: string }>>("log_error"),
-    forceLogUpdate: $<ActionCommand<{ port: number }>>("force_log_update")
+    forceLogUpdate: $<ActionCommand<{ port: number }>>("force_log_update"),
+    getModSettings: $<ModCommand<ModSetting[]>>("get_mod_settings"),
+    setModSetting:
+        $<ActionCommand<{ uniqueName: string; key: string; value: ModSettingValue }>>(
+            "set_mod_setting"
+        ),
+    resetModSettings: $<ActionCommand<{ uniqueName: string; keys: string[] }>>(
+        "reset_mod_settings"
+    ),
+    getChangedSettings:
+        $<CommandInfo<{ uniqueName: string; snapshotId?: string }, ModSetting[]>>(
+            "get_changed_settings"
+        ),
+    getSettingsSnapshots: $<ModCommand<SettingsSnapshot[]>>("get_settings_snapshots"),
+    takeSettingsSnapshot: $<ModAction>("take_settings_snapshot"),
+    restoreSettingsSnapshot: $<ActionCommand<{ uniqueName: string; snapshotId: string }>>(
+        "restore_settings_snapshot"
+    ),
+    deleteSettingsSnapshot: $<ActionCommand<{ uniqueName: string; snapshotId: string }>>(
+        "delete_settings_snapshot"
+    )
 };
 
 type Command = keyof typeof commandInfo;
@@ -50,6 +50,83 @@ const InnerLogApp = memo(function InnerLogApp({ port }: { port: number }) {
         setLogLines([]);
     }, [port]);
 
+    const onAnalyze = useCallback(() => {
+        commands
+            .analyzeGameLog({ port })
+            .then((analysis) => {
+                const lines = [
+                    getTranslation("LOG_ANALYSIS_COUNTS", {
+                        total: analysis.totalMessages.toString(),
+                        warnings: analysis.warnings.toString(),
+                        errors: analysis.errors.toString()
+                    })
+                ];
+                if (analysis.errors === 0 && analysis.exceptions.length === 0) {
+                    lines.push(getTranslation("LOG_ANALYSIS_CLEAN"));
+                }
+                if (analysis.firstFatal) {
+                    lines.push(
+                        "",
+                        getTranslation("LOG_ANALYSIS_FIRST_FATAL", {
+                            sender: analysis.firstFatal.senderName ?? "Unknown",
+                            message: analysis.firstFatal.message.split("\n")[0]
+                        })
+                    );
+                }
+                if (analysis.exceptions.length !== 0) {
+                    lines.push("", getTranslation("LOG_ANALYSIS_EXCEPTIONS"));
+                    for (const exception of analysis.exceptions.slice(0, 5)) {
+                        lines.push(
+                            `- ${exception.exceptionType} x${exception.count} (${exception.source})`
+                        );
+                    }
+                }
+                if (analysis.topSources.length !== 0) {
+                    lines.push("", getTranslation("LOG_ANALYSIS_TOP_SOURCES"));
+                    for (const source of analysis.topSources) {
+                        lines.push(
+                            `- ${getTranslation("LOG_ANALYSIS_SOURCE", {
+                                name: source.name,
+                                errors: source.errors.toString(),
+                                exceptions: source.exceptions.toString()
+                            })}`
+                        );
+                    }
+                }
+                if (analysis.suspects.length !== 0) {
+                    lines.push(
+                        "",
+                        getTranslation("LOG_ANALYSIS_SUSPECTS", {
+                            suspects: analysis.suspects.join(", ")
+                        })
+                    );
+                }
+                dialog.message(lines.join("\n"), { title: getTranslation("ANALYZE_LOGS") });
+            })
+            .catch(simpleOnError);
+    }, [getTranslation, port]);
+
+    const onExport = useCallback(
+        (filtered: boolean) => {
+            dialog
+                .save({
+                    title: getTranslation("EXPORT_LOGS"),
+                    defaultPath: "game-logs.txt",
+                    filters: [
+                        { name: getTranslation("LOG_EXPORT_TEXT"), extensions: ["txt", "log"] },
+                        { name: getTranslation("LOG_EXPORT_HTML"), extensions: ["html"] },
+                        { name: getTranslation("JSON_FILE"), extensions: ["json"] }
+                    ]
+                })
+                .then((path) => {
+                    if (path) {
+                        commands.exportGameLog({ port, path, filtered }).catch(simpleOnError);
+                    }
+                });
+        },
+        [getTranslation, port]
+    );
+
     useEffect(() => {
         const unsubscribe = listen("logUpdate", (portPayload) => {
             if (portPayload !== port) return;
@@ -108,6 +185,8 @@ const InnerLogApp = memo(function InnerLogApp({ port }: { port: number }) {
         >
             <LogHeader
                 onClear={onClear}
+                onAnalyze={onAnalyze}
+                onExport={onExport}
                 logsLen={logTotal}
                 activeSearch={activeSearch}
                 setActiveSearch={setActiveSearch}
@@ -1,8 +1,22 @@
 import { useGetTranslation } from "@hooks";
-import { memo } from "react";
+import { MouseEvent, memo, useCallback, useState } from "react";
 import { LogFilter } from "./LogApp";
-import { Box, IconButton, Paper, Toolbar, Typography, useTheme } from "@mui/material";
-import { DeleteSweepRounded, WarningAmberRounded } from "@mui/icons-material";
+import {
+    Box,
+    IconButton,
+    Menu,
+    MenuItem,
+    Paper,
+    Toolbar,
+    Typography,
+    useTheme
+} from "@mui/material";
+import {
+    DeleteSweepRounded,
+    QueryStatsRounded,
+    SaveAltRounded,
+    WarningAmberRounded
+} from "@mui/icons-material";
 import ODTooltip from "@components/common/ODTooltip";
 import LogFilters from "./LogFilters";
 
@@ -14,6 +28,8 @@ export interface LogHeaderProps {
     setActiveSearch: (newSearch: string) => void;
     isBehind: boolean;
     onClear: () => void;
+    onAnalyze: () => void;
+    onExport: (filtered: boolean) => void;
 }
 
 const Counter = memo(function BehindWarning(props: { isBehind: boolean; count: number }) {
@@ -43,6 +59,63 @@ const Counter = memo(function BehindWarning(props: { isBehind: boolean; count: n
     );
 });
 
+const ExportButton = memo(function ExportButton(props: {
+    onExport: (filtered: boolean) => void;
+}) {
+    const getTranslation = useGetTranslation();
+    const [anchorEl, setAnchorEl] = useState<HTMLElement | null>(null);
+
+    const onOpen = useCallback((event: MouseEvent<HTMLButtonElement>) => {
+        setAnchorEl(event.currentTarget);
+    }, []);
+
+    const onClose = useCallback(() => {
+        setAnchorEl(null);
+    }, []);
+
+    const onExport = props.onExport;
+
+    const onPick = useCallback(
+        (filtered: boolean) => {
+            setAnchorEl(null);
+            onExport(filtered);
+        },
+        [onExport]
+    );
+
+    return (
+        <>
+            <ODTooltip title={getTranslation("EXPORT_LOGS")}>
+                <IconButton
+                    onClick={onOpen}
+                    id="export-logs-button"
+                    aria-controls={anchorEl ? "export-logs-menu" : undefined}
+                    aria-haspopup="true"
+                    aria-expanded={anchorEl ? "true" : undefined}
+                >
+                    <SaveAltRounded />
+                </IconButton>
+            </ODTooltip>
+            <Menu
+                id="export-logs-menu"
+                anchorEl={anchorEl}
+                open={Boolean(anchorEl)}
+                onClose={onClose}
+                MenuListProps={{
+                    "aria-labelledby": "export-logs-button"
+                }}
+            >
+                <MenuItem onClick={() => onPick(true)}>
+                    {getTranslation("EXPORT_SHOWN_LOGS")}
+                </MenuItem>
+                <MenuItem onClick={() => onPick(false)}>
+                    {getTranslation("EXPORT_ALL_LOGS")}
+                </MenuItem>
+            </Menu>
+        </>
+    );
+});
+
 const LogHeader = memo(function LogHeader(props: LogHeaderProps) {
     const getTranslation = useGetTranslation();
 
@@ -57,6 +130,12 @@ const LogHeader = memo(function LogHeader(props: LogHeaderProps) {
                 />
                 <Counter isBehind={props.isBehind} count={props.logsLen} />
                 <Box paddingLeft={2}>
+                    <ODTooltip title={getTranslation("ANALYZE_LOGS")}>
+                        <IconButton onClick={props.onAnalyze}>
+                            <QueryStatsRounded />
+                        </IconButton>
+                    </ODTooltip>
+                    <ExportButton onExport={props.onExport} />
                     <ODTooltip title={getTranslation("CLEAR_LOGS")}>
                         <IconButton onClick={props.onClear}>
                             <DeleteSweepRounded />
@@ -1,4 +1,5 @@
 import { hooks } from "@commands";
+import { useGetTranslation } from "@hooks";
 import ODTooltip from "@components/common/ODTooltip";
 import StyledErrorBoundary from "@components/common/StyledErrorBoundary";
 import { Box, Chip, Palette, Skeleton, TableCell, Typography, useTheme } from "@mui/material";
@@ -31,6 +32,7 @@ const getColor = (palette: Palette, messageType: SocketMessageType) => {
 
 const InnerLogRow = memo(function LogRow(props: LogRowProps) {
     const theme = useTheme();
+    const getTranslation = useGetTranslation();
 
     const [status, logLine] = hooks.getLogLine(
         "logLineCountUpdate",
@@ -75,16 +77,31 @@ const InnerLogRow = memo(function LogRow(props: LogRowProps) {
                     <ODTooltip
                         title={`${logLine?.message.senderName ?? "Unknown"}::${
                             logLine?.message.senderType ?? "Unknown"
-                        }\n${logLine?.timestamp ?? ""}`}
+                        }\n${logLine?.timestamp ?? ""}${
+                            logLine?.session != null
+                                ? `\n${getTranslation("LOG_SESSION", {
+                                      session: logLine.session.toString()
+                                  })}`
+                                : ""
+                        }`}
                     >
-                        <Typography
-                            className="senderName"
-                            textOverflow="ellipsis"
-                            width="100%"
-                            overflow="hidden"
-                        >
-                            {logLine?.message.senderName ?? "Unknown"}
-                        </Typography>
+                        <Box display="flex" alignItems="center" gap={1}>
+                            {logLine?.session != null && (
+                                <Chip
+                                    size="small"
+                                    variant="outlined"
+                                    label={`#${logLine.session}`}
+                                />
+                            )}
+                            <Typography
+                                className="senderName"
+                                textOverflow="ellipsis"
+                                width="100%"
+                                overflow="hidden"
+                            >
+                                {logLine?.message.senderName ?? "Unknown"}
+                            </Typography>
+                        </Box>
                     </ODTooltip>
                 )}
             </TableCell>
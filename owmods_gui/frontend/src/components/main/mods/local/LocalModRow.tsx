@@ -4,7 +4,7 @@ import { memo, useCallback, useMemo } from "react";
 import { useGetTranslation, useUnifiedMod } from "@hooks";
 import { dialog } from "@tauri-apps/api";
 import LocalModActions from "./LocalModActions";
-import { LocalMod, UnsafeLocalMod } from "@types";
+import { LocalMod, ModConflict, ModValidationError, UnsafeLocalMod } from "@types";
 
 const getErrorLevel = (mod?: UnsafeLocalMod): "err" | "warn" | undefined => {
     if (mod?.loadState === "invalid") {
@@ -22,6 +22,16 @@ const getErrorLevel = (mod?: UnsafeLocalMod): "err" | "warn" | undefined => {
     }
 };
 
+const getErrorPayload = (e: ModValidationError): string => {
+    if (e.errorType === "InvalidManifest") {
+        const location =
+            e.payload.line != null ? ` (${e.payload.line}:${e.payload.column})` : "";
+        const hint = e.payload.hint ? ` - ${e.payload.hint}` : "";
+        return `${e.payload.message}${location}${hint}`;
+    }
+    return e.payload ?? "";
+};
+
 const getDisplayErrors = (
     getTranslation: ReturnType<typeof useGetTranslation>,
     mod?: UnsafeLocalMod
@@ -31,11 +41,13 @@ const getDisplayErrors = (
     if (mod) {
         if (mod.loadState === "invalid") {
             errors.push(
-                getTranslation(mod.mod.error.errorType, { payload: mod.mod.error.payload ?? "" })
+                getTranslation(mod.mod.error.errorType, {
+                    payload: getErrorPayload(mod.mod.error)
+                })
             );
         } else {
             errors = mod.mod.errors.map((e) =>
-                getTranslation(e.errorType, { payload: e.payload ?? "" })
+                getTranslation(e.errorType, { payload: getErrorPayload(e) })
             );
         }
     }
@@ -43,6 +55,18 @@ const getDisplayErrors = (
     return errors;
 };
 
+// The display names of the mods a mod conflicts with, falls back to the unique name for mods that failed to load
+const getConflictNames = async (conflicts: ModConflict[]): Promise<string> => {
+    const uniqueNames = [...new Set(conflicts.map((c) => c.conflictingMod))];
+    const names = await Promise.all(
+        uniqueNames.map(async (uniqueName) => {
+            const mod = await commands.getLocalMod({ uniqueName });
+            return mod?.loadState === "valid" ? mod.mod.manifest.name : uniqueName;
+        })
+    );
+    return names.sort().join(", ");
+};
+
 const canFix = (mod?: UnsafeLocalMod): boolean => {
     if (
         mod === undefined ||
@@ -113,11 +137,35 @@ const LocalModRow = memo(function LocalModRow(props: LocalModRowProps) {
                             title: getTranslation("CONFIRM")
                         }));
                 }
-                const warnings = await commands.toggleMod({
-                    uniqueName: props.uniqueName,
-                    enabled: newVal,
-                    recursive: enableDeps
-                });
+                let warnings: string[];
+                if (newVal) {
+                    const enable = (disableConflicting: boolean) =>
+                        commands.enableMod({
+                            uniqueName: props.uniqueName,
+                            recursive: enableDeps,
+                            disableConflicting
+                        });
+                    let result = await enable(false);
+                    if (!result.enabled && result.conflicts.length !== 0) {
+                        const names = await getConflictNames(result.conflicts);
+                        const disable = await dialog.ask(
+                            getTranslation("CONFLICTING_MODS_MESSAGE", { names }),
+                            {
+                                type: "warning",
+                                title: getTranslation("CONFLICTING_MODS")
+                            }
+                        );
+                        if (!disable) return;
+                        result = await enable(true);
+                    }
+                    warnings = result.showWarningsFor;
+                } else {
+                    warnings = await commands.toggleMod({
+                        uniqueName: props.uniqueName,
+                        enabled: newVal,
+                        recursive: enableDeps
+                    });
+                }
                 commands.refreshLocalDb();
                 for (const modName of warnings) {
                     dialog.message(getTranslation("PREPATCHER_WARNING", { name: modName }), {
@@ -133,16 +181,43 @@ const LocalModRow = memo(function LocalModRow(props: LocalModRowProps) {
         [autoEnableDeps, getTranslation, props.uniqueName]
     );
     const onUninstall = useCallback(() => {
-        commands.uninstallMod({ uniqueName: props.uniqueName }).then((warnings) => {
+        const task = async () => {
+            const hasDeps =
+                local?.loadState === "valid" &&
+                (local.mod.manifest.dependencies ?? []).length !== 0;
+            const recursive =
+                hasDeps &&
+                (await dialog.ask(getTranslation("UNINSTALL_DEPS_MESSAGE"), {
+                    type: "info",
+                    title: getTranslation("CONFIRM")
+                }));
+            const result = await commands.uninstallMod({
+                uniqueName: props.uniqueName,
+                recursive
+            });
             commands.refreshLocalDb();
-            for (const modName of warnings) {
+            if (result.kept.length !== 0) {
+                dialog.message(
+                    result.kept
+                        .map((dep) =>
+                            getTranslation("KEPT_DEPENDENCY", {
+                                name: dep.name,
+                                neededBy: dep.neededBy.join(", ")
+                            })
+                        )
+                        .join("\n"),
+                    { type: "info", title: getTranslation("UNINSTALL") }
+                );
+            }
+            for (const modName of result.showWarningsFor) {
                 dialog.message(getTranslation("PREPATCHER_WARNING", { name: modName }), {
                     type: "warning",
                     title: getTranslation("PREPATCHER_WARNING_TITLE", { name: modName })
                 });
             }
-        });
-    }, [getTranslation, props.uniqueName]);
+        };
+        task();
+    }, [getTranslation, local, props.uniqueName]);
     const onFix = useCallback(() => {
         const task = async () => {
             if (outdated) {
@@ -1,6 +1,6 @@
 import { useGetTranslation } from "@hooks";
 import { ModalProps } from "./About";
-import { memo, useCallback, useState } from "react";
+import { memo, useCallback, useEffect, useMemo, useState } from "react";
 import { SouthEastRounded } from "@mui/icons-material";
 import {
     MenuItem,
@@ -18,6 +18,9 @@ import {
 } from "@mui/material";
 import { OpenFileInput } from "@components/common/FileInput";
 import { commands } from "@commands";
+import { listen } from "@events";
+import { getCurrent } from "@tauri-apps/api/window";
+import { ImportAction, ImportPreview } from "@types";
 import { simpleOnError } from "../../../../errorHandling";
 
 const Import = memo(function Import({ onClick }: ModalProps) {
@@ -26,9 +29,56 @@ const Import = memo(function Import({ onClick }: ModalProps) {
     const theme = useTheme();
 
     const [filePath, setFilePath] = useState("");
+    const [shareCode, setShareCode] = useState<string | null>(null);
+    const [preview, setPreview] = useState<ImportPreview | null>(null);
     const [disableMissing, setDisableMissing] = useState(false);
 
+    useEffect(() => {
+        const unsubscribe = listen("protocolInvoke", (protocolPayload) => {
+            if (protocolPayload.installType !== "importList") return;
+            commands.checkOWML().then((valid) => {
+                if (valid) {
+                    setShareCode(protocolPayload.payload);
+                    setOpen(true);
+                    getCurrent().setFocus().catch(simpleOnError);
+                }
+            });
+        });
+        return unsubscribe;
+    }, []);
+
+    useEffect(() => {
+        if (shareCode === null) {
+            setPreview(null);
+            return;
+        }
+        commands
+            .previewImportCode({ code: shareCode, disableMissing })
+            .then(setPreview)
+            .catch((e) => {
+                simpleOnError(e);
+                setShareCode(null);
+                setOpen(false);
+            });
+    }, [shareCode, disableMissing]);
+
+    const previewLines = useMemo(() => {
+        if (preview === null) return [];
+        const names = (action: ImportAction) =>
+            preview.entries.filter((e) => e.action === action).map((e) => e.name);
+        const lines: [string, string[]][] = [
+            [getTranslation("IMPORT_WILL_INSTALL"), names(ImportAction.Install)],
+            [getTranslation("IMPORT_WILL_ENABLE"), names(ImportAction.Enable)],
+            [getTranslation("IMPORT_WILL_DISABLE"), names(ImportAction.Disable)],
+            [getTranslation("IMPORT_UNKNOWN"), names(ImportAction.Unknown)]
+        ];
+        return lines
+            .filter(([, mods]) => mods.length !== 0)
+            .map(([line, mods]) => line.replaceAll("$mods$", mods.join(", ")));
+    }, [getTranslation, preview]);
+
     const handleClick = useCallback(() => {
+        setShareCode(null);
         setOpen(true);
         onClick?.();
     }, [onClick]);
@@ -38,12 +88,13 @@ const Import = memo(function Import({ onClick }: ModalProps) {
     }, []);
 
     const onImport = useCallback(() => {
-        commands
-            .importMods({ path: filePath, disableMissing })
-            .then(() => commands.refreshLocalDb())
-            .catch(simpleOnError);
+        const task =
+            shareCode === null
+                ? commands.importMods({ path: filePath, disableMissing })
+                : commands.importModsFromCode({ code: shareCode, disableMissing });
+        task.then(() => commands.refreshLocalDb()).catch(simpleOnError);
         onClose();
-    }, [disableMissing, filePath, onClose]);
+    }, [disableMissing, filePath, shareCode, onClose]);
 
     return (
         <>
@@ -56,26 +107,44 @@ const Import = memo(function Import({ onClick }: ModalProps) {
             <Dialog open={open} onClose={onClose}>
                 <DialogTitle>{getTranslation("IMPORT_MODS")}</DialogTitle>
                 <DialogContent dividers>
-                    <DialogContentText marginBottom={theme.spacing(1)}>
-                        {getTranslation("IMPORT_MODS_EXPLANATION")}
-                    </DialogContentText>
-                    <OpenFileInput
-                        id="import-file-field"
-                        label={getTranslation("FILE_PATH")}
-                        value={filePath}
-                        onChange={setFilePath}
-                        dialogOptions={{
-                            title: getTranslation("IMPORT_MODS"),
-                            filters: [
-                                {
-                                    name: getTranslation("JSON_FILE"),
-                                    extensions: ["json"]
-                                }
-                            ],
-                            directory: false,
-                            multiple: false
-                        }}
-                    />
+                    {shareCode === null ? (
+                        <>
+                            <DialogContentText marginBottom={theme.spacing(1)}>
+                                {getTranslation("IMPORT_MODS_EXPLANATION")}
+                            </DialogContentText>
+                            <OpenFileInput
+                                id="import-file-field"
+                                label={getTranslation("FILE_PATH")}
+                                value={filePath}
+                                onChange={setFilePath}
+                                dialogOptions={{
+                                    title: getTranslation("IMPORT_MODS"),
+                                    filters: [
+                                        {
+                                            name: getTranslation("JSON_FILE"),
+                                            extensions: ["json"]
+                                        }
+                                    ],
+                                    directory: false,
+                                    multiple: false
+                                }}
+                            />
+                        </>
+                    ) : (
+                        <>
+                            <DialogContentText marginBottom={theme.spacing(1)}>
+                                {getTranslation("IMPORT_LINK_EXPLANATION")}
+                            </DialogContentText>
+                            {previewLines.length === 0 && preview !== null && (
+                                <DialogContentText>
+                                    {getTranslation("IMPORT_NO_CHANGES")}
+                                </DialogContentText>
+                            )}
+                            {previewLines.map((line) => (
+                                <DialogContentText key={line}>{line}</DialogContentText>
+                            ))}
+                        </>
+                    )}
                     <FormControlLabel
                         checked={disableMissing}
                         onChange={() => setDisableMissing(!disableMissing)}
@@ -0,0 +1,50 @@
+import { useGetTranslation } from "@hooks";
+import { ModalProps } from "./About";
+import { memo, useCallback } from "react";
+import { HistoryRounded } from "@mui/icons-material";
+import { MenuItem, ListItemIcon, ListItemText } from "@mui/material";
+import { dialog } from "@tauri-apps/api";
+import { commands } from "@commands";
+import { simpleOnError } from "../../../../errorHandling";
+
+const OpenGameLog = memo(function OpenGameLog({ onClick }: ModalProps) {
+    const getTranslation = useGetTranslation();
+
+    const onOpen = useCallback(() => {
+        dialog
+            .open({
+                title: getTranslation("OPEN_GAME_LOG"),
+                filters: [
+                    {
+                        name: getTranslation("GAME_LOG_FILE"),
+                        extensions: ["jsonl", "gz"]
+                    }
+                ],
+                directory: false,
+                multiple: false
+            })
+            .then((path) => {
+                if (typeof path === "string") {
+                    commands.openGameLog({ path }).catch(simpleOnError);
+                }
+            });
+    }, [getTranslation]);
+
+    const handleClick = useCallback(() => {
+        onOpen();
+        onClick?.();
+    }, [onClick, onOpen]);
+
+    return (
+        <>
+            <MenuItem onClick={handleClick}>
+                <ListItemIcon>
+                    <HistoryRounded fontSize="small" />
+                </ListItemIcon>
+                <ListItemText>{getTranslation("OPEN_GAME_LOG")}</ListItemText>
+            </MenuItem>
+        </>
+    );
+});
+
+export default OpenGameLog;
@@ -16,6 +16,7 @@ import { useState, MouseEvent, useCallback, lazy, Suspense } from "react";
 const About = lazy(() => import("./About"));
 const Import = lazy(() => import("./Import"));
 const Export = lazy(() => import("./Export"));
+const OpenGameLog = lazy(() => import("./OpenGameLog"));
 const InstallFrom = lazy(() => import("./InstallFrom"));
 
 const OverflowMenu = () => {
@@ -72,6 +73,7 @@ const OverflowMenu = () => {
                     <InstallFrom onClick={onClose} />
                     <Export onClick={onClose} />
                     <Import onClick={onClose} />
+                    <OpenGameLog onClick={onClose} />
                 </Suspense>
                 <MenuItem onClick={onOpenOwml}>
                     <ListItemIcon>
@@ -98,6 +98,14 @@ const SettingsForm = forwardRef(function SettingsForm(props: SettingsFormProps,
         setConfig({ ...config, [id]: newVal });
     };
 
+    const handleRetention = (id: string, newVal: string) => {
+        const value = parseInt(newVal);
+        setConfig({
+            ...config,
+            logRetention: { ...config.logRetention, [id]: isNaN(value) ? 0 : Math.max(value, 0) }
+        });
+    };
+
     const handleOwml = (id: string, newVal: string | boolean) => {
         setOwmlConfig({ ...owmlConfig, [id]: newVal });
     };
@@ -229,6 +237,44 @@ const SettingsForm = forwardRef(function SettingsForm(props: SettingsFormProps,
                 id="alertUrl"
                 tooltip={getTranslation("TOOLTIP_ALERT_URL")}
             />
+            <SettingsText
+                onChange={(id, newVal) => {
+                    const days = parseInt(newVal);
+                    setConfig({ ...config, [id]: isNaN(days) ? 0 : Math.max(days, 0) });
+                }}
+                value={config.trashMaxAgeDays.toString()}
+                label={getTranslation("TRASH_MAX_AGE_DAYS")}
+                id="trashMaxAgeDays"
+                tooltip={getTranslation("TOOLTIP_TRASH_MAX_AGE_DAYS")}
+            />
+            <SettingsText
+                onChange={handleRetention}
+                value={config.logRetention.maxAgeDays.toString()}
+                label={getTranslation("LOG_MAX_AGE_DAYS")}
+                id="maxAgeDays"
+                tooltip={getTranslation("TOOLTIP_LOG_MAX_AGE_DAYS")}
+            />
+            <SettingsText
+                onChange={handleRetention}
+                value={config.logRetention.maxTotalSizeMb.toString()}
+                label={getTranslation("LOG_MAX_TOTAL_SIZE_MB")}
+                id="maxTotalSizeMb"
+                tooltip={getTranslation("TOOLTIP_LOG_MAX_TOTAL_SIZE_MB")}
+            />
+            <SettingsText
+                onChange={handleRetention}
+                value={config.logRetention.maxFiles.toString()}
+                label={getTranslation("LOG_MAX_FILES")}
+                id="maxFiles"
+                tooltip={getTranslation("TOOLTIP_LOG_MAX_FILES")}
+            />
+            <SettingsText
+                onChange={handleRetention}
+                value={config.logRetention.compressAfterDays.toString()}
+                label={getTranslation("LOG_COMPRESS_AFTER_DAYS")}
+                id="compressAfterDays"
+                tooltip={getTranslation("TOOLTIP_LOG_COMPRESS_AFTER_DAYS")}
+            />
             <SettingsFolder
                 onChange={handleConf}
                 value={config.owmlPath}
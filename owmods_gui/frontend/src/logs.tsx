@@ -9,18 +9,30 @@ let port = 0;
 
 const LogApp = React.lazy(() => import("@components/logs/LogApp"));
 
-listen("gameStart", (inPort) => {
-    if (port !== 0) return;
-
-    port = inPort;
-
+const render = (logPort: number) => {
     ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
         <React.StrictMode>
             <ErrorBoundary fallbackRender={basicFallbackRender} onError={onError}>
-                <LogApp port={port} />
+                <LogApp port={logPort} />
             </ErrorBoundary>
         </React.StrictMode>
     );
-});
+};
+
+// Replayed sessions are already loaded by the time the window opens, so we don't start the game
+const replayPort = new URLSearchParams(window.location.search).get("replay");
+
+if (replayPort !== null) {
+    port = parseInt(replayPort);
+    render(port);
+} else {
+    listen("gameStart", (inPort) => {
+        if (port !== 0) return;
+
+        port = inPort;
+
+        render(port);
+    });
 
-commands.runGame().catch(simpleOnError);
+    commands.runGame().catch(simpleOnError);
+}
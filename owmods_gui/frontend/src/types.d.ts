@@ -18,6 +18,18 @@ export interface Alert {
     urlLabel?: string;
 }
 
+/** How long to keep manager and game logs, each limit applies to the `logs` and `game_logs` folders separately */
+export interface LogRetentionPolicy {
+    /** Delete log files older than this many days, `0` keeps them forever */
+    maxAgeDays: number;
+    /** Delete the oldest log files once the folder takes up more than this many megabytes, `0` for no limit */
+    maxTotalSizeMb: number;
+    /** Only keep this many of the newest log files, `0` for no limit */
+    maxFiles: number;
+    /** Compress log files (with gzip) once they're older than this many days, `0` never compresses them */
+    compressAfterDays: number;
+}
+
 /** Represents the core config, contains critical info needed by the core API */
 export interface Config {
     /** The path to the OWML install, defaults to `~/.local/share/OuterWildsModManager/OWML` */
@@ -28,12 +40,32 @@ export interface Config {
     alertUrl: string;
     /** The mod warnings that have been shown to the user */
     viewedAlerts: string[];
+    /** How many days uninstalled mods are kept in the trash before they're deleted for good, `0` keeps them forever */
+    trashMaxAgeDays: number;
+    /** How long to keep manager and game logs, see [crate::log_retention] */
+    logRetention: LogRetentionPolicy;
+}
+
+/** Details on why a mod's `manifest.json` couldn't be loaded */
+export interface ManifestError {
+    /** The path to the manifest that failed to load */
+    file: string;
+    /** What went wrong, without any location info */
+    message: string;
+    /** The line the error occurred on (starting at 1), if the error came from parsing the JSON */
+    line?: number;
+    /** The column the error occurred on (starting at 1), if the error came from parsing the JSON */
+    column?: number;
+    /** The contents of the line the error occurred on */
+    snippet?: string;
+    /** A suggestion on how to fix the error, if we can guess what's wrong */
+    hint?: string;
 }
 
 /** Represents an error with a [LocalMod] */
 export type ModValidationError =
-    /** The mod's manifest was invalid, contains the error encountered when loading it */
-    | { errorType: "InvalidManifest"; payload: string }
+    /** The mod's manifest was invalid, contains details on the error encountered when loading it */
+    | { errorType: "InvalidManifest"; payload: ManifestError }
     /** The mod is missing a dependency that needs to be installed, contains the unique name of the missing dep */
     | { errorType: "MissingDep"; payload: string }
     /** A dependency of the mod is disabled, contains the unique name of the disabled dep */
@@ -45,7 +77,11 @@ export type ModValidationError =
     /** There's another mod already in the DB with this mod's unique name, contains the path of the other mod that has the same unique name */
     | { errorType: "DuplicateMod"; payload: string }
     /** The mod is outdated, contains the newest version */
-    | { errorType: "Outdated"; payload: string };
+    | { errorType: "Outdated"; payload: string }
+    /** The prepatcher the mod specifies in its `manifest.json` doesn't exist, contains the path to the patcher specified by the mod */
+    | { errorType: "MissingPatcher"; payload: string }
+    /** A path in the mod's `pathsToPreserve` is absolute or leads outside the mod's folder, contains the offending path */
+    | { errorType: "InvalidPreservePath"; payload: string };
 
 /** Represents a warning a mod wants to show to the user on start */
 export interface ModWarning {
@@ -243,6 +279,8 @@ export enum ProtocolInstallType {
     InstallPreRelease = "installPreRelease",
     /** Install a mod from a zip file */
     InstallZip = "installZip",
+    /** Import a list of mods from a share code, see [crate::io::decode_share_code] */
+    ImportList = "importList",
     /** Unknown install type, means the protocol link was invalid and therefore should be ignored */
     Unknown = "unknown"
 }
@@ -260,6 +298,7 @@ export enum ProtocolInstallType {
  * - owmods://install-url/https://example.com/Mod.zip
  * - owmods://install-zip//home/user/Downloads/Mod.zip
  * - owmods://install-prerelease/Raicuparta.NomaiVR
+ * - owmods://import/1cypPtrQwN9MLycxNDU4sSy0CAA
  */
 export interface ProtocolPayload {
     /** The type of install that should be done */
@@ -297,6 +336,435 @@ export interface SocketMessage {
      * Note that the message sent calls this `type` so we have to alias it because type is a reserved keyword
      */
     messageType: SocketMessageType;
+    /**
+     * The id of the connection this message came from, set by the [LogServer] so messages from different
+     * game instances (or a game that was relaunched) can be told apart. Ids start at 1 and are unique per server,
+     * clients get one when they send their first log so clients that only send commands don't have one.
+     */
+    clientId?: number;
+    /**
+     * When the sender says it sent this message, in milliseconds since the Unix epoch.
+     * Senders don't have to set this, and values that aren't a number of milliseconds are ignored.
+     */
+    timestamp?: number;
+    /** When the [LogServer] received this message, in milliseconds since the Unix epoch */
+    receivedAt?: number;
+    /**
+     * The order the [LogServer] received this message in, starting at 1. Unlike [SocketMessage::received_at]
+     * this is unique per server, so it can order messages that were received in the same millisecond.
+     */
+    sequence?: number;
+}
+
+/**
+ * A command sent to the game through a [LogServer], each command is sent as a single line of JSON.
+ *
+ * The JSON has a `command` field with the name of the command (camelCase), and a `payload` field for commands that take one.
+ * For example `{"command":"ping"}` or `{"command":"console","payload":"help"}`.
+ *
+ * A log server sends these to the games connected to it, and also accepts them from other clients,
+ * in which case it passes them on to every connected game, see [send_command].
+ */
+export type SocketCommand =
+    /** Check that the game is still responding, the game should reply with a log message */
+    | { command: "ping"; payload?: undefined }
+    /** Ask OWML to reload the configs of every mod */
+    | { command: "reloadConfigs"; payload?: undefined }
+    /** Run a console command, what this does is up to OWML and the mods running */
+    | { command: "console"; payload: string };
+
+/** A mod in the dependency graph */
+export interface ModGraphNode {
+    /** The unique name of the mod */
+    uniqueName: string;
+    /** The display name of the mod, this is the unique name if the mod isn't installed and wasn't found in the remote database */
+    name: string;
+    /** The installed version of the mod, or the latest version in the remote database if it isn't installed */
+    version?: string;
+    /** Whether the mod is installed */
+    installed: boolean;
+    /** Whether the mod is enabled, always false if the mod isn't installed */
+    enabled: boolean;
+}
+
+/** The kind of relation an edge represents */
+export enum ModGraphEdgeKind {
+    /** `from` depends on `to` */
+    Dependency = "Dependency",
+    /** `from` declares it conflicts with `to` */
+    Conflict = "Conflict"
+}
+
+/** A relation between two mods in the dependency graph */
+export interface ModGraphEdge {
+    /** The unique name of the mod that declares this relation */
+    from: string;
+    /** The unique name of the mod the relation points to */
+    to: string;
+    /** What kind of relation this is */
+    kind: ModGraphEdgeKind;
+}
+
+/** The dependency and conflict graph of installed mods */
+export interface ModGraph {
+    /** All mods in the graph, sorted by unique name */
+    nodes: ModGraphNode[];
+    /** All dependencies and conflicts between mods in the graph */
+    edges: ModGraphEdge[];
+}
+
+/** The result of checking a mod's files against what was installed */
+export interface ModIntegrityReport {
+    /** The unique name of the mod this report is for */
+    uniqueName: string;
+    /** Files that were installed but aren't there anymore */
+    missing: string[];
+    /** Files that are there but have changed since install */
+    modified: string[];
+    /** Files that weren't installed with the mod */
+    extra: string[];
+}
+
+/** A conflict between a mod we want to enable and a mod that's already enabled */
+export interface ModConflict {
+    /** The unique name of the mod being enabled (this can be a dependency of the mod the user asked to enable) */
+    uniqueName: string;
+    /** The unique name of the enabled mod it conflicts with */
+    conflictingMod: string;
+    /** Whether the conflict is declared by the already enabled mod instead of the mod being enabled */
+    reverse: boolean;
+}
+
+/** The result of trying to enable a mod with [enable_mod] */
+export interface EnableModResult {
+    /** Whether the mod was enabled, this is false if there were conflicts and we weren't allowed to disable the conflicting mods */
+    enabled: boolean;
+    /** The conflicts that were found */
+    conflicts: ModConflict[];
+    /** The unique names of mods that were disabled to resolve the conflicts */
+    disabled: string[];
+    /**
+     * The unique names of disabled mods that use pre patchers,
+     * and therefore **should alert the user to check the mod's README for instructions on how to fully disable it**.
+     */
+    showWarningsFor: string[];
+}
+
+/** A mod included in a modpack */
+export interface ModpackMod {
+    /** The unique name of the mod */
+    uniqueName: string;
+    /** The name of the mod */
+    name: string;
+    /** The version of the mod that's in the modpack */
+    version: string;
+    /** Whether the mod should be enabled after importing */
+    enabled: boolean;
+}
+
+/**
+ * The manifest of a modpack, this is stored at the root of the archive as `modpack.json`.
+ * Each mod's files are stored as `mods/{unique_name}.zip`, and OWML (if included) as `OWML.zip`.
+ */
+export interface ModpackManifest {
+    /** The mods in the modpack */
+    mods: ModpackMod[];
+    /** The version of OWML in the modpack, [None] if OWML isn't included */
+    owmlVersion?: string;
+}
+
+/** The type of a mod setting, inferred from the mod's `default-config.json` */
+export enum ModSettingType {
+    /** An on/off switch */
+    Toggle = "Toggle",
+    /** A number that must be between a minimum and a maximum */
+    Slider = "Slider",
+    /** One of a set of options */
+    Selector = "Selector",
+    /** Free-form text */
+    Text = "Text",
+    /** Any number */
+    Number = "Number"
+}
+
+/** The value of a mod setting */
+export type ModSettingValue =
+    /** The value of a [ModSettingType::Toggle] */
+    | { type: "Bool"; value: boolean }
+    /** The value of a [ModSettingType::Slider] or [ModSettingType::Number] */
+    | { type: "Number"; value: number }
+    /** The value of a [ModSettingType::Selector] or [ModSettingType::Text] */
+    | { type: "Text"; value: string };
+
+/** A single setting of a mod, along with its current value */
+export interface ModSetting {
+    /** The key of the setting in the mod's config */
+    key: string;
+    /** The title the mod gives this setting, if any */
+    title?: string;
+    /** The tooltip the mod gives this setting, if any */
+    tooltip?: string;
+    /** The type of the setting */
+    settingType: ModSettingType;
+    /** The minimum value for sliders */
+    min?: number;
+    /** The maximum value for sliders */
+    max?: number;
+    /** Whether this setting only accepts whole numbers, inferred from its default value */
+    integer: boolean;
+    /** The possible values for selectors */
+    options?: string[];
+    /** The value in the mod's `default-config.json` */
+    defaultValue: ModSettingValue;
+    /** The current value in the mod's `config.json` */
+    value: ModSettingValue;
+}
+
+/** Why a snapshot was taken */
+export enum SnapshotReason {
+    /** The user asked for it, these are never deleted automatically */
+    Manual = "Manual",
+    /** Before the mod was updated */
+    Update = "Update",
+    /** Before the mod was enabled or disabled */
+    Toggle = "Toggle",
+    /** Before the mod's settings were reset */
+    Reset = "Reset",
+    /** Before another snapshot was restored */
+    Restore = "Restore",
+    /** Before a modpack was imported */
+    Import = "Import"
+}
+
+/** A copy of a mod's `config.json` at some point in time */
+export interface SettingsSnapshot {
+    /** The ID of the snapshot, this is the time it was taken in milliseconds since the Unix epoch */
+    id: string;
+    /** The unique name of the mod this snapshot is of */
+    uniqueName: string;
+    /** Why the snapshot was taken */
+    reason: SnapshotReason;
+    /** Whether the mod was enabled */
+    enabled: boolean;
+}
+
+/** A mod in a list of mods to import */
+export interface ImportEntry {
+    /** The unique name of the mod */
+    uniqueName: string;
+    /** The version of the mod the list was made with, if the list has versions */
+    version?: string;
+}
+
+/** The format to export a list of mods in, see [export_mods] */
+export enum ExportFormat {
+    /** A pretty-printed JSON array that can be saved to a file */
+    Json = "Json",
+    /** A share code, see [encode_share_code] */
+    ShareCode = "ShareCode"
+}
+
+/** What importing a list would do to a mod */
+export enum ImportAction {
+    /** The mod isn't installed and will be installed from the database */
+    Install = "Install",
+    /** The mod is installed but disabled, it will be enabled */
+    Enable = "Enable",
+    /** The mod is enabled but not in the list, it will be disabled */
+    Disable = "Disable",
+    /** Nothing will change for this mod */
+    Untouched = "Untouched",
+    /** The mod isn't installed or in the database, it'll be skipped */
+    Unknown = "Unknown"
+}
+
+/** How importing a list would affect a single mod */
+export interface ImportPreviewEntry {
+    /** The unique name of the mod */
+    uniqueName: string;
+    /** The name of the mod, this is the unique name if the mod isn't installed or in the database */
+    name: string;
+    /** What will happen to the mod */
+    action: ImportAction;
+    /** Whether the mod is in the list being imported */
+    inList: boolean;
+    /** The version that's currently installed, if any */
+    installedVersion?: string;
+    /** The version the list was made with, if the list has versions */
+    listVersion?: string;
+    /** The version in the database, if the mod is in it */
+    remoteVersion?: string;
+}
+
+/** A preview of what importing a list of mods would do, see [preview_import] */
+export interface ImportPreview {
+    /** Every mod in the list and every installed mod, sorted by action then name */
+    entries: ImportPreviewEntry[];
+    /** The unique names of mods in the list that aren't installed or in the database */
+    unknown: string[];
+}
+
+/** A mod found in a folder we're migrating from */
+export interface MigrationCandidate {
+    /** The unique name of the mod */
+    uniqueName: string;
+    /** The name of the mod */
+    name: string;
+    /** The version of the mod in the folder we're migrating from */
+    version: string;
+    /** The path to the mod's folder */
+    sourcePath: string;
+    /** Whether the mod is enabled, this is kept when adopting the mod */
+    enabled: boolean;
+    /** The version of the mod in the database, [None] if the mod isn't in the database */
+    remoteVersion?: string;
+    /** The version of the mod that's already installed, if any */
+    installedVersion?: string;
+}
+
+/** The result of scanning a folder to migrate from, see [scan_migration_source] */
+export interface MigrationScan {
+    /** The `Mods` folder that was scanned */
+    modsPath: string;
+    /** The mods that were found, sorted by name */
+    mods: MigrationCandidate[];
+    /** Folders that have a manifest we couldn't read (or with an invalid unique name), or a duplicate of a mod that was already found */
+    invalid: string[];
+}
+
+/** How to adopt mods when migrating */
+export enum MigrationMode {
+    /** Copy the mods, leaving the source folder untouched */
+    Copy = "Copy",
+    /** Move the mods, removing them from the source folder */
+    Move = "Move"
+}
+
+/** The result of adopting mods, see [adopt_mods] */
+export interface MigrationResult {
+    /** The unique names of the mods that were adopted */
+    adopted: string[];
+    /** The unique names of the mods that were skipped because they're already installed */
+    skipped: string[];
+}
+
+/**
+ * A mod that was uninstalled and moved to the trash, it can be restored until it's purged.
+ * Leftovers removed by [crate::clean::clean_issue] are kept here too, see [TrashedMod::leftover].
+ */
+export interface TrashedMod {
+    /** The ID of this entry, this is the time the mod was removed in milliseconds since the Unix epoch */
+    id: string;
+    /** The unique name of the mod */
+    uniqueName: string;
+    /** The name of the mod */
+    name: string;
+    /** The version of the mod that was removed */
+    version: string;
+    /** The path the mod was in before it was removed */
+    originalPath: string;
+    /**
+     * Whether this is a leftover file or folder rather than a mod.
+     * For leftovers the unique name and name are the path relative to the `Mods` folder and there's no version.
+     */
+    leftover: boolean;
+}
+
+/** A dependency that was kept when recursively uninstalling a mod because other installed mods still need it */
+export interface KeptDependency {
+    /** The unique name of the dependency */
+    uniqueName: string;
+    /** The name of the dependency */
+    name: string;
+    /** The names of the installed mods that still depend on it */
+    neededBy: string[];
+}
+
+/** The outcome of uninstalling a mod */
+export interface RemoveResult {
+    /** The unique names of the mods that were uninstalled, the mod that was asked to be removed comes first */
+    removed: string[];
+    /** Dependencies that were left installed because other mods need them */
+    kept: KeptDependency[];
+    /**
+     * The names of removed mods that use pre-patchers
+     * and thus **should have a warning shown to the user telling them to check the mod's README for instructions**
+     */
+    showWarningsFor: string[];
+}
+
+/** What kind of leftover a CleanIssue is */
+export enum CleanIssueKind {
+    /** A folder that doesn't have a manifest in it, OWML won't load anything from it */
+    OrphanFolder = "OrphanFolder",
+    /** A folder that doesn't have any files in it */
+    EmptyFolder = "EmptyFolder",
+    /** A mod with the same unique name as another mod that's already loaded */
+    Duplicate = "Duplicate",
+    /** An archive that wasn't installed with any mod, usually left over from installing a mod manually */
+    StrayArchive = "StrayArchive",
+    /** A large file that wasn't installed with any mod */
+    LargeFile = "LargeFile"
+}
+
+/** Something in the `Mods` folder that probably shouldn't be there */
+export interface CleanIssue {
+    /** What kind of leftover this is */
+    kind: CleanIssueKind;
+    /** The absolute path to the file or folder */
+    path: string;
+    /** The path relative to the `Mods` folder, good for display */
+    displayPath: string;
+    /** The size of the file or folder in KiB */
+    sizeKb: number;
+    /** For duplicates, the unique name of the mod that's duplicated */
+    uniqueName?: string;
+    /** For duplicates, the path of the mod that's loaded instead */
+    otherPath?: string;
+}
+
+/** An exception found in the logs, repeats of the same exception (same type, source, and first stack frame) are counted together */
+export interface LoggedException {
+    /** The full name of the exception type, ex: `System.NullReferenceException` */
+    exceptionType: string;
+    /** The exception's message, from the first time it was logged */
+    message: string;
+    /** The stack frames, from the first time it was logged */
+    frames: string[];
+    /** What we think caused the exception, a mod's unique name if we could figure it out, otherwise the sender name */
+    source: string;
+    /** How many times this exception was logged */
+    count: number;
+    /** Whether this exception was ever logged as fatal */
+    fatal: boolean;
+}
+
+/** Something that logged errors */
+export interface ErrorSource {
+    /** A mod's unique name if we could figure it out, otherwise the sender name */
+    name: string;
+    /** How many error and fatal messages this source logged */
+    errors: number;
+    /** How many of those errors were exceptions */
+    exceptions: number;
+}
+
+/** A summary of a game session's logs, see [LogAnalyzer] */
+export interface LogAnalysis {
+    /** The total amount of messages */
+    totalMessages: number;
+    /** The amount of warnings */
+    warnings: number;
+    /** The amount of errors (including fatal errors) */
+    errors: number;
+    /** The first fatal message, this is usually what crashed the game */
+    firstFatal?: SocketMessage;
+    /** Exceptions found, most logged first */
+    exceptions: LoggedException[];
+    /** What logged the most errors, most errors first */
+    topSources: ErrorSource[];
+    /** Unique names of mods that are most likely causing problems, most likely first */
+    suspects: string[];
 }
 
 export interface LogLineCountUpdatePayload {
@@ -314,6 +782,11 @@ export interface GameMessage {
     message: SocketMessage;
     amount: number;
     timestamp: string;
+    /**
+     * The client this message came from, only set once more than one client has connected to this log
+     * so the UI can tell sessions apart (for example when the game restarts after a crash)
+     */
+    session?: number;
 }
 
 export enum Language {
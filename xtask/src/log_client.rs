@@ -1,21 +1,48 @@
-use std::{io::Write, net::TcpStream};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::Result;
 
+fn make_message(message: &str) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    format!(
+        "{{\"type\": 0, \"message\": \"{}\", \"senderName\": \"xtask\", \"senderType\": \"log_client\", \"timestamp\": {}}}\n",
+        message, timestamp
+    )
+}
+
 pub fn log_client() -> Result<()> {
     let port = std::env::args().nth(2).expect("Missing Port");
     let mut stream = TcpStream::connect(format!("127.0.0.1:{port}"))?;
+    // Print any commands the server sends us, and answer pings like the game would
+    let mut reply_stream = stream.try_clone()?;
+    std::thread::spawn(move || {
+        let reader = BufReader::new(reply_stream.try_clone().unwrap());
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            println!("Received command: {line}");
+            if line.contains("\"command\":\"ping\"") {
+                reply_stream
+                    .write_all(make_message("Pong!").as_bytes())
+                    .ok();
+            }
+        }
+    });
     loop {
         let mut input = String::new();
         let user_entered = std::io::stdin().read_line(&mut input)?;
         if user_entered == 0 {
             break;
         }
-        let message = format!(
-            "{{\"type\": 0, \"message\": \"{}\", \"senderName\": \"xtask\", \"senderType\": \"log_client\"}}\n",
-            input.trim()
-        );
-        stream.write_all(message.as_bytes())?;
+        stream.write_all(make_message(input.trim()).as_bytes())?;
     }
     Ok(())
 }